@@ -0,0 +1,52 @@
+//! Benchmarks for updating large numbers of [`Sun`] entities every frame
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use kj_bevy_realistic_sun::*;
+
+
+/// Builds an app with `count` suns, ready to be updated
+fn sun_app(count: usize) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+    app.insert_resource(Environment::default()
+        .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+        .with_latitude_deg(40.0));
+    app.world_mut().spawn_batch((0..count).map(|_| Sun));
+    app.update();
+    app
+}
+
+/// Moves the sun a little and updates every light, the cost of a running clock
+fn moving_sun(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("moving_sun");
+    for count in [1, 100, 10_000] {
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |bencher, &count| {
+            let mut app = sun_app(count);
+            bencher.iter(|| {
+                app.world_mut().resource_mut::<Environment>().time_of_day += 0.001;
+                app.update();
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Updates without touching the environment, the cost of a paused clock
+fn paused_sun(criterion: &mut Criterion) {
+    let mut app = sun_app(10_000);
+    criterion.bench_function("paused_sun/10000", |bencher| bencher.iter(|| app.update()));
+}
+
+/// Works out the sun direction on its own, without any ECS overhead
+fn sun_direction(criterion: &mut Criterion) {
+    let environment = Environment::default()
+        .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+        .with_latitude_deg(40.0)
+        .with_hours_since_noon(-2.0);
+    criterion.bench_function("sun_direction", |bencher| {
+        bencher.iter(|| std::hint::black_box(environment).sun_direction())
+    });
+}
+
+criterion_group!(benches, moving_sun, paused_sun, sun_direction);
+criterion_main!(benches);
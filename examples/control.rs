@@ -20,11 +20,10 @@
 //! Shift       | Increase speed of environment changes
 //! Ctrl        | Decrease speed of environment changes
 
-use std::f32::consts::{PI, TAU};
 use bevy::prelude::*;
 use bevy::{
     camera::Exposure, core_pipeline::tonemapping::Tonemapping,
-    light::{AtmosphereEnvironmentMapLight, SunDisk},
+    light::AtmosphereEnvironmentMapLight,
     pbr::Atmosphere, post_process::bloom::Bloom, render::view::Hdr,
 };
 use kj_bevy_realistic_sun::*;
@@ -47,6 +46,8 @@ const FLOOR_HEIGHT: f32 = -0.6;
 const OBJECT_SIZE: f32 = 0.7;
 /// Spacing between objects
 const OBJECT_SPACING: f32 = 1.0;
+/// Radius of the sky grid drawn around the objects
+const SKY_GRID_RADIUS: f32 = 3.0;
 /// Background color for the UI panes
 const UI_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
 /// Size of the font in the UI panes
@@ -69,7 +70,7 @@ struct CameraBase;
 
 fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, RealisticSunDirectionPlugin))
+        .add_plugins((DefaultPlugins, RealisticSunDirectionPlugin::default()))
         .add_systems(Startup, (spawn_camera, spawn_floor, spawn_objects, spawn_sun, spawn_ui))
         .add_systems(Update, (
             draw_gizmos, process_camera_input, process_sun_input,
@@ -79,8 +80,9 @@ fn main() {
 }
 
 /// Draw gizmos to orient the player in space
-fn draw_gizmos(mut gizmos: Gizmos){
+fn draw_gizmos(mut gizmos: Gizmos, environment: Res<Environment>){
     gizmos.axes(Transform::default(), OBJECT_SIZE);
+    draw_sky_grid(&mut gizmos, &environment, Vec3::ZERO, SKY_GRID_RADIUS);
 }
 
 /// Takes player input for the camera and updates the camera position accordingly
@@ -139,12 +141,7 @@ fn process_sun_input(
     environment.latitude += latitude_input * speed * delta;
     environment.axial_tilt += axial_tilt_input * speed * delta;
     // clamp/loop environment values as needed
-    if environment.time_of_day > PI { environment.time_of_day -= TAU; }
-    if environment.time_of_day < -PI { environment.time_of_day += TAU; }
-    if environment.time_of_year > PI { environment.time_of_year -= TAU; }
-    if environment.time_of_year < -PI { environment.time_of_year += TAU; }
-    environment.latitude = environment.latitude.clamp(-PI/2.0, PI/2.0);
-    environment.axial_tilt = environment.axial_tilt.clamp(-PI/2.0, PI/2.0);
+    environment.normalize();
 }
 
 /// Updates UI labels marked with [`EnvironmentOutputLabel`]
@@ -153,15 +150,16 @@ fn update_labels(
 ){
     for (mut text, label) in &mut labels {
         text.0 = match label {
-            EnvironmentOutputLabel::TimeOfDay => {
-                let hours = environment.time_of_day * conversion::RAD_TO_HOURS + 12.0;
-                let seconds = hours * 60.0 * 60.0;
-                let total_seconds_int = seconds.round() as isize;
-                let hours_int = total_seconds_int / (60 * 60);
-                let minutes_int = total_seconds_int / 60 % 60;
-                format!("{:.3} rad ({:02}:{:02})", environment.time_of_day, hours_int, minutes_int)
-            },
-            EnvironmentOutputLabel::TimeOfYear => format!("{:.3} rad", environment.time_of_year),
+            EnvironmentOutputLabel::TimeOfDay => format!(
+                "{:.3} rad ({})",
+                environment.time_of_day,
+                ui::clock(&environment),
+            ),
+            EnvironmentOutputLabel::TimeOfYear => format!(
+                "{:.3} rad ({})",
+                environment.time_of_year,
+                ui::season_name(environment.season()),
+            ),
             EnvironmentOutputLabel::Latitude => format!(
                 "{:.3} rad ({:.1} deg)",
                 environment.latitude,
@@ -254,15 +252,7 @@ fn spawn_objects(
 
 /// Spawns the sun light entity
 fn spawn_sun(mut commands: Commands){
-    commands.spawn((
-        DirectionalLight{
-            illuminance: light_consts::lux::DIRECT_SUNLIGHT,
-            shadows_enabled: true,
-            ..default()
-        },
-        SunDisk::EARTH,
-        Sun,
-    ));
+    commands.spawn_realistic_sun(SunConfig::default());
 }
 
 /// Spawns the UI elements
@@ -310,6 +300,8 @@ fn spawn_ui(mut commands: Commands){
             (Text::new("Speed/slow sun change input: Shift/Ctrl"), font.clone()),
         ],
     ));
+    // top pane - time, season and sun position
+    commands.spawn(ui::sun_info_overlay());
 }
 
 fn sun_control_row_bundle<S>(
@@ -1,5 +1,4 @@
 //! Demonstrates the bare minimum needed to get the realistic sun direction working in Bevy
-use std::f32::consts::TAU;
 use bevy::prelude::*;
 use kj_bevy_realistic_sun::*;
 
@@ -10,10 +9,10 @@ const DAY_LENGTH: f32 = 20.0;
 
 fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, RealisticSunDirectionPlugin))
+        .add_plugins((DefaultPlugins, RealisticSunDirectionPlugin::default()))
         .add_systems(Startup, setup_essentials) // spawns the essentials
         .add_systems(Startup, setup_secondary) // spawns other example specific entities
-        .add_systems(Update, update_time_of_day)
+        .add_systems(Update, draw_sky) // draws the sky grid to show where the sun is heading
         .run();
 }
 
@@ -24,14 +23,12 @@ fn setup_essentials(mut commands: Commands){
         .with_latitude_deg(80.0)
         .with_axial_tilt(Environment::AXIAL_TILT_EARTH);
     commands.insert_resource(environment);
+    // advance time automatically
+    commands.insert_resource(TimeFlow::default().with_seconds_per_day(DAY_LENGTH));
     // spawn sun light
-    commands.spawn((
-        DirectionalLight{
-            shadows_enabled: true,
-            ..default()
-        },
-        Sun,
-    ));
+    commands.spawn_realistic_sun(SunConfig::default());
+    // show the time and where the sun is
+    commands.spawn(ui::sun_info_overlay());
     // spawn camera
     commands.spawn((
         Camera3d::default(),
@@ -69,10 +66,7 @@ fn setup_secondary(
     ));
 }
 
-fn update_time_of_day(
-    mut environment: ResMut<Environment>,
-    time: Res<Time>,
-){
-    let time_step = TAU / DAY_LENGTH;
-    environment.time_of_day += time_step * time.delta_secs()
+/// Draws the sky grid and the solstice paths around the torus
+fn draw_sky(mut gizmos: Gizmos, environment: Res<Environment>){
+    draw_sky_grid(&mut gizmos, &environment, Vec3::ZERO, 5.0);
 }
@@ -0,0 +1,225 @@
+//! Contains the [`SunAlignment`] component for detecting when the sun lines up with a direction
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::Environment;
+use crate::conversion::*;
+
+
+/// Watches for the sun lining up with a world space direction, like the axis of a temple corridor
+/// 
+/// A [`SunAligned`] event is triggered on the entity when the direction *towards* the sun comes
+/// within [`tolerance`](SunAlignment::tolerance) of [`direction`](SunAlignment::direction), and a
+/// [`SunAlignmentLost`] event is triggered when it moves back out.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{SunAligned, SunAlignment};
+/// fn spawn_temple(mut commands: Commands) {
+///     commands.spawn(
+///         // the corridor looks out to the south east, just above the horizon
+///         SunAlignment::new(Vec3::new(1.0, 0.05, 1.0)).with_tolerance_deg(2.0)
+///     ).observe(|aligned: On<SunAligned>| {
+///         println!("The sun shines down the corridor! ({} rad off)", aligned.angle);
+///     });
+/// }
+/// ```
+/// 
+/// Because the alignment is checked against the sun model itself, it happens at whatever time and
+/// date the [`Environment`] puts the sun there, with no scripting needed.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SunAlignment
+{
+    /// World space direction *towards* the sun that counts as aligned. Doesn't need to be
+    /// normalized
+    pub direction: Vec3,
+    /// Largest angle in radians between the sun and [`direction`](SunAlignment::direction) that
+    /// still counts as aligned
+    pub tolerance: f32,
+    /// Whether the sun was aligned the last time it was checked
+    aligned: bool,
+}
+
+impl SunAlignment
+{
+    /// Default [`tolerance`](SunAlignment::tolerance) of 1 degree
+    pub const DEFAULT_TOLERANCE: f32 = 1.0 * DEG_TO_RAD;
+
+    /// Creates a new `SunAlignment` watching for the sun in `direction` with the default tolerance
+    pub const fn new(direction: Vec3) -> Self {
+        Self {
+            direction,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            aligned: false,
+        }
+    }
+
+    /// Creates a new `SunAlignment` watching for the sun at a compass `azimuth` and `elevation`
+    /// above the horizon, both in radians
+    /// 
+    /// Azimuth is measured clockwise from north (the `-Z` axis), so east (the `+X` axis) is `PI/2`
+    pub fn from_azimuth_elevation(azimuth: f32, elevation: f32) -> Self {
+        Self::new(azimuth_elevation_to_direction(azimuth, elevation))
+    }
+
+    /// Sets the tolerance in radians
+    pub const fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the tolerance in degrees
+    pub const fn with_tolerance_deg(self, tolerance: f32) -> Self {
+        self.with_tolerance(tolerance * DEG_TO_RAD)
+    }
+
+    /// Whether the sun was aligned the last time it was checked
+    pub const fn is_aligned(&self) -> bool {
+        self.aligned
+    }
+
+    /// Predicts the next time and date the sun will line up with this alignment's direction, or
+    /// `None` if it never will with the `environment`'s latitude and axial tilt
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, SunAlignment};
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(52.0);
+    /// let corridor = SunAlignment::from_azimuth_elevation(2.3, 0.0);
+    /// match corridor.next_alignment(&environment) {
+    ///     Some(next) => println!("Return on day {} at {}", next.time_of_year, next.time_of_day),
+    ///     None => println!("The sun never shines down this corridor"),
+    /// }
+    /// ```
+    /// 
+    /// The sun passes through most directions on two dates a year, once while the days are getting
+    /// longer and once while they're getting shorter, and this returns whichever comes first on or
+    /// after the current [`time_of_year`](Environment::time_of_year). With no axial tilt the sun
+    /// takes the same path every day, so the current time of year is returned.
+    pub fn next_alignment(&self, environment: &Environment) -> Option<SunAlignmentTime> {
+        let (earth_tilt_angle, solar_time_of_day) = environment.solve_light_direction(-self.direction)?;
+        // the tilt angle swings between plus and minus half the axial tilt over the year
+        let max_tilt_angle = environment.axial_tilt.abs() / 2.0;
+        if earth_tilt_angle.abs() > max_tilt_angle + self.tolerance {
+            return None;
+        }
+        if environment.axial_tilt == 0.0 {
+            let time_of_day = environment.solar_to_clock_time(solar_time_of_day);
+            return Some(SunAlignmentTime { time_of_day, time_of_year: environment.time_of_year });
+        }
+        let time_of_year_cos = (-2.0 * earth_tilt_angle / environment.axial_tilt).clamp(-1.0, 1.0);
+        let candidate = time_of_year_cos.acos();
+        // both dates are measured from the summer solstice, which moves with the precession
+        let candidates = [candidate, -candidate].map(|candidate| {
+            wrap_angle(candidate + environment.precession.angle)
+        });
+        let until = |time_of_year: f32| (time_of_year - environment.time_of_year).rem_euclid(TAU);
+        let time_of_year = if until(candidates[0]) <= until(candidates[1]) { candidates[0] } else { candidates[1] };
+        let time_of_day = environment.with_date(time_of_year).solar_to_clock_time(solar_time_of_day);
+        Some(SunAlignmentTime { time_of_day, time_of_year })
+    }
+}
+
+/// When the sun will line up with a [`SunAlignment`], returned by
+/// [`SunAlignment::next_alignment`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SunAlignmentTime
+{
+    /// Time of day in radians, in the same format as [`Environment::time_of_day`]
+    pub time_of_day: f32,
+    /// Time of year in radians, in the same format as [`Environment::time_of_year`]
+    pub time_of_year: f32,
+}
+
+/// Triggered on an entity with a [`SunAlignment`] when the sun comes into alignment
+#[derive(Clone, Copy, Debug)]
+#[derive(EntityEvent)]
+pub struct SunAligned
+{
+    /// Entity with the [`SunAlignment`]
+    pub entity: Entity,
+    /// Angle in radians between the sun and the alignment direction when it was detected
+    pub angle: f32,
+}
+
+/// Triggered on an entity with a [`SunAlignment`] when the sun moves out of alignment
+#[derive(Clone, Copy, Debug)]
+#[derive(EntityEvent)]
+pub struct SunAlignmentLost
+{
+    /// Entity with the [`SunAlignment`]
+    pub entity: Entity,
+}
+
+/// Checks every [`SunAlignment`] against the current sun direction and triggers events when their
+/// alignment changes
+pub(crate) fn check_sun_alignments(
+    mut commands: Commands,
+    mut alignments: Query<(Entity, &mut SunAlignment)>,
+    environment: Res<Environment>,
+){
+    let to_sun = environment.sun_direction();
+    for (entity, mut alignment) in &mut alignments {
+        let Some(direction) = alignment.direction.try_normalize() else {
+            continue;
+        };
+        let angle = direction.angle_between(to_sun);
+        let aligned = angle <= alignment.tolerance;
+        if aligned == alignment.aligned {
+            continue;
+        }
+        alignment.aligned = aligned;
+        if aligned {
+            commands.trigger(SunAligned { entity, angle });
+        } else {
+            commands.trigger(SunAlignmentLost { entity });
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[derive(Default, Resource)]
+    struct Triggered(Vec<bool>);
+
+    #[test]
+    fn aligned_at_noon() {
+        let mut app = App::new();
+        app.add_plugins(RealisticSunDirectionPlugin::default());
+        app.init_resource::<Triggered>();
+        app.add_observer(|_: On<SunAligned>, mut triggered: ResMut<Triggered>| triggered.0.push(true));
+        app.add_observer(|_: On<SunAlignmentLost>, mut triggered: ResMut<Triggered>| triggered.0.push(false));
+        // straight up at the equator at noon
+        app.world_mut().spawn(SunAlignment::new(Vec3::Y));
+        app.insert_resource(Environment::default().with_hours_since_noon(-1.0));
+        app.update();
+        app.insert_resource(Environment::default());
+        app.update();
+        app.insert_resource(Environment::default().with_hours_since_noon(1.0));
+        app.update();
+        assert_eq!(app.world().resource::<Triggered>().0, vec![true, false]);
+    }
+
+    #[test]
+    fn predicts_next_alignment() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(40.0)
+            .with_date(Environment::DATE_AUTUMN);
+        let target = environment.with_hours_since_noon(-3.0).with_date(2.5);
+        let alignment = SunAlignment::new(target.sun_direction());
+        let next = alignment.next_alignment(&environment).unwrap();
+        let predicted = environment.with_time_of_day(next.time_of_day).with_date(next.time_of_year);
+        let predicted_direction = predicted.sun_direction();
+        assert!(predicted_direction.angle_between(alignment.direction) < 1e-3);
+        assert!((next.time_of_year - 2.5).abs() < 1e-3, "{:?}", next);
+        // straight up never happens this far north
+        assert_eq!(SunAlignment::new(Vec3::Y).next_alignment(&environment), None);
+    }
+}
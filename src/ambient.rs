@@ -0,0 +1,127 @@
+//! Contains the [`SunAmbientLight`] resource that drives Bevy's ambient light from the sun
+use bevy::prelude::*;
+use crate::appearance::{sample_curve, to_sorted_radians};
+use crate::conversion::*;
+use crate::{CinematicOverrides, Environment};
+
+
+/// Drives the [`AmbientLight`] resource from how high the sun is in the sky
+/// 
+/// While this resource exists, the ambient light's brightness and color are set every frame from
+/// the solar elevation in the [`Environment`], so scenes without a full atmosphere setup still
+/// get bright days, blue dusks and dark nights. The default gradients can be replaced with your
+/// own keys.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use bevy::color::LinearRgba;
+/// # use kj_bevy_realistic_sun::SunAmbientLight;
+/// # let mut app = App::new();
+/// // Uses the default gradients
+/// app.insert_resource(SunAmbientLight::default());
+/// // Or a custom gradient that never gets completely dark
+/// app.insert_resource(SunAmbientLight::default()
+///     .with_brightness_curve(vec![(-12.0, 20.0), (10.0, 200.0)])
+///     .with_color_curve(vec![(-12.0, LinearRgba::BLUE), (10.0, LinearRgba::WHITE)]));
+/// ```
+/// 
+/// **Note:** only the [`AmbientLight`] resource is changed, not [`AmbientLight`] components on
+/// cameras. Nothing happens if the resource doesn't exist.
+#[derive(Clone, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct SunAmbientLight
+{
+    /// Ambient brightness as an [elevation curve](crate::SunAppearance#elevation-curves)
+    pub brightness: Vec<(f32, f32)>,
+
+    /// Ambient color as an [elevation curve](crate::SunAppearance#elevation-curves)
+    pub color: Vec<(f32, LinearRgba)>,
+}
+
+impl Default for SunAmbientLight {
+    /// Dim blue at night, fading through a warm twilight up to Bevy's default brightness in
+    /// white once the sun is 15 degrees up
+    fn default() -> Self {
+        Self {
+            brightness: vec![
+                (-18.0 * DEG_TO_RAD, 2.0),
+                (-6.0 * DEG_TO_RAD, 15.0),
+                (0.0, 40.0),
+                (15.0 * DEG_TO_RAD, 80.0),
+            ],
+            color: vec![
+                (-18.0 * DEG_TO_RAD, Color::srgb(0.1, 0.15, 0.3).into()),
+                (-6.0 * DEG_TO_RAD, Color::srgb(0.35, 0.4, 0.6).into()),
+                (0.0, Color::srgb(0.9, 0.7, 0.6).into()),
+                (15.0 * DEG_TO_RAD, LinearRgba::WHITE),
+            ],
+        }
+    }
+}
+
+impl SunAmbientLight
+{
+    /// Replaces the brightness gradient with `(elevation in degrees, brightness)` keys
+    pub fn with_brightness_curve(mut self, keys: Vec<(f32, f32)>) -> Self {
+        self.brightness = to_sorted_radians(keys);
+        self
+    }
+
+    /// Replaces the color gradient with `(elevation in degrees, color)` keys
+    pub fn with_color_curve(mut self, keys: Vec<(f32, LinearRgba)>) -> Self {
+        self.color = to_sorted_radians(keys);
+        self
+    }
+
+    /// Brightness at `elevation` in radians, or `None` if the brightness gradient is empty
+    pub fn brightness_at(&self, elevation: f32) -> Option<f32> {
+        sample_curve(&self.brightness, elevation, |from, to, t| from.lerp(*to, t))
+    }
+
+    /// Color at `elevation` in radians, or `None` if the color gradient is empty
+    pub fn color_at(&self, elevation: f32) -> Option<LinearRgba> {
+        sample_curve(&self.color, elevation, |from, to, t| from.mix(to, t))
+    }
+}
+
+/// Applies the [`SunAmbientLight`] gradients to the [`AmbientLight`] resource
+pub(crate) fn apply_sun_ambient_light(
+    sun_ambient_light: Res<SunAmbientLight>,
+    ambient_light: Option<ResMut<AmbientLight>>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let Some(mut ambient_light) = ambient_light else {
+        return;
+    };
+    let elevation = cinematic_overrides.resolve(&environment).solar_elevation();
+    if let Some(brightness) = sun_ambient_light.brightness_at(elevation) && ambient_light.brightness != brightness {
+        ambient_light.brightness = brightness;
+    }
+    if let Some(color) = sun_ambient_light.color_at(elevation).map(Color::from) && ambient_light.color != color {
+        ambient_light.color = color;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn follows_elevation() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.init_resource::<AmbientLight>();
+        app.insert_resource(SunAmbientLight::default());
+        app.update();
+        let ambient_light = app.world().resource::<AmbientLight>();
+        assert_eq!(ambient_light.brightness, 80.0);
+        assert_eq!(LinearRgba::from(ambient_light.color), LinearRgba::WHITE);
+        app.insert_resource(Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT));
+        app.update();
+        assert_eq!(app.world().resource::<AmbientLight>().brightness, 2.0);
+    }
+}
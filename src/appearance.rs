@@ -0,0 +1,180 @@
+//! Contains the [`SunAppearance`] component that changes how bright and warm the sun is based on
+//! how high it is
+use bevy::light::light_consts::lux;
+use bevy::prelude::*;
+use crate::SunReferenceFrame;
+use crate::conversion::*;
+
+
+/// Changes a sun light's illuminance and color depending on how high it is in the sky
+/// 
+/// Attach it next to a [`Sun`](crate::Sun) and its
+/// [`DirectionalLight`](https://docs.rs/bevy/0.17.3/bevy/light/struct.DirectionalLight.html) will
+/// be bright white at noon, dim and warm orange near the horizon, and off below the horizon. The
+/// default curves can be replaced with your own keys.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::color::LinearRgba;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{Sun, SunAppearance};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     // Uses the default curves
+///     SunAppearance::default(),
+/// ));
+/// // Or with a custom curve, fading in over the first 20 degrees
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     SunAppearance::default()
+///         .with_illuminance_curve(vec![(0.0, 0.0), (20.0, 50_000.0)])
+///         .with_color_curve(vec![(0.0, LinearRgba::RED), (20.0, LinearRgba::WHITE)]),
+/// ));
+/// ```
+/// 
+/// Elevation is read from the light's own [`Transform`], so it follows everything that changes the
+/// sun direction like [`StylizedSunPath`](crate::StylizedSunPath) and
+/// [`PhotoModeSunOffset`](crate::PhotoModeSunOffset).
+/// 
+/// # Elevation curves
+/// 
+/// The curves here, and in [`IlluminanceCurve`](crate::IlluminanceCurve),
+/// [`SunAmbientLight`](crate::SunAmbientLight) and [`SkyColor`](crate::SkyColor), are lists of
+/// `(elevation, value)` keys sorted by elevation in radians. Elevations between keys are linearly
+/// interpolated, and elevations outside the keys use the closest key.
+/// 
+/// **Note:** this overwrites the light's illuminance and color every frame, so it
+/// shouldn't be combined with the illuminance and color tracks of a `SunPathAsset`
+#[derive(Clone, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct SunAppearance
+{
+    /// Illuminance in lux as an [elevation curve](SunAppearance#elevation-curves)
+    pub illuminance: Vec<(f32, f32)>,
+
+    /// Light color as an [elevation curve](SunAppearance#elevation-curves)
+    pub color: Vec<(f32, LinearRgba)>,
+}
+
+impl Default for SunAppearance {
+    /// Off below the horizon, warm orange near the horizon and full
+    /// [`AMBIENT_DAYLIGHT`](lux::AMBIENT_DAYLIGHT) white above 30 degrees
+    fn default() -> Self {
+        Self {
+            illuminance: vec![
+                (0.0, 0.0),
+                (10.0 * DEG_TO_RAD, lux::AMBIENT_DAYLIGHT * 0.5),
+                (30.0 * DEG_TO_RAD, lux::AMBIENT_DAYLIGHT),
+            ],
+            color: vec![
+                (0.0, Color::srgb(1.0, 0.45, 0.15).into()),
+                (10.0 * DEG_TO_RAD, Color::srgb(1.0, 0.8, 0.6).into()),
+                (30.0 * DEG_TO_RAD, LinearRgba::WHITE),
+            ],
+        }
+    }
+}
+
+impl SunAppearance
+{
+    /// Replaces the illuminance curve with `(elevation in degrees, lux)` keys
+    pub fn with_illuminance_curve(mut self, keys: Vec<(f32, f32)>) -> Self {
+        self.illuminance = to_sorted_radians(keys);
+        self
+    }
+
+    /// Replaces the color curve with `(elevation in degrees, color)` keys
+    pub fn with_color_curve(mut self, keys: Vec<(f32, LinearRgba)>) -> Self {
+        self.color = to_sorted_radians(keys);
+        self
+    }
+
+    /// Illuminance at `elevation` in radians, or `None` if the illuminance curve is empty
+    pub fn illuminance_at(&self, elevation: f32) -> Option<f32> {
+        sample_curve(&self.illuminance, elevation, |from, to, t| from.lerp(*to, t))
+    }
+
+    /// Color at `elevation` in radians, or `None` if the color curve is empty
+    pub fn color_at(&self, elevation: f32) -> Option<LinearRgba> {
+        sample_curve(&self.color, elevation, |from, to, t| from.mix(to, t))
+    }
+}
+
+/// Converts keys from degrees to radians and sorts them by elevation
+pub(crate) fn to_sorted_radians<T>(mut keys: Vec<(f32, T)>) -> Vec<(f32, T)> {
+    for key in &mut keys {
+        key.0 *= DEG_TO_RAD;
+    }
+    keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+    keys
+}
+
+/// Linearly interpolates a curve sorted by elevation, clamping at both ends
+pub(crate) fn sample_curve<T: Copy>(curve: &[(f32, T)], elevation: f32, lerp: impl Fn(&T, &T, f32) -> T) -> Option<T> {
+    let next = curve.partition_point(|key| key.0 <= elevation);
+    if next == 0 {
+        return curve.first().map(|key| key.1);
+    }
+    if next == curve.len() {
+        return curve.last().map(|key| key.1);
+    }
+    let (from, to) = (&curve[next - 1], &curve[next]);
+    let t = (elevation - from.0) / (to.0 - from.0);
+    Some(lerp(&from.1, &to.1, t))
+}
+
+/// Applies each [`SunAppearance`] to its light, based on the direction the light is facing
+pub(crate) fn apply_sun_appearance(
+    mut lights: Query<(&SunAppearance, &Transform, &mut DirectionalLight)>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    for (appearance, transform, mut light) in &mut lights {
+        let (_, elevation) = direction_to_azimuth_elevation(reference_frame.from_world(-*transform.forward()));
+        if let Some(illuminance) = appearance.illuminance_at(elevation) && light.illuminance != illuminance {
+            light.illuminance = illuminance;
+        }
+        if let Some(color) = appearance.color_at(elevation).map(Color::from) && light.color != color {
+            light.color = color;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn follows_elevation() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default());
+        let sun = app.world_mut().spawn((DirectionalLight::default(), Sun, SunAppearance::default())).id();
+        app.update();
+        let light = app.world().get::<DirectionalLight>(sun).unwrap();
+        assert_eq!(light.illuminance, lux::AMBIENT_DAYLIGHT);
+        assert_eq!(LinearRgba::from(light.color), LinearRgba::WHITE);
+        // below the horizon at midnight
+        app.insert_resource(Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT));
+        app.update();
+        assert_eq!(app.world().get::<DirectionalLight>(sun).unwrap().illuminance, 0.0);
+    }
+
+    #[test]
+    fn curve_sampling() {
+        let appearance = SunAppearance::default()
+            .with_illuminance_curve(vec![(20.0, 100.0), (0.0, 0.0)]);
+        assert_eq!(appearance.illuminance_at(-1.0), Some(0.0));
+        assert!((appearance.illuminance_at(10.0 * DEG_TO_RAD).unwrap() - 50.0).abs() < 1e-3);
+        assert_eq!(appearance.illuminance_at(1.0), Some(100.0));
+    }
+}
@@ -0,0 +1,601 @@
+//! Lets [`Environment`] configurations and authored sun paths live in the asset system
+use std::f32::consts::TAU;
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::{Environment, PlanetPreset, StylizedSunPath, Sun, SunPath, TimeFlow};
+use crate::conversion::*;
+
+
+/// Adds the [`EnvironmentAsset`] and [`SunPathAsset`] asset types and keeps the [`Environment`]
+/// and [`StylizedSunPath`] resources in sync with the assets referenced by the
+/// [`ActiveEnvironmentAsset`] and [`ActiveSunPathAsset`] resources
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{EnvironmentAssetPlugin, RealisticSunDirectionPlugin};
+/// # let mut app = App::new();
+/// app.add_plugins((RealisticSunDirectionPlugin::default(), EnvironmentAssetPlugin));
+/// ```
+/// 
+/// **Note:** requires Bevy's `AssetPlugin`, which is part of `DefaultPlugins`
+pub struct EnvironmentAssetPlugin;
+impl Plugin for EnvironmentAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ActiveEnvironmentAsset>()
+            .register_type::<ActiveSunPathAsset>();
+        app.init_asset::<EnvironmentAsset>();
+        app.init_asset::<SunPathAsset>();
+        app.init_asset_loader::<EnvironmentAssetLoader>();
+        app.init_asset_loader::<SunPathAssetLoader>();
+        app.add_systems(Update, (
+            apply_environment_asset.before(crate::SunUpdateSet::UpdateLights),
+            apply_sun_path_asset.before(crate::SunUpdateSet::UpdateLights),
+        ));
+    }
+}
+
+/// An [`Environment`] configuration stored as an asset
+/// 
+/// Multiple levels can hold a [`Handle`] to the same `EnvironmentAsset` so they all share one
+/// authored configuration. Editing the asset updates the [`Environment`] resource whenever the
+/// asset is the one referenced by [`ActiveEnvironmentAsset`]. Environments can be created in code
+/// or loaded from `.sun.ron` files, see [`EnvironmentAssetLoader`].
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{ActiveEnvironmentAsset, Environment, EnvironmentAsset};
+/// fn setup(mut commands: Commands, mut environments: ResMut<Assets<EnvironmentAsset>>) {
+///     let arctic = environments.add(EnvironmentAsset::new(
+///         Environment::default()
+///             .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///             .with_latitude_deg(75.0)
+///     ));
+///     // make it the environment used by the sun
+///     commands.insert_resource(ActiveEnvironmentAsset(arctic));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+#[derive(Asset, TypePath)]
+pub struct EnvironmentAsset
+{
+    /// Values copied into the [`Environment`] resource when this asset is active
+    pub environment: Environment,
+    /// When set, inserted as the [`TimeFlow`] resource when this asset is active
+    pub time_flow: Option<TimeFlow>,
+}
+
+impl EnvironmentAsset
+{
+    /// Creates a new `EnvironmentAsset` from an [`Environment`]
+    pub const fn new(environment: Environment) -> Self {
+        Self { environment, time_flow: None }
+    }
+
+    /// Sets the [`TimeFlow`] inserted along with the environment
+    pub const fn with_time_flow(mut self, time_flow: TimeFlow) -> Self {
+        self.time_flow = Some(time_flow);
+        self
+    }
+}
+
+/// On-disk format of an [`EnvironmentAsset`], in degrees and hours
+#[derive(Deserialize)]
+#[serde(default)]
+struct EnvironmentAssetFile {
+    preset: Option<PlanetPreset>,
+    axial_tilt: Option<f32>,
+    latitude: f32,
+    longitude: f32,
+    utc_offset: f32,
+    hours_since_noon: f32,
+    time_of_year: f32,
+    horizon_offset: f32,
+    declination: Option<f32>,
+    seconds_per_day: Option<f32>,
+    days_per_year: Option<f32>,
+}
+
+impl Default for EnvironmentAssetFile {
+    fn default() -> Self {
+        Self {
+            preset: None,
+            axial_tilt: None,
+            latitude: 0.0,
+            longitude: 0.0,
+            utc_offset: 0.0,
+            hours_since_noon: 0.0,
+            time_of_year: 0.0,
+            horizon_offset: 0.0,
+            declination: None,
+            seconds_per_day: None,
+            days_per_year: None,
+        }
+    }
+}
+
+impl From<EnvironmentAssetFile> for EnvironmentAsset {
+    fn from(file: EnvironmentAssetFile) -> Self {
+        let mut environment = file.preset.map_or_else(Environment::default, Environment::from_preset);
+        if let Some(axial_tilt) = file.axial_tilt {
+            environment = environment.with_axial_tilt_deg(axial_tilt);
+        }
+        if let Some(declination) = file.declination {
+            environment = environment.with_declination_deg(declination);
+        }
+        let environment = environment
+            .with_latitude_deg(file.latitude)
+            .with_longitude_deg(file.longitude)
+            .with_utc_offset_hours(file.utc_offset)
+            .with_hours_since_noon(file.hours_since_noon)
+            .with_date(file.time_of_year * DEG_TO_RAD)
+            .with_horizon_offset_deg(file.horizon_offset);
+        let days_per_year = file.days_per_year
+            .or(file.preset.map(PlanetPreset::days_per_year))
+            .unwrap_or(TimeFlow::default().days_per_year);
+        Self {
+            environment,
+            time_flow: file.seconds_per_day.map(|seconds| TimeFlow::default()
+                .with_seconds_per_day(seconds)
+                .with_days_per_year(days_per_year)),
+        }
+    }
+}
+
+/// Loads [`EnvironmentAsset`]s from `.sun.ron` files
+/// 
+/// ```ron
+/// (
+///     // optional, sets the axial tilt
+///     preset: Some(Mars),
+///     // all angles in degrees, every field is optional
+///     axial_tilt: Some(25.2),
+///     latitude: -4.5,
+///     longitude: 137.4,
+///     // hours ahead of UTC
+///     utc_offset: 0.0,
+///     hours_since_noon: -3.0,
+///     // degrees around the orbit from the summer solstice
+///     time_of_year: 90.0,
+///     // elevation the sun counts as up above, for `Environment::is_daytime`
+///     horizon_offset: 5.0,
+///     // optional, sets the sun's declination directly instead
+///     // of working it out from the time of year
+///     declination: None,
+///     // optional, inserts a `TimeFlow` with this day length in
+///     // real seconds. Days per year default to the preset's
+///     seconds_per_day: Some(1200.0),
+///     days_per_year: None,
+/// )
+/// ```
+/// 
+/// Load the file with the `AssetServer` and make it active with [`ActiveEnvironmentAsset`]. With
+/// Bevy's asset hot reloading turned on, saving the file updates the [`Environment`] while the
+/// game is running, so values can be tuned without recompiling.
+#[derive(Default)]
+#[derive(TypePath)]
+pub struct EnvironmentAssetLoader;
+
+/// Error loading an [`EnvironmentAsset`]
+#[derive(Debug)]
+pub enum EnvironmentAssetLoaderError
+{
+    /// The file couldn't be read
+    Io(std::io::Error),
+    /// The file isn't valid RON or doesn't match the format
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for EnvironmentAssetLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read environment asset: {error}"),
+            Self::Ron(error) => write!(f, "could not parse environment asset: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for EnvironmentAssetLoaderError {}
+
+impl From<std::io::Error> for EnvironmentAssetLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for EnvironmentAssetLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Ron(error)
+    }
+}
+
+impl AssetLoader for EnvironmentAssetLoader {
+    type Asset = EnvironmentAsset;
+    type Settings = ();
+    type Error = EnvironmentAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: EnvironmentAssetFile = ron::de::from_bytes(&bytes)?;
+        Ok(file.into())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sun.ron"]
+    }
+}
+
+/// Points to the [`EnvironmentAsset`] that the [`Environment`] resource should be copied from
+/// 
+/// The whole [`Environment`] is overwritten when this resource changes to point to another asset,
+/// and again whenever the asset it points to is modified, so any changes made directly to the
+/// [`Environment`] resource in between (including time advanced by a
+/// [`SolarClock`](crate::SolarClock)) are replaced by the asset's values at that point. The same
+/// goes for the [`TimeFlow`] resource when the asset has one.
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct ActiveEnvironmentAsset(pub Handle<EnvironmentAsset>);
+
+/// Copies the active [`EnvironmentAsset`] into the [`Environment`] resource when it is switched,
+/// loaded or modified
+fn apply_environment_asset(
+    mut commands: Commands,
+    active: Option<Res<ActiveEnvironmentAsset>>,
+    assets: Res<Assets<EnvironmentAsset>>,
+    mut asset_events: MessageReader<AssetEvent<EnvironmentAsset>>,
+    mut environment: ResMut<Environment>,
+){
+    let Some(active) = active else {
+        asset_events.clear();
+        return;
+    };
+    let active_id = active.0.id();
+    let asset_changed = asset_events.read().any(|event| match event {
+        AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => *id == active_id,
+        _ => false,
+    });
+    if !(active.is_changed() || asset_changed) {
+        return;
+    }
+    if let Some(asset) = assets.get(active_id) {
+        *environment = asset.environment;
+        if let Some(time_flow) = asset.time_flow {
+            commands.insert_resource(time_flow);
+        }
+    }
+}
+
+/// Value of a [`SunPathAsset`] track at a time of day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackKey<T>
+{
+    /// Local solar time of day in radians, in the same format as
+    /// [`Environment::solar_time_of_day`]
+    pub time_of_day: f32,
+    /// Value at this time of day
+    pub value: T,
+}
+
+/// An authored sun path plus illuminance and color tracks keyed by time of day, loaded from a
+/// `.sunpath.ron` file
+/// 
+/// ```ron
+/// (
+///     // hours since local solar noon, compass azimuth and elevation in degrees
+///     path: [
+///         (hours: -6.0, azimuth: 90.0, elevation: 0.0),
+///         (hours: 0.0, azimuth: 180.0, elevation: 40.0),
+///         (hours: 6.0, azimuth: 270.0, elevation: 0.0),
+///         (hours: 12.0, azimuth: 0.0, elevation: -30.0),
+///     ],
+///     // optional, in lux
+///     illuminance: [
+///         (hours: -6.0, value: 0.0),
+///         (hours: 0.0, value: 100000.0),
+///         (hours: 6.0, value: 0.0),
+///     ],
+///     // optional, linear RGB
+///     color: [
+///         (hours: -6.0, value: (1.0, 0.5, 0.2)),
+///         (hours: 0.0, value: (1.0, 1.0, 1.0)),
+///         (hours: 6.0, value: (1.0, 0.4, 0.1)),
+///     ],
+/// )
+/// ```
+/// 
+/// Tracks are linearly interpolated between keys and loop around over the day like the path does.
+/// Make the asset active with [`ActiveSunPathAsset`]. Editing the file while the game is running
+/// updates the sun right away when Bevy's asset hot reloading is turned on.
+#[derive(Clone, Debug, Default)]
+#[derive(Asset, TypePath)]
+pub struct SunPathAsset
+{
+    /// Path the sun follows over the day
+    pub path: SunPath,
+    /// Illuminance of the sun light in lux over the day. Left alone if empty
+    pub illuminance: Vec<TrackKey<f32>>,
+    /// Color of the sun light over the day. Left alone if empty
+    pub color: Vec<TrackKey<LinearRgba>>,
+}
+
+impl SunPathAsset
+{
+    /// Illuminance at `time_of_day`, or `None` if there is no illuminance track
+    pub fn illuminance_at(&self, time_of_day: f32) -> Option<f32> {
+        sample_track(&self.illuminance, time_of_day, |from, to, t| from.lerp(*to, t))
+    }
+
+    /// Color at `time_of_day`, or `None` if there is no color track
+    pub fn color_at(&self, time_of_day: f32) -> Option<LinearRgba> {
+        sample_track(&self.color, time_of_day, |from, to, t| from.mix(to, t))
+    }
+}
+
+/// Linearly interpolates a track sorted by time of day, looping around over the day
+fn sample_track<T: Copy>(
+    track: &[TrackKey<T>], time_of_day: f32, lerp: impl Fn(&T, &T, f32) -> T,
+) -> Option<T> {
+    let count = track.len();
+    if count <= 1 {
+        return track.first().map(|key| key.value);
+    }
+    let time_of_day = wrap_angle(time_of_day);
+    let next = track.partition_point(|key| key.time_of_day <= time_of_day) % count;
+    let current = (next + count - 1) % count;
+    let start = track[current].time_of_day;
+    let span = (track[next].time_of_day - start).rem_euclid(TAU);
+    let t = if span > 0.0 { (time_of_day - start).rem_euclid(TAU) / span } else { 0.0 };
+    Some(lerp(&track[current].value, &track[next].value, t))
+}
+
+/// On-disk format of a [`SunPathAsset`]
+#[derive(Deserialize)]
+struct SunPathAssetFile {
+    path: Vec<PathKeyFile>,
+    #[serde(default)]
+    illuminance: Vec<TrackKeyFile<f32>>,
+    #[serde(default)]
+    color: Vec<TrackKeyFile<(f32, f32, f32)>>,
+}
+
+#[derive(Deserialize)]
+struct PathKeyFile {
+    hours: f32,
+    azimuth: f32,
+    elevation: f32,
+}
+
+#[derive(Deserialize)]
+struct TrackKeyFile<T> {
+    hours: f32,
+    value: T,
+}
+
+impl From<SunPathAssetFile> for SunPathAsset {
+    fn from(file: SunPathAssetFile) -> Self {
+        let path = file.path.into_iter().fold(SunPath::default(), |path, key| {
+            path.with_key_deg(key.hours, key.azimuth, key.elevation)
+        });
+        fn track<F, T>(keys: Vec<TrackKeyFile<F>>, convert: impl Fn(F) -> T) -> Vec<TrackKey<T>> {
+            let mut track: Vec<_> = keys.into_iter()
+                .map(|key| TrackKey {
+                    time_of_day: wrap_angle(key.hours * HOURS_TO_RAD),
+                    value: convert(key.value),
+                })
+                .collect();
+            track.sort_by(|a, b| a.time_of_day.total_cmp(&b.time_of_day));
+            track
+        }
+        Self {
+            path,
+            illuminance: track(file.illuminance, |value| value),
+            color: track(file.color, |(red, green, blue)| LinearRgba::rgb(red, green, blue)),
+        }
+    }
+}
+
+/// Loads [`SunPathAsset`]s from `.sunpath.ron` files
+#[derive(Default)]
+#[derive(TypePath)]
+pub struct SunPathAssetLoader;
+
+/// Error loading a [`SunPathAsset`]
+#[derive(Debug)]
+pub enum SunPathAssetLoaderError
+{
+    /// The file couldn't be read
+    Io(std::io::Error),
+    /// The file isn't valid RON or doesn't match the format
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for SunPathAssetLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read sun path asset: {error}"),
+            Self::Ron(error) => write!(f, "could not parse sun path asset: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SunPathAssetLoaderError {}
+
+impl From<std::io::Error> for SunPathAssetLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for SunPathAssetLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Ron(error)
+    }
+}
+
+impl AssetLoader for SunPathAssetLoader {
+    type Asset = SunPathAsset;
+    type Settings = ();
+    type Error = SunPathAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: SunPathAssetFile = ron::de::from_bytes(&bytes)?;
+        Ok(file.into())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sunpath.ron"]
+    }
+}
+
+/// Points to the [`SunPathAsset`] the sun should follow
+/// 
+/// The asset's path is copied into the [`StylizedSunPath`] resource with this resource's
+/// [`blend`](ActiveSunPathAsset::blend), so a blend of `1.0` uses the authored path on its own.
+/// Its illuminance and color tracks (when they have keys) are applied to every [`Sun`]'s
+/// [`DirectionalLight`] each frame.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::ActiveSunPathAsset;
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     commands.insert_resource(ActiveSunPathAsset {
+///         handle: asset_server.load("skies/stylized.sunpath.ron"),
+///         blend: 1.0,
+///     });
+/// }
+/// ```
+/// 
+/// Removing this resource also removes the [`StylizedSunPath`] resource
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct ActiveSunPathAsset
+{
+    /// Asset to follow
+    pub handle: Handle<SunPathAsset>,
+    /// How far to blend from the realistic path (`0.0`) to the authored path (`1.0`)
+    pub blend: f32,
+}
+
+/// Copies the active [`SunPathAsset`] into the [`StylizedSunPath`] resource when it changes, and
+/// applies its tracks to the sun lights
+fn apply_sun_path_asset(
+    mut commands: Commands,
+    active: Option<Res<ActiveSunPathAsset>>,
+    assets: Res<Assets<SunPathAsset>>,
+    mut asset_events: MessageReader<AssetEvent<SunPathAsset>>,
+    mut suns: Query<&mut DirectionalLight, With<Sun>>,
+    environment: Res<Environment>,
+    mut was_active: Local<bool>,
+){
+    let Some(active) = active else {
+        asset_events.clear();
+        if *was_active {
+            commands.remove_resource::<StylizedSunPath>();
+            *was_active = false;
+        }
+        return;
+    };
+    *was_active = true;
+    let active_id = active.handle.id();
+    let asset_changed = asset_events.read().any(|event| match event {
+        AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => *id == active_id,
+        _ => false,
+    });
+    let Some(asset) = assets.get(active_id) else {
+        return;
+    };
+    if active.is_changed() || asset_changed {
+        commands.insert_resource(StylizedSunPath::new(asset.path.clone()).with_blend(active.blend));
+    }
+    let illuminance = asset.illuminance_at(environment.solar_time_of_day());
+    let color = asset.color_at(environment.solar_time_of_day());
+    for mut light in &mut suns {
+        if let Some(illuminance) = illuminance {
+            light.illuminance = illuminance;
+        }
+        if let Some(color) = color {
+            light.color = color.into();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn follows_active_asset() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.add_plugins((RealisticSunDirectionPlugin::default(), EnvironmentAssetPlugin));
+        let handle = app.world_mut().resource_mut::<Assets<EnvironmentAsset>>()
+            .add(EnvironmentAsset::new(Environment::default().with_latitude(0.5)));
+        app.insert_resource(ActiveEnvironmentAsset(handle.clone()));
+        app.update();
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.5);
+        // editing the asset updates the resource
+        app.world_mut().resource_mut::<Assets<EnvironmentAsset>>()
+            .get_mut(&handle).unwrap()
+            .environment.latitude = 0.25;
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.25);
+    }
+
+    #[test]
+    fn parses_environment_file() {
+        let file: EnvironmentAssetFile = ron::de::from_str("(
+            preset: Some(Mars),
+            latitude: 45.0,
+            hours_since_noon: -3.0,
+            seconds_per_day: Some(600.0),
+        )").unwrap();
+        let asset = EnvironmentAsset::from(file);
+        assert_eq!(asset.environment.axial_tilt, PlanetPreset::Mars.axial_tilt());
+        assert!((asset.environment.latitude - 45.0 * DEG_TO_RAD).abs() < 1e-6);
+        assert!((asset.environment.time_of_day + 3.0 * HOURS_TO_RAD).abs() < 1e-6);
+        let time_flow = asset.time_flow.unwrap();
+        assert_eq!(time_flow.seconds_per_day, 600.0);
+        assert_eq!(time_flow.days_per_year, PlanetPreset::Mars.days_per_year());
+        let empty = EnvironmentAsset::from(ron::de::from_str::<EnvironmentAssetFile>("()").unwrap());
+        assert_eq!(empty.environment.axial_tilt, 0.0);
+        assert!(empty.time_flow.is_none());
+    }
+
+    #[test]
+    fn parses_sun_path_file() {
+        let file: SunPathAssetFile = ron::de::from_str("(
+            path: [
+                (hours: -6.0, azimuth: 90.0, elevation: 0.0),
+                (hours: 6.0, azimuth: 270.0, elevation: 0.0),
+            ],
+            illuminance: [
+                (hours: 6.0, value: 0.0),
+                (hours: 0.0, value: 1000.0),
+            ],
+        )").unwrap();
+        let asset = SunPathAsset::from(file);
+        assert_eq!(asset.path.keys().len(), 2);
+        assert!(asset.color.is_empty());
+        assert_eq!(asset.illuminance_at(0.0), Some(1000.0));
+        assert!((asset.illuminance_at(3.0 * HOURS_TO_RAD).unwrap() - 500.0).abs() < 0.01);
+        assert_eq!(asset.color_at(0.0), None);
+    }
+}
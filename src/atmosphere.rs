@@ -0,0 +1,179 @@
+//! Contains the [`AtmosphereSyncPlugin`], which keeps the sun disks drawn by Bevy's atmosphere in
+//! line with the [`Sun`] and [`Moon`] lights
+use bevy::light::SunDisk;
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, Moon, Sun, SunEnvironment};
+use crate::conversion::DEG_TO_RAD;
+
+
+/// Keeps the disks Bevy's procedural atmosphere draws for [`Sun`] and [`Moon`] lights in sync with
+/// them
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{AtmosphereSyncPlugin, RealisticSunDirectionPlugin};
+/// # let mut app = App::new();
+/// app.add_plugins((RealisticSunDirectionPlugin::default(), AtmosphereSyncPlugin));
+/// ```
+/// 
+/// The atmosphere (the `Atmosphere` component on a camera, from `bevy_pbr`) draws a disk for every
+/// [`DirectionalLight`] in the direction the light comes from, so the disk already follows the
+/// sun. What it can't know is which light is which, so without this plugin a moon gets a disk as
+/// big and bright as the sun's. This plugin gives every [`Sun`] and [`Moon`] light a [`SunDisk`]
+/// with the angular size and intensity from the [`AtmosphereSync`] resource, with the moon's
+/// intensity following its [`moon_illumination`](Environment::moon_illumination).
+/// 
+/// Only entities with a [`DirectionalLight`] are touched. Insert your own [`AtmosphereSync`] to
+/// change the sizes, for example to match a
+/// [`SolarEclipse`](crate::SolarEclipse) setup or a planet further from its star.
+pub struct AtmosphereSyncPlugin;
+impl Plugin for AtmosphereSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AtmosphereSync>();
+        app.init_resource::<AtmosphereSync>();
+        app.add_systems(Update, sync_sun_disks.after(crate::SunUpdateSet::UpdateLights));
+    }
+}
+
+/// Sizes and brightness of the disks drawn by the [`AtmosphereSyncPlugin`]
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::AtmosphereSync;
+/// # let mut app = App::new();
+/// // A huge red giant and a tiny moon
+/// app.insert_resource(AtmosphereSync::default()
+///     .with_sun_angular_size_deg(6.0)
+///     .with_moon_angular_size_deg(0.2));
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct AtmosphereSync
+{
+    /// Angular diameter of sun disks in radians
+    pub sun_angular_size: f32,
+    /// Brightness multiplier for sun disks, where `1.0` is physically based
+    pub sun_intensity: f32,
+    /// Angular diameter of moon disks in radians
+    pub moon_angular_size: f32,
+    /// Brightness multiplier for a full moon's disk, scaled down with the moon's phase
+    pub moon_intensity: f32,
+}
+
+impl Default for AtmosphereSync {
+    /// The sun and moon as seen from Earth
+    fn default() -> Self {
+        Self {
+            sun_angular_size: SunDisk::EARTH.angular_size,
+            sun_intensity: SunDisk::EARTH.intensity,
+            moon_angular_size: 0.00904,
+            moon_intensity: 1.0,
+        }
+    }
+}
+
+impl AtmosphereSync
+{
+    /// Sets the angular diameter of sun disks in radians
+    pub const fn with_sun_angular_size(mut self, angular_size: f32) -> Self {
+        self.sun_angular_size = angular_size;
+        self
+    }
+
+    /// Sets the angular diameter of sun disks in degrees
+    pub const fn with_sun_angular_size_deg(self, angular_size: f32) -> Self {
+        self.with_sun_angular_size(angular_size * DEG_TO_RAD)
+    }
+
+    /// Sets the angular diameter of moon disks in radians
+    pub const fn with_moon_angular_size(mut self, angular_size: f32) -> Self {
+        self.moon_angular_size = angular_size;
+        self
+    }
+
+    /// Sets the angular diameter of moon disks in degrees
+    pub const fn with_moon_angular_size_deg(self, angular_size: f32) -> Self {
+        self.with_moon_angular_size(angular_size * DEG_TO_RAD)
+    }
+
+    /// Sets the brightness multipliers for sun and full moon disks
+    pub const fn with_intensity(mut self, sun: f32, moon: f32) -> Self {
+        self.sun_intensity = sun;
+        self.moon_intensity = moon;
+        self
+    }
+}
+
+/// [`Sun`] entities that are directional lights
+type SunLightFilter = (With<Sun>, With<DirectionalLight>);
+/// [`Moon`] entities that are directional lights, and not suns too
+type MoonLightFilter = (With<Moon>, Without<Sun>, With<DirectionalLight>);
+
+/// Gives every [`Sun`] and [`Moon`] light a [`SunDisk`] matching the [`AtmosphereSync`] settings
+fn sync_sun_disks(
+    mut commands: Commands,
+    mut suns: Query<(Entity, Option<&mut SunDisk>), SunLightFilter>,
+    mut moons: Query<(Entity, Option<&mut SunDisk>, Option<&SunEnvironment>), MoonLightFilter>,
+    settings: Res<AtmosphereSync>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let sun_disk = SunDisk {
+        angular_size: settings.sun_angular_size,
+        intensity: settings.sun_intensity,
+    };
+    for (entity, disk) in &mut suns {
+        set_disk(&mut commands, entity, disk, &sun_disk);
+    }
+    let environment = cinematic_overrides.resolve(&environment);
+    for (entity, disk, moon_environment) in &mut moons {
+        let illumination = moon_environment.map_or(&environment, |moon_environment| &moon_environment.0)
+            .moon_illumination();
+        let moon_disk = SunDisk {
+            angular_size: settings.moon_angular_size,
+            intensity: settings.moon_intensity * illumination,
+        };
+        set_disk(&mut commands, entity, disk, &moon_disk);
+    }
+}
+
+/// Inserts `target` on `entity`, or copies it into the existing disk if it is different
+fn set_disk(commands: &mut Commands, entity: Entity, disk: Option<Mut<SunDisk>>, target: &SunDisk) {
+    match disk {
+        Some(mut disk) => {
+            if disk.angular_size != target.angular_size || disk.intensity != target.intensity {
+                *disk = target.clone();
+            }
+        },
+        None => {
+            commands.entity(entity).insert(target.clone());
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use crate::{LunarOrbit, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn moons_get_their_own_disk() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default(), AtmosphereSyncPlugin));
+        app.insert_resource(Environment::default().with_lunar_orbit(LunarOrbit::EARTH.with_phase_offset(PI / 2.0)));
+        let sun = app.world_mut().spawn((DirectionalLight::default(), Sun)).id();
+        let moon = app.world_mut().spawn((DirectionalLight::default(), Moon)).id();
+        let marker = app.world_mut().spawn(Sun).id();
+        app.update();
+        let sun = app.world().get::<SunDisk>(sun).unwrap();
+        assert_eq!(sun.angular_size, SunDisk::EARTH.angular_size);
+        let moon = app.world().get::<SunDisk>(moon).unwrap();
+        assert_eq!(moon.angular_size, AtmosphereSync::default().moon_angular_size);
+        assert!((moon.intensity - 0.5).abs() < 1e-5, "{}", moon.intensity);
+        assert!(app.world().get::<SunDisk>(marker).is_none());
+    }
+}
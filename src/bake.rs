@@ -0,0 +1,470 @@
+//! Utilities for baking sun data ahead of time instead of calculating it every frame
+use bevy::prelude::*;
+use crate::Environment;
+use crate::conversion::*;
+
+
+/// A grid of terrain heights to bake sun exposure over
+/// 
+/// Heights are stored row by row, with `width` cells along the X axis (east) and `depth` rows
+/// along the Z axis (south), so the height of cell `(x, z)` is at `heights[z * width + x]`. Each
+/// cell is `cell_size` units apart in world space.
+#[derive(Clone, Copy, Debug)]
+pub struct Heightmap<'a>
+{
+    /// Height of each cell, row by row
+    pub heights: &'a [f32],
+    /// Number of cells along the X axis
+    pub width: usize,
+    /// Number of cells along the Z axis
+    pub depth: usize,
+    /// Distance between neighbouring cells in world units
+    pub cell_size: f32,
+}
+
+impl<'a> Heightmap<'a>
+{
+    /// Creates a new `Heightmap` from a slice of heights
+    /// 
+    /// **Note:** panics if `heights` doesn't have exactly `width * depth` values
+    pub fn new(heights: &'a [f32], width: usize, depth: usize, cell_size: f32) -> Self {
+        assert_eq!(heights.len(), width * depth, "heightmap must have width * depth heights");
+        Self { heights, width, depth, cell_size }
+    }
+
+    /// Height of the cell at `(x, z)`, clamped to the edges of the grid
+    fn height(&self, x: isize, z: isize) -> f32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let z = z.clamp(0, self.depth as isize - 1) as usize;
+        self.heights[z * self.width + x]
+    }
+
+    /// Surface normal of the cell at `(x, z)` from the heights of its neighbours
+    fn normal(&self, x: isize, z: isize) -> Vec3 {
+        let dx = self.height(x + 1, z) - self.height(x - 1, z);
+        let dz = self.height(x, z + 1) - self.height(x, z - 1);
+        Vec3::new(-dx, 2.0 * self.cell_size, -dz).normalize()
+    }
+
+    /// Whether the terrain blocks the sun from the cell at `(x, z)`, by marching from the cell
+    /// towards the sun one cell at a time until it leaves the grid
+    fn is_shadowed(&self, x: usize, z: usize, to_sun: Vec3) -> bool {
+        let horizontal = Vec2::new(to_sun.x, to_sun.z);
+        let horizontal_length = horizontal.length();
+        if horizontal_length <= f32::EPSILON {
+            return false;
+        }
+        let step = horizontal / horizontal_length;
+        let rise = to_sun.y / horizontal_length * self.cell_size;
+        let mut position = Vec2::new(x as f32, z as f32);
+        let mut ray_height = self.heights[z * self.width + x];
+        loop {
+            position += step;
+            ray_height += rise;
+            let cell = position.round();
+            if cell.x < 0.0 || cell.y < 0.0
+                || cell.x >= self.width as f32 || cell.y >= self.depth as f32 {
+                return false;
+            }
+            if self.height(cell.x as isize, cell.y as isize) > ray_height {
+                return true;
+            }
+        }
+    }
+}
+
+/// Span of time to bake sun exposure over
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BakePeriod
+{
+    /// The single day in the [`Environment`]'s current [`time_of_year`](Environment::time_of_year)
+    Day,
+    /// A whole year, sampling this many days spread evenly through the year. Results are the
+    /// average for a single day
+    Year {
+        /// Number of days to sample through the year
+        sampled_days: u32,
+    },
+}
+
+/// Settings for [`bake_exposure`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExposureSettings
+{
+    /// Span of time to bake over
+    pub period: BakePeriod,
+    /// Number of times to sample the sun in each day. More samples are more accurate but slower
+    pub samples_per_day: u32,
+    /// Irradiance of direct sunlight on a surface facing the sun, in W/m²
+    pub irradiance: f32,
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            period: BakePeriod::Day,
+            samples_per_day: 96,
+            irradiance: 1000.0,
+        }
+    }
+}
+
+/// Sun exposure baked for each cell of a [`Heightmap`]
+/// 
+/// Values are stored row by row in the same layout as the [`Heightmap`] they were baked from
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExposureGrid
+{
+    /// Number of cells along the X axis
+    pub width: usize,
+    /// Number of cells along the Z axis
+    pub depth: usize,
+    /// Hours of direct sunlight each cell gets in a day
+    pub sunlit_hours: Vec<f32>,
+    /// Energy from direct sunlight each cell gets in a day, in Wh/m²
+    pub irradiation: Vec<f32>,
+}
+
+impl ExposureGrid
+{
+    /// Hours of direct sunlight the cell at `(x, z)` gets in a day
+    pub fn sunlit_hours_at(&self, x: usize, z: usize) -> f32 {
+        self.sunlit_hours[z * self.width + x]
+    }
+
+    /// Energy from direct sunlight the cell at `(x, z)` gets in a day, in Wh/m²
+    pub fn irradiation_at(&self, x: usize, z: usize) -> f32 {
+        self.irradiation[z * self.width + x]
+    }
+}
+
+/// Bakes how much direct sunlight each cell of a [`Heightmap`] gets, accounting for the slope of
+/// the terrain and shadows cast by the terrain itself
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::Environment;
+/// # use kj_bevy_realistic_sun::bake::{bake_exposure, BakePeriod, ExposureSettings, Heightmap};
+/// let environment = Environment::default()
+///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///     .with_latitude_deg(45.0);
+/// let heights = vec![0.0; 64 * 64];
+/// let heightmap = Heightmap::new(&heights, 64, 64, 1.0);
+/// let settings = ExposureSettings {
+///     period: BakePeriod::Year { sampled_days: 12 },
+///     ..Default::default()
+/// };
+/// let exposure = bake_exposure(&environment, &heightmap, &settings);
+/// println!("{} hours of sun", exposure.sunlit_hours_at(32, 32));
+/// ```
+/// 
+/// Latitude and axial tilt are taken from `environment`, and so is the time of year when baking a
+/// single [`BakePeriod::Day`]. Its time of day is ignored. Useful for farming, snow melt or
+/// vegetation systems that need to know which areas get the most sun without checking every frame.
+pub fn bake_exposure(
+    environment: &Environment, heightmap: &Heightmap, settings: &ExposureSettings,
+) -> ExposureGrid {
+    let cells = heightmap.width * heightmap.depth;
+    let mut grid = ExposureGrid {
+        width: heightmap.width,
+        depth: heightmap.depth,
+        sunlit_hours: vec![0.0; cells],
+        irradiation: vec![0.0; cells],
+    };
+    let days: Vec<f32> = match settings.period {
+        BakePeriod::Day => vec![environment.time_of_year],
+        BakePeriod::Year { sampled_days } => (0..sampled_days.max(1))
+            .map(|day| day as f32 / sampled_days.max(1) as f32 * std::f32::consts::TAU)
+            .collect(),
+    };
+    let samples = settings.samples_per_day.max(1);
+    let sample_hours = 24.0 / samples as f32 / days.len() as f32;
+    for time_of_year in days {
+        for sample in 0..samples {
+            let time_of_day = (sample as f32 + 0.5) / samples as f32 * 24.0 * HOURS_TO_RAD;
+            let sample_environment = environment
+                .with_date(time_of_year)
+                .with_time_of_day(time_of_day);
+            let to_sun = sample_environment.sun_direction();
+            if to_sun.y <= 0.0 {
+                continue;
+            }
+            for z in 0..heightmap.depth {
+                for x in 0..heightmap.width {
+                    if heightmap.is_shadowed(x, z, to_sun) {
+                        continue;
+                    }
+                    let facing = heightmap.normal(x as isize, z as isize).dot(to_sun);
+                    if facing <= 0.0 {
+                        continue;
+                    }
+                    let index = z * heightmap.width + x;
+                    grid.sunlit_hours[index] += sample_hours;
+                    grid.irradiation[index] += facing * settings.irradiance * sample_hours;
+                }
+            }
+        }
+    }
+    grid
+}
+
+/// Area of a static scene to bake a [`ShadeMap`] over
+/// 
+/// The area is a flat grid of `width` by `depth` cells starting at `origin` and extending along
+/// the positive X (east) and Z (south) axes, with each cell `cell_size` units across.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadeMapArea
+{
+    /// Corner of the area with the lowest X and Z values
+    pub origin: Vec3,
+    /// Number of cells along the X axis
+    pub width: usize,
+    /// Number of cells along the Z axis
+    pub depth: usize,
+    /// Size of each cell in world units
+    pub cell_size: f32,
+}
+
+impl ShadeMapArea
+{
+    /// World space position of the center of the cell at `(x, z)`
+    pub fn cell_center(&self, x: usize, z: usize) -> Vec3 {
+        self.origin + Vec3::new(x as f32 + 0.5, 0.0, z as f32 + 0.5) * self.cell_size
+    }
+}
+
+/// How much of the day each cell of a [`ShadeMapArea`] spends in shade, baked with
+/// [`bake_shade_map`]
+/// 
+/// Values are stored row by row, so the value for cell `(x, z)` is at `shade[z * width + x]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadeMap
+{
+    /// Area the shade map was baked over
+    pub area: ShadeMapArea,
+    /// Fraction of daylight hours each cell spends in shade, from `0.0` (always in direct sunlight)
+    /// to `1.0` (never in direct sunlight)
+    pub shade: Vec<f32>,
+}
+
+impl ShadeMap
+{
+    /// Fraction of daylight hours the cell at `(x, z)` spends in shade
+    pub fn shade_at(&self, x: usize, z: usize) -> f32 {
+        self.shade[z * self.area.width + x]
+    }
+
+    /// Fraction of daylight hours spent in shade at a world space position, or `None` if the
+    /// position is outside the baked area
+    pub fn shade_at_position(&self, position: Vec3) -> Option<f32> {
+        let local = (position - self.area.origin) / self.area.cell_size;
+        if local.x < 0.0 || local.z < 0.0 {
+            return None;
+        }
+        let (x, z) = (local.x as usize, local.z as usize);
+        (x < self.area.width && z < self.area.depth).then(|| self.shade_at(x, z))
+    }
+}
+
+/// Bakes how much of the day each cell of a static scene spends in shade
+/// 
+/// The sun is sampled `samples_per_day` times through the day in the [`Environment`]'s current
+/// time of year. For every sample where the sun is above the horizon, `is_occluded` is called with
+/// the center of each cell and the direction *towards* the sun, and should return `true` if
+/// something in the scene blocks the sun from that point. Usually this would be a raycast into
+/// your physics engine or a check against a list of simple shapes.
+/// 
+/// ```no_run
+/// # use bevy::math::{Vec3, bounding::{Aabb3d, RayCast3d}};
+/// # use kj_bevy_realistic_sun::Environment;
+/// # use kj_bevy_realistic_sun::bake::{bake_shade_map, ShadeMapArea};
+/// // A market building to find shady spots around
+/// let building = Aabb3d::new(Vec3::new(10.0, 2.0, 10.0), Vec3::new(3.0, 2.0, 3.0));
+/// let area = ShadeMapArea { origin: Vec3::ZERO, width: 20, depth: 20, cell_size: 1.0 };
+/// let environment = Environment::default()
+///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///     .with_latitude_deg(40.0);
+/// let shade_map = bake_shade_map(&environment, area, 48, |point, to_sun| {
+///     let ray = RayCast3d::new(point, bevy::math::Dir3::new(to_sun).unwrap(), 100.0);
+///     ray.aabb_intersection_at(&building).is_some()
+/// });
+/// ```
+/// 
+/// Cells that never see the sun because it doesn't rise that day at all are fully shaded. The
+/// result is useful for placing NPC idle spots, market stalls or solar panels.
+pub fn bake_shade_map(
+    environment: &Environment,
+    area: ShadeMapArea,
+    samples_per_day: u32,
+    mut is_occluded: impl FnMut(Vec3, Vec3) -> bool,
+) -> ShadeMap {
+    let cells = area.width * area.depth;
+    let mut shaded_samples = vec![0u32; cells];
+    let mut daylight_samples = 0u32;
+    let samples = samples_per_day.max(1);
+    for sample in 0..samples {
+        let time_of_day = (sample as f32 + 0.5) / samples as f32 * 24.0 * HOURS_TO_RAD;
+        let to_sun = environment.with_time_of_day(time_of_day).sun_direction();
+        if to_sun.y <= 0.0 {
+            continue;
+        }
+        daylight_samples += 1;
+        for z in 0..area.depth {
+            for x in 0..area.width {
+                if is_occluded(area.cell_center(x, z), to_sun) {
+                    shaded_samples[z * area.width + x] += 1;
+                }
+            }
+        }
+    }
+    let shade = shaded_samples.into_iter()
+        .map(|shaded| if daylight_samples == 0 { 1.0 } else { shaded as f32 / daylight_samples as f32 })
+        .collect();
+    ShadeMap { area, shade }
+}
+
+
+/// Sun directions baked into a lookup table with [`bake_sun_path_lut`], for skies drawn entirely
+/// in a shader
+/// 
+/// Directions point *towards* the sun and are stored row by row, so the direction for texel
+/// `(x, y)` is at `directions[y * width + x]`. Columns go through the day from solar midnight,
+/// and rows go through the year from the winter solstice. Each texel is sampled at its center.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SunPathLut
+{
+    /// Number of texels through the day
+    pub width: usize,
+    /// Number of texels through the year, `1` for a single day
+    pub height: usize,
+    /// Unit vector towards the sun at each texel, row by row
+    pub directions: Vec<Vec3>,
+}
+
+impl SunPathLut
+{
+    /// Direction towards the sun at texel `(x, y)`
+    pub fn direction_at(&self, x: usize, y: usize) -> Vec3 {
+        self.directions[y * self.width + x]
+    }
+
+    /// Direction towards the sun at the nearest texel to `day_phase` and `year_phase`, both from
+    /// `0.0` to `1.0` and wrapped outside that range
+    pub fn nearest(&self, day_phase: f32, year_phase: f32) -> Vec3 {
+        let texel = |phase: f32, size: usize| ((phase.rem_euclid(1.0) * size as f32) as usize).min(size - 1);
+        self.direction_at(texel(day_phase, self.width), texel(year_phase, self.height))
+    }
+
+    /// Raw texture data for an `Rgba32Float` texture, with the direction in the RGB channels and
+    /// `1.0` in alpha where the sun is above the horizon or `0.0` where it's below
+    /// 
+    /// Pass it to Bevy's `Image::new` with a `width` by `height` `Extent3d` and
+    /// `TextureFormat::Rgba32Float`, and sample it with a non-filtering or linear sampler
+    pub fn texture_data(&self) -> Vec<u8> {
+        self.directions.iter()
+            .flat_map(|direction| {
+                let daylight = if direction.y > 0.0 { 1.0 } else { 0.0 };
+                [direction.x, direction.y, direction.z, daylight]
+            })
+            .flat_map(f32::to_ne_bytes)
+            .collect()
+    }
+}
+
+/// Bakes the sun's direction through a day or a whole year into a [`SunPathLut`]
+/// 
+/// ```no_run
+/// # use bevy::math::UVec2;
+/// # use kj_bevy_realistic_sun::Environment;
+/// # use kj_bevy_realistic_sun::bake::bake_sun_path_lut;
+/// let environment = Environment::default()
+///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///     .with_latitude_deg(50.0);
+/// // 96 samples a day, every day of the year
+/// let lut = bake_sun_path_lut(&environment, UVec2::new(96, 365));
+/// let texture_data = lut.texture_data();
+/// ```
+/// 
+/// `resolution.x` is the number of samples through the day and `resolution.y` the number of days
+/// through the year. With a height of `1` only the [`Environment`]'s current time of year is baked.
+/// Everything else, like latitude, axial tilt and precession, is taken from `environment`, and
+/// its time of day is ignored.
+pub fn bake_sun_path_lut(environment: &Environment, resolution: UVec2) -> SunPathLut {
+    let (width, height) = (resolution.x.max(1) as usize, resolution.y.max(1) as usize);
+    let mut directions = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let time_of_year = if height == 1 {
+            environment.time_of_year
+        } else {
+            Environment::DATE_WINTER + (y as f32 + 0.5) / height as f32 * std::f32::consts::TAU
+        };
+        let dated = environment.with_date(time_of_year);
+        // solar midnight in clock time, allowing for longitude, UTC offset and the equation of time
+        let midnight = dated.solar_to_clock_time(-std::f32::consts::PI);
+        for x in 0..width {
+            let time_of_day = midnight + (x as f32 + 0.5) / width as f32 * std::f32::consts::TAU;
+            directions.push(dated.with_time_of_day(time_of_day).sun_direction());
+        }
+    }
+    SunPathLut { width, height, directions }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn flat_equator_equinox() {
+        let environment = Environment::default().with_date(Environment::DATE_SPRING);
+        let heights = vec![0.0; 4];
+        let heightmap = Heightmap::new(&heights, 2, 2, 1.0);
+        let exposure = bake_exposure(&environment, &heightmap, &ExposureSettings::default());
+        assert!(abs_diff_eq!(exposure.sunlit_hours_at(0, 0), 12.0, epsilon = 0.01));
+        let expected_irradiation = 1000.0 * 24.0 / PI;
+        assert!(abs_diff_eq!(exposure.irradiation_at(1, 1), expected_irradiation, epsilon = 5.0));
+    }
+
+    #[test]
+    fn wall_casts_shadow() {
+        // a tall wall along the east edge blocks the morning sun
+        let mut heights = vec![0.0; 5 * 5];
+        for z in 0..5 {
+            heights[z * 5 + 4] = 100.0;
+        }
+        let heightmap = Heightmap::new(&heights, 5, 5, 1.0);
+        let exposure = bake_exposure(&Environment::default(), &heightmap, &ExposureSettings::default());
+        assert!(exposure.sunlit_hours_at(0, 2) < 7.0);
+    }
+
+    #[test]
+    fn box_shades_morning() {
+        use bevy::math::bounding::{Aabb3d, RayCast3d};
+        // a box directly east of the first cell blocks the sun until it gets high enough
+        let occluder = Aabb3d::new(Vec3::new(3.0, 0.0, 0.5), Vec3::new(0.5, 2.0, 10.0));
+        let area = ShadeMapArea { origin: Vec3::ZERO, width: 1, depth: 1, cell_size: 1.0 };
+        let shade_map = bake_shade_map(&Environment::default(), area, 96, |point, to_sun| {
+            let ray = RayCast3d::new(point, Dir3::new(to_sun).unwrap(), 100.0);
+            ray.aabb_intersection_at(&occluder).is_some()
+        });
+        let shade = shade_map.shade_at(0, 0);
+        assert!(shade > 0.1 && shade < 0.5, "{}", shade);
+        assert_eq!(shade_map.shade_at_position(Vec3::new(0.5, 0.0, 0.5)), Some(shade));
+        assert_eq!(shade_map.shade_at_position(Vec3::new(-0.5, 0.0, 0.5)), None);
+    }
+
+    #[test]
+    fn sun_path_lut() {
+        let environment = Environment::default().with_date(Environment::DATE_SPRING);
+        let day = bake_sun_path_lut(&environment, UVec2::new(24, 1));
+        assert_eq!(day.directions.len(), 24);
+        assert!(day.direction_at(0, 0).y < -0.9);
+        assert!(day.nearest(0.5, 0.0).y > 0.9);
+        assert_eq!(day.texture_data().len(), 24 * 4 * 4);
+        let year = bake_sun_path_lut(&environment
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(45.0), UVec2::new(24, 12));
+        // noon is higher in summer, halfway through the year, than in winter
+        assert!(year.direction_at(12, 6).y > year.direction_at(12, 0).y);
+    }
+}
@@ -0,0 +1,77 @@
+//! Contains the [`SecondarySun`] component and [`StarOffset`] for planets with two suns
+use bevy::prelude::*;
+use crate::conversion::*;
+
+
+/// Attach next to a [`Sun`](crate::Sun) to make it the second star of a binary system
+/// 
+/// The light is pointed the same way as any other [`Sun`](crate::Sun), except it follows the
+/// star offset from the primary by [`Environment::secondary_sun`](crate::Environment::secondary_sun)
+/// instead of the primary star itself.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{SecondarySun, Sun};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// commands.spawn((DirectionalLight::default(), Sun));
+/// commands.spawn((
+///     DirectionalLight {
+///         illuminance: 20_000.0,
+///         ..Default::default()
+///     },
+///     Sun,
+///     SecondarySun,
+/// ));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct SecondarySun;
+
+/// Where the second star of a binary system is in the sky compared to the primary, stored in
+/// [`Environment::secondary_sun`](crate::Environment::secondary_sun)
+/// 
+/// Defaults to no offset, with both stars in the same place.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct StarOffset
+{
+    /// Angle in radians the secondary star is ahead of the primary along the planet's orbit.
+    /// Positive values rise and set later than the primary, negative values earlier
+    pub elongation: f32,
+
+    /// Angle in radians the secondary star is north of the planet's orbital plane
+    pub inclination: f32,
+}
+
+impl StarOffset
+{
+    /// Sets the elongation in radians
+    pub const fn with_elongation(mut self, elongation: f32) -> Self {
+        self.elongation = elongation;
+        self
+    }
+
+    /// Sets the elongation in degrees
+    pub const fn with_elongation_deg(self, elongation: f32) -> Self {
+        self.with_elongation(elongation * DEG_TO_RAD)
+    }
+
+    /// Sets the inclination in radians
+    pub const fn with_inclination(mut self, inclination: f32) -> Self {
+        self.inclination = inclination;
+        self
+    }
+
+    /// Sets the inclination in degrees
+    pub const fn with_inclination_deg(self, inclination: f32) -> Self {
+        self.with_inclination(inclination * DEG_TO_RAD)
+    }
+}
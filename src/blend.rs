@@ -0,0 +1,159 @@
+//! Contains the [`BlendedEnvironment`] resource for fading between named environment profiles
+use std::collections::HashMap;
+use bevy::prelude::*;
+use crate::{CinematicOverride, CinematicOverrideId, CinematicOverrides, Environment};
+
+
+/// A set of named [`Environment`] profiles that the sun can be blended towards over time
+/// 
+/// Useful for interiors, cutscene volumes and the like, where the sun should ease towards a fixed
+/// "studio" setup while the player is inside and ease back to the real [`Environment`] once they
+/// leave. Blends are applied through the [`CinematicOverrides`] stack, so the [`Environment`]
+/// resource itself is never touched and a running [`SolarClock`](crate::SolarClock) carries on
+/// underneath. The axial tilt, latitude, time of day and time of year are blended.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{BlendedEnvironment, Environment};
+/// fn setup(mut commands: Commands) {
+///     commands.insert_resource(BlendedEnvironment::default()
+///         .with_profile("studio", Environment::default().with_latitude_deg(30.0).with_hours_since_noon(-2.0)));
+/// }
+/// 
+/// fn enter_cutscene_volume(mut blended: ResMut<BlendedEnvironment>) {
+///     blended.blend_to("studio", 1.5);
+/// }
+/// 
+/// fn leave_cutscene_volume(mut blended: ResMut<BlendedEnvironment>) {
+///     blended.stop(3.0);
+/// }
+/// ```
+/// 
+/// Not added by default, insert it to enable environment blending
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct BlendedEnvironment
+{
+    profiles: HashMap<String, Environment>,
+    priority: i32,
+    request: Option<BlendRequest>,
+    active: Option<(String, CinematicOverrideId)>,
+}
+
+/// A change to the blend, applied on the next update
+#[derive(Clone, Debug)]
+#[derive(Reflect)]
+enum BlendRequest {
+    To { profile: String, seconds: f32 },
+    Stop { seconds: f32 },
+}
+
+impl BlendedEnvironment
+{
+    /// Adds a named profile
+    pub fn with_profile(mut self, name: impl Into<String>, environment: Environment) -> Self {
+        self.insert_profile(name, environment);
+        self
+    }
+
+    /// Sets the priority of the [`CinematicOverride`]s pushed by blends. Defaults to `0`
+    pub const fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Adds or replaces a named profile. Replacing the active profile takes effect the next time
+    /// it's blended to
+    pub fn insert_profile(&mut self, name: impl Into<String>, environment: Environment) {
+        self.profiles.insert(name.into(), environment);
+    }
+
+    /// The profile with the given name, if there is one
+    pub fn profile(&self, name: &str) -> Option<&Environment> {
+        self.profiles.get(name)
+    }
+
+    /// The name of the profile currently being blended towards, or `None` if the sun is following
+    /// the [`Environment`]
+    pub fn active_profile(&self) -> Option<&str> {
+        match &self.request {
+            Some(BlendRequest::To { profile, .. }) => Some(profile.as_str()),
+            Some(BlendRequest::Stop { .. }) => None,
+            None => self.active.as_ref().map(|(profile, _)| profile.as_str()),
+        }
+    }
+
+    /// Starts blending towards the named profile over `seconds`, fading out any profile that was
+    /// already active over the same time. Does nothing if there's no profile with that name
+    pub fn blend_to(&mut self, profile: impl Into<String>, seconds: f32) {
+        self.request = Some(BlendRequest::To { profile: profile.into(), seconds });
+    }
+
+    /// Starts blending back to the [`Environment`] over `seconds`
+    pub fn stop(&mut self, seconds: f32) {
+        self.request = Some(BlendRequest::Stop { seconds });
+    }
+}
+
+/// Turns [`BlendedEnvironment`] requests into [`CinematicOverrides`]
+pub(crate) fn drive_blended_environment(
+    mut blended: ResMut<BlendedEnvironment>,
+    mut overrides: ResMut<CinematicOverrides>,
+){
+    if blended.request.is_none() {
+        return;
+    }
+    let blended = blended.as_mut();
+    let (target, seconds) = match blended.request.take() {
+        Some(BlendRequest::To { profile, seconds }) => (Some(profile), seconds),
+        Some(BlendRequest::Stop { seconds }) => (None, seconds),
+        None => return,
+    };
+    if target.as_ref() == blended.active.as_ref().map(|(profile, _)| profile) {
+        return;
+    }
+    let environment = match &target {
+        Some(profile) => match blended.profiles.get(profile) {
+            Some(environment) => Some(*environment),
+            None => return,
+        },
+        None => None,
+    };
+    if let Some((_, id)) = blended.active.take() {
+        overrides.release_over(id, seconds);
+    }
+    if let (Some(profile), Some(environment)) = (target, environment) {
+        let id = overrides.push(CinematicOverride::full(environment)
+            .with_priority(blended.priority)
+            .with_blend_in(seconds)
+            .with_blend_out(seconds));
+        blended.active = Some((profile, id));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RealisticSunDirectionPlugin, SunState};
+
+    #[test]
+    fn blends_to_profile_and_back() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::headless()));
+        let studio = Environment::default().with_latitude_deg(10.0).with_hours_since_noon(-3.0);
+        app.insert_resource(BlendedEnvironment::default().with_profile("studio", studio));
+        app.world_mut().resource_mut::<BlendedEnvironment>().blend_to("studio", 0.0);
+        app.update();
+        let state = *app.world().resource::<SunState>();
+        assert!(state.direction.abs_diff_eq(studio.sun_direction(), 1e-5));
+        assert_eq!(app.world().resource::<BlendedEnvironment>().active_profile(), Some("studio"));
+
+        app.world_mut().resource_mut::<BlendedEnvironment>().stop(0.0);
+        app.update();
+        app.update();
+        assert!(app.world().resource::<CinematicOverrides>().is_empty());
+        assert_eq!(app.world().resource::<BlendedEnvironment>().active_profile(), None);
+    }
+}
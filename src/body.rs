@@ -0,0 +1,216 @@
+//! Contains the [`CelestialBody`] trait, the [`CelestialRegistry`] of bodies in the sky and the
+//! [`Tracks`] component that points entities at them
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, LunarOrbit, StarOffset, SunEnvironment, SunReferenceFrame};
+
+
+/// Anything in the sky whose position can be worked out from an [`Environment`]
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{CelestialBody, Environment};
+/// /// A comet that hangs just above the sun
+/// struct Comet;
+/// impl CelestialBody for Comet {
+///     fn direction(&self, environment: &Environment) -> Vec3 {
+///         (environment.sun_direction() + Vec3::Y * 0.1).normalize()
+///     }
+/// }
+/// ```
+/// 
+/// [`LunarOrbit`] is a body on its own orbit like the moon, and [`StarOffset`] is a body fixed
+/// in place next to the sun like the second star of a binary system. Register bodies in the
+/// [`CelestialRegistry`] to point entities at them with [`Tracks`].
+pub trait CelestialBody: Send + Sync + 'static
+{
+    /// Unit vector pointing from the world *towards* the body
+    fn direction(&self, environment: &Environment) -> Vec3;
+}
+
+impl CelestialBody for LunarOrbit {
+    fn direction(&self, environment: &Environment) -> Vec3 {
+        environment.orbiting_body_direction(self)
+    }
+}
+
+impl CelestialBody for StarOffset {
+    fn direction(&self, environment: &Environment) -> Vec3 {
+        environment.offset_body_rotation(self.elongation, self.inclination) * Vec3::Y
+    }
+}
+
+/// Bodies built into every [`CelestialRegistry`], using the values stored in the [`Environment`]
+enum EnvironmentBody
+{
+    Sun,
+    SecondarySun,
+    Moon,
+}
+
+impl CelestialBody for EnvironmentBody {
+    fn direction(&self, environment: &Environment) -> Vec3 {
+        match self {
+            Self::Sun => environment.sun_direction(),
+            Self::SecondarySun => environment.secondary_sun_direction(),
+            Self::Moon => environment.moon_direction(),
+        }
+    }
+}
+
+/// Identifies a body in the [`CelestialRegistry`]
+/// 
+/// The sun, secondary sun and moon from the [`Environment`] are always registered, with the ids
+/// [`BodyId::SUN`], [`BodyId::SECONDARY_SUN`] and [`BodyId::MOON`]. Other ids are handed out by
+/// [`CelestialRegistry::register`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub struct BodyId(u32);
+
+impl BodyId
+{
+    /// The sun, following [`Environment::sun_direction`]
+    pub const SUN: Self = Self(0);
+    /// The second star of a binary system, following [`Environment::secondary_sun_direction`]
+    pub const SECONDARY_SUN: Self = Self(1);
+    /// The moon, following [`Environment::moon_direction`]
+    pub const MOON: Self = Self(2);
+}
+
+/// Every body in the sky that entities can [track](Tracks)
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{CelestialRegistry, LunarOrbit, Tracks};
+/// fn setup(mut commands: Commands, mut registry: ResMut<CelestialRegistry>) {
+///     // A second, faster moon
+///     let phobos = registry.register(LunarOrbit::default()
+///         .with_period_days(0.3, 668.6)
+///         .with_inclination_deg(1.1));
+///     commands.spawn((DirectionalLight { illuminance: 0.05, ..default() }, Tracks(phobos)));
+/// }
+/// ```
+/// 
+/// Added by the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin) with the
+/// bodies in the [`Environment`] already registered. Bodies can't be removed, since entities may
+/// still be tracking them.
+#[derive(Resource)]
+pub struct CelestialRegistry
+{
+    bodies: Vec<Box<dyn CelestialBody>>,
+}
+
+impl Default for CelestialRegistry {
+    fn default() -> Self {
+        Self {
+            bodies: vec![
+                Box::new(EnvironmentBody::Sun),
+                Box::new(EnvironmentBody::SecondarySun),
+                Box::new(EnvironmentBody::Moon),
+            ],
+        }
+    }
+}
+
+impl CelestialRegistry
+{
+    /// Adds a body to the sky, returning the id to track it with
+    pub fn register(&mut self, body: impl CelestialBody) -> BodyId {
+        self.bodies.push(Box::new(body));
+        BodyId(self.bodies.len() as u32 - 1)
+    }
+
+    /// The body registered with `id`, or `None` if there isn't one
+    pub fn get(&self, id: BodyId) -> Option<&dyn CelestialBody> {
+        self.bodies.get(id.0 as usize).map(|body| body.as_ref())
+    }
+
+    /// Direction towards the body registered with `id`, or `None` if there isn't one
+    pub fn direction(&self, id: BodyId, environment: &Environment) -> Option<Vec3> {
+        self.get(id).map(|body| body.direction(environment))
+    }
+
+    /// Number of registered bodies, including the built in ones
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+
+    /// Always `false`, since the built in bodies are always registered
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+}
+
+/// Points an entity's light at a body in the [`CelestialRegistry`]
+/// 
+/// Works the same way as a [`Moon`](crate::Moon), with the entity's [`Transform`] rotated every
+/// frame so its forward direction shines away from the body. The entity's own
+/// [`SunEnvironment`] is used if it has one. `Tracks(BodyId::MOON)` behaves just like a
+/// [`Moon`](crate::Moon).
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{BodyId, Tracks};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// commands.spawn((DirectionalLight::default(), Tracks(BodyId::MOON)));
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[require(Transform)]
+pub struct Tracks(pub BodyId);
+
+/// Runs once per frame, pointing every entity with a [`Tracks`] component away from its body
+pub(crate) fn update_tracking_lights(
+    mut lights: Query<(Entity, &Tracks, &mut Transform, Option<&SunEnvironment>)>,
+    visibilities: Query<(Option<&Visibility>, Option<&ChildOf>)>,
+    registry: Res<CelestialRegistry>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    let environment = cinematic_overrides.resolve(&environment);
+    for (entity, tracks, mut transform, light_environment) in &mut lights {
+        if crate::is_hidden(entity, &visibilities) {
+            continue;
+        }
+        let environment = light_environment.map_or(&environment, |light_environment| &light_environment.0);
+        let Some(direction) = registry.direction(tracks.0, environment) else {
+            continue;
+        };
+        let rotation = reference_frame.light_rotation(-direction);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Moon, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn tracks_registered_bodies() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default().with_hours_since_noon(-2.0));
+        let orbit = LunarOrbit::default().with_period(0.01).with_phase_offset(1.0);
+        let custom = app.world_mut().resource_mut::<CelestialRegistry>().register(orbit);
+        let moon = app.world_mut().spawn(Moon).id();
+        let tracking_moon = app.world_mut().spawn(Tracks(BodyId::MOON)).id();
+        let tracking_custom = app.world_mut().spawn(Tracks(custom)).id();
+        app.update();
+        let rotation = |entity| app.world().get::<Transform>(entity).unwrap().rotation;
+        assert_eq!(rotation(moon), rotation(tracking_moon));
+        let environment = app.world().resource::<Environment>();
+        let forward = rotation(tracking_custom) * Vec3::NEG_Z;
+        assert!(forward.abs_diff_eq(-orbit.direction(environment), 1e-5));
+        assert!(!forward.abs_diff_eq(-environment.moon_direction(), 1e-2));
+    }
+}
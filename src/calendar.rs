@@ -0,0 +1,270 @@
+//! Contains the [`Calendar`] used to convert between dates and
+//! [`time_of_year`](crate::Environment::time_of_year)
+use std::f64::consts::TAU;
+use bevy::prelude::*;
+use crate::conversion::wrap_angle_f64;
+
+
+/// A single month in a [`Calendar`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct CalendarMonth
+{
+    /// Name of the month, for displaying dates
+    pub name: String,
+
+    /// Number of days in the month outside of leap years
+    pub days: u32,
+}
+
+/// A date and clock time in a [`Calendar`], returned by
+/// [`Environment::calendar_date`](crate::Environment::calendar_date)
+/// 
+/// Months and days start at `1`, like they are written on a calendar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub struct CalendarDate
+{
+    /// Month of the year, starting at `1`
+    pub month: u32,
+    /// Day of the month, starting at `1`
+    pub day: u32,
+    /// Hour on the clock, from `0` to `23`
+    pub hour: u32,
+    /// Minute on the clock, from `0` to `59`
+    pub minute: u32,
+}
+
+/// Months, year length and epoch used to convert dates into a
+/// [`time_of_year`](crate::Environment::time_of_year) and back
+/// 
+/// Lets you think in dates instead of radians. [`Calendar::gregorian`] (also the default) is
+/// Earth's calendar, and [`Calendar::new`] makes a calendar for any other planet.
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{Calendar, Environment};
+/// // 9:30 AM on the 4th of July 2025
+/// let environment = Environment::default()
+///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///     .with_latitude_deg(40.7)
+///     .with_calendar_date(&Calendar::gregorian(), 2025, 7, 4, 9, 30);
+/// // A fantasy world with a 100 day year split into 4 seasons
+/// let calendar = Calendar::new([("Thaw", 25), ("Bloom", 25), ("Harvest", 25), ("Frost", 25)])
+///     .with_solstice(0, 37.5);
+/// let environment = environment.with_calendar_date(&calendar, 1, 2, 13, 12, 0);
+/// ```
+/// 
+/// **Note:** the [`Environment`](crate::Environment) only stores the time within the current year,
+/// so the year is only used to count leap days and the drift of the solstice between years
+#[derive(Clone, Debug, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct Calendar
+{
+    /// Months of the year in order
+    pub months: Vec<CalendarMonth>,
+
+    /// Length of the solar year in days, from one summer solstice to the next. Can be fractional
+    /// when the calendar has leap years to make up the difference
+    pub days_per_year: f64,
+
+    /// Month (starting at `1`) that gains an extra day every 4 years, except for years divisible
+    /// by 100 but not 400, the same as February in the Gregorian calendar
+    pub leap_month: Option<u32>,
+
+    /// Year in which the summer solstice is at [`solstice_day`](Calendar::solstice_day)
+    pub epoch_year: i32,
+
+    /// Day of the [`epoch_year`](Calendar::epoch_year) the summer solstice is on, counted from
+    /// `0.0` at midnight UTC at the start of the year. Fractional days are the time of day
+    pub solstice_day: f64,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self::gregorian()
+    }
+}
+
+impl Calendar
+{
+    /// Earth's Gregorian calendar, with the June solstice of 2000 as the epoch
+    pub fn gregorian() -> Self {
+        Self::new([
+            ("January", 31),
+            ("February", 28),
+            ("March", 31),
+            ("April", 30),
+            ("May", 31),
+            ("June", 30),
+            ("July", 31),
+            ("August", 31),
+            ("September", 30),
+            ("October", 31),
+            ("November", 30),
+            ("December", 31),
+        ])
+            .with_days_per_year(365.24219)
+            .with_leap_month(2)
+            // 2000-06-21 01:48 UTC
+            .with_solstice(2000, 172.075)
+    }
+
+    /// Creates a calendar from `(name, days)` pairs for each month, with the year exactly as long
+    /// as all its months and the summer solstice at the start of year `0`
+    pub fn new(months: impl IntoIterator<Item = (impl Into<String>, u32)>) -> Self {
+        let months: Vec<CalendarMonth> = months.into_iter()
+            .map(|(name, days)| CalendarMonth { name: name.into(), days })
+            .collect();
+        let days_per_year = months.iter().map(|month| month.days as f64).sum();
+        Self {
+            months,
+            days_per_year,
+            leap_month: None,
+            epoch_year: 0,
+            solstice_day: 0.0,
+        }
+    }
+
+    /// Sets the length of the solar year in days
+    pub fn with_days_per_year(mut self, days_per_year: f64) -> Self {
+        self.days_per_year = days_per_year;
+        self
+    }
+
+    /// Sets the month (starting at `1`) that gains a day in leap years
+    pub fn with_leap_month(mut self, month: u32) -> Self {
+        self.leap_month = Some(month);
+        self
+    }
+
+    /// Sets the day of `year` the summer solstice is on, counted from `0.0` at the start of the year
+    pub fn with_solstice(mut self, year: i32, day: f64) -> Self {
+        self.epoch_year = year;
+        self.solstice_day = day;
+        self
+    }
+
+    /// Whether `year` has an extra day in the [`leap_month`](Calendar::leap_month)
+    pub fn is_leap_year(&self, year: i32) -> bool {
+        self.leap_month.is_some() && (year % 4 == 0 && (year % 100 != 0 || year % 400 == 0))
+    }
+
+    /// Number of days in `month` (starting at `1`) of `year`, or `0` if there is no such month
+    pub fn days_in_month(&self, year: i32, month: u32) -> u32 {
+        let Some(days) = month.checked_sub(1).and_then(|index| self.months.get(index as usize)).map(|month| month.days) else {
+            return 0;
+        };
+        if self.leap_month == Some(month) && self.is_leap_year(year) { days + 1 } else { days }
+    }
+
+    /// Number of days in `year`
+    pub fn days_in_year(&self, year: i32) -> u32 {
+        (1..=self.months.len() as u32).map(|month| self.days_in_month(year, month)).sum()
+    }
+
+    /// Name of `month` (starting at `1`), or `None` if there is no such month
+    pub fn month_name(&self, month: u32) -> Option<&str> {
+        let index = month.checked_sub(1)?;
+        self.months.get(index as usize).map(|month| month.name.as_str())
+    }
+
+    /// Whole days from the start of the epoch year to the start of `year`
+    fn days_before_year(&self, year: i32) -> i64 {
+        let base_days: i64 = self.months.iter().map(|month| month.days as i64).sum();
+        let mut days = (year as i64 - self.epoch_year as i64) * base_days;
+        if self.leap_month.is_some() {
+            days += leap_years_before(year) - leap_years_before(self.epoch_year);
+        }
+        days
+    }
+
+    /// Whole days from the start of `year` to the start of `day` in `month`. Days past the end of
+    /// a month carry on into the next month
+    fn day_of_year(&self, year: i32, month: u32, day: u32) -> u32 {
+        let month = month.clamp(1, self.months.len().max(1) as u32);
+        let before: u32 = (1..month).map(|month| self.days_in_month(year, month)).sum();
+        before + day.saturating_sub(1)
+    }
+
+    /// Time of year in radians for a date, with the time of day given in hours since midnight UTC
+    pub(crate) fn time_of_year(&self, year: i32, month: u32, day: u32, utc_hours: f64) -> f32 {
+        if self.days_per_year <= 0.0 {
+            return 0.0;
+        }
+        let days = self.days_before_year(year) as f64
+            + self.day_of_year(year, month, day) as f64
+            + utc_hours / 24.0
+            - self.solstice_day;
+        wrap_angle_f64(days / self.days_per_year * TAU) as f32
+    }
+
+    /// Month and day (both starting at `1`) in the epoch year for a time of year in radians,
+    /// given the local clock time in hours since midnight and the local time zone in hours
+    pub(crate) fn month_and_day(&self, time_of_year: f32, local_hours: f64, utc_offset_hours: f64) -> (u32, u32) {
+        let year_days = self.days_in_year(self.epoch_year);
+        if year_days == 0 {
+            return (1, 1);
+        }
+        let utc_days = (time_of_year as f64).rem_euclid(TAU) / TAU * self.days_per_year + self.solstice_day;
+        // the clock time is known exactly, so round to the midnight it was counted from. The
+        // calendar year and solar year aren't quite the same length, so near the end of the year
+        // pick whichever lap of the solar year lands closest to a midnight
+        let local_midnight = (utc_days + (utc_offset_hours - local_hours) / 24.0).rem_euclid(self.days_per_year);
+        let last_day = (year_days - 1) as f64;
+        let mut day = [local_midnight, local_midnight - self.days_per_year]
+            .into_iter()
+            .map(|days| (days.round().clamp(0.0, last_day), days))
+            .min_by(|a, b| (a.0 - a.1).abs().total_cmp(&(b.0 - b.1).abs()))
+            .map_or(0, |(day, _)| day as u32);
+        for month in 1..=self.months.len() as u32 {
+            let days = self.days_in_month(self.epoch_year, month);
+            if day < days {
+                return (month, day + 1);
+            }
+            day -= days;
+        }
+        (self.months.len() as u32, day + 1)
+    }
+}
+
+/// Number of Gregorian leap years before `year`
+fn leap_years_before(year: i32) -> i64 {
+    let year = year as i64 - 1;
+    year.div_euclid(4) - year.div_euclid(100) + year.div_euclid(400)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::abs_diff_eq;
+    use crate::Environment;
+
+    #[test]
+    fn gregorian_solstices() {
+        let calendar = Calendar::gregorian();
+        assert!(calendar.is_leap_year(2000) && calendar.is_leap_year(2024));
+        assert!(!calendar.is_leap_year(1900) && !calendar.is_leap_year(2023));
+        assert_eq!(calendar.days_in_month(2024, 2), 29);
+        // 2023-06-21 14:57 UTC
+        let summer = calendar.time_of_year(2023, 6, 21, 14.95);
+        assert!(abs_diff_eq!(summer, 0.0, epsilon = 0.03), "{}", summer);
+        // 2023-12-22 03:27 UTC
+        let winter = calendar.time_of_year(2023, 12, 22, 3.45);
+        assert!(abs_diff_eq!(winter.abs(), std::f32::consts::PI, epsilon = 0.03), "{}", winter);
+    }
+
+    #[test]
+    fn date_round_trip() {
+        let calendar = Calendar::gregorian();
+        for (month, day, hour, minute) in [(1, 1, 0, 0), (3, 15, 9, 30), (6, 21, 12, 0), (12, 31, 23, 59)] {
+            let environment = Environment::default()
+                .with_utc_offset_hours(-5.0)
+                .with_calendar_date(&calendar, 2000, month, day, hour, minute);
+            assert_eq!(environment.calendar_date(&calendar), CalendarDate { month, day, hour, minute });
+        }
+    }
+}
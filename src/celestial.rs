@@ -0,0 +1,77 @@
+//! Contains the [`CelestialSphere`] component that turns with the fixed stars
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, SunEnvironment};
+
+
+/// Attach to a star skybox mesh or particle field to have it wheel overhead with the sky
+/// 
+/// Any entity with this component has its [`Transform`] rotation set every frame to
+/// [`Environment::celestial_sphere_rotation`], the apparent rotation of the fixed stars for the
+/// latitude, time of day and time of year in the [`Environment`] (or its own [`SunEnvironment`]).
+/// The stars turn once a day around the celestial pole, and drift a little each day compared to
+/// the sun so different stars are out at night over the course of the year.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::CelestialSphere;
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // Star meshes parented to this entity will turn with the sky
+/// commands.spawn(CelestialSphere);
+/// ```
+/// 
+/// In the sphere's local space the north celestial pole is `-Z` and the celestial equator is the
+/// `XY` plane. At the summer solstice (time of year `0.0`) the sun is in the `+Y` direction,
+/// tilted towards the north pole.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct CelestialSphere;
+
+/// Runs once per frame, rotating every entity with a [`CelestialSphere`] component
+pub(crate) fn update_celestial_spheres(
+    mut spheres: Query<(&mut Transform, Option<&SunEnvironment>), With<CelestialSphere>>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let global_rotation = cinematic_overrides.resolve(&environment).celestial_sphere_rotation();
+    for (mut transform, sphere_environment) in &mut spheres {
+        let rotation = match sphere_environment {
+            Some(sphere_environment) => sphere_environment.0.celestial_sphere_rotation(),
+            None => global_rotation,
+        };
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sun_fixed_against_stars_within_a_day() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(50.0)
+            .with_date(1.0);
+        let pole = Vec3::new(0.0, 50f32.to_radians().sin(), -50f32.to_radians().cos());
+        let mut previous = None;
+        for hours in [-6.0, 0.0, 3.0] {
+            let environment = environment.with_hours_since_noon(hours);
+            let rotation = environment.celestial_sphere_rotation();
+            assert!((rotation * Vec3::NEG_Z).abs_diff_eq(pole, 1e-5));
+            let local_sun = rotation.inverse() * environment.sun_direction();
+            if let Some(previous) = previous {
+                assert!(local_sun.abs_diff_eq(previous, 1e-5));
+            }
+            previous = Some(local_sun);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Contains the [`EnvironmentChanged`] event, triggered when the [`Environment`] moves by more than
+//! an [`EnvironmentChangeThreshold`]
+use bevy::prelude::*;
+use crate::Environment;
+use crate::conversion::wrap_angle;
+
+
+/// Triggered when the [`Environment`] resource has moved meaningfully since the last time this
+/// event was triggered
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::EnvironmentChanged;
+/// # let mut app = App::new();
+/// app.add_observer(|changed: On<EnvironmentChanged>| {
+///     if changed.old.latitude != changed.new.latitude {
+///         println!("Travelled to a new latitude, time to swap the ambient birdsong");
+///     }
+/// });
+/// ```
+/// 
+/// Audio, weather and UI systems can react to this instead of checking the resource every frame.
+/// Small changes, like a running [`SolarClock`](crate::SolarClock), build up until one of the
+/// values is more than the [`EnvironmentChangeThreshold`] away from [`old`](EnvironmentChanged::old),
+/// so a slowly moving sun triggers it every so often rather than every frame.
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct EnvironmentChanged
+{
+    /// The environment the last time this event was triggered, or when the app started
+    pub old: Environment,
+    /// The environment now
+    pub new: Environment,
+}
+
+/// How far in radians any of the [`Environment`]'s angles have to move before an
+/// [`EnvironmentChanged`] event is triggered
+/// 
+/// Defaults to [`EnvironmentChangeThreshold::DEFAULT`]. Set it to `0.0` to trigger on every change.
+/// Added by the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin)
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EnvironmentChangeThreshold(pub f32);
+
+impl EnvironmentChangeThreshold
+{
+    /// Default threshold of a thousandth of a radian, around 14 seconds of time of day
+    pub const DEFAULT: Self = Self(0.001);
+}
+
+impl Default for EnvironmentChangeThreshold {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The [`Environment`] the last [`EnvironmentChanged`] event was triggered for
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Resource)]
+pub(crate) struct EnvironmentChangeTracker
+{
+    reported: Option<Environment>,
+}
+
+/// Whether any of the values that place the sun moved further than `threshold` between `old`
+/// and `new`, going the short way around for the times
+fn moved_beyond(old: &Environment, new: &Environment, threshold: f32) -> bool {
+    let angle = |old: f32, new: f32| (new - old).abs() > threshold;
+    let time = |old: f32, new: f32| wrap_angle(new - old).abs() > threshold;
+    angle(old.axial_tilt, new.axial_tilt)
+        || angle(old.latitude, new.latitude)
+        || angle(old.longitude, new.longitude)
+        || angle(old.utc_offset, new.utc_offset)
+        || angle(old.horizon_offset, new.horizon_offset)
+        || angle(old.declination(), new.declination())
+        || time(old.time_of_day, new.time_of_day)
+        || time(old.time_of_year, new.time_of_year)
+}
+
+/// Triggers [`EnvironmentChanged`] when the [`Environment`] moves past the threshold
+pub(crate) fn trigger_environment_changed(
+    mut commands: Commands,
+    mut tracker: ResMut<EnvironmentChangeTracker>,
+    environment: Res<Environment>,
+    threshold: Res<EnvironmentChangeThreshold>,
+){
+    let Some(old) = tracker.reported else {
+        tracker.reported = Some(*environment);
+        return;
+    };
+    if !environment.is_changed() || !moved_beyond(&old, &environment, threshold.0) {
+        return;
+    }
+    tracker.reported = Some(*environment);
+    commands.trigger(EnvironmentChanged { old, new: *environment });
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[derive(Default, Resource)]
+    struct Triggered(Vec<EnvironmentChanged>);
+
+    #[test]
+    fn small_changes_build_up() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::headless()));
+        app.init_resource::<Triggered>();
+        app.add_observer(|changed: On<EnvironmentChanged>, mut triggered: ResMut<Triggered>| triggered.0.push(*changed));
+        app.insert_resource(EnvironmentChangeThreshold(0.1));
+        app.update();
+        for _ in 0..3 {
+            app.world_mut().resource_mut::<Environment>().time_of_day += 0.04;
+            app.update();
+        }
+        let triggered = &app.world().resource::<Triggered>().0;
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].old.time_of_day, 0.0);
+        assert!((triggered[0].new.time_of_day - 0.12).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,265 @@
+//! Contains the [`CinematicOverrides`] resource for temporarily overriding the environment
+use bevy::prelude::*;
+use crate::{Environment, EnvironmentOverride};
+use crate::conversion::wrap_angle;
+
+
+/// A temporary override for the [`Environment`], pushed onto [`CinematicOverrides`] by cutscenes
+/// and other scripted moments
+/// 
+/// The values to override are described with an [`EnvironmentOverride`], so only the values that
+/// are set get overridden. The override fades in over [`blend_in`](CinematicOverride::blend_in)
+/// seconds when it's pushed and back out over [`blend_out`](CinematicOverride::blend_out) seconds
+/// when it's released.
+#[derive(Clone, Copy, Debug)]
+#[derive(Reflect)]
+pub struct CinematicOverride
+{
+    /// Values to override
+    pub values: EnvironmentOverride,
+    /// Overrides with a higher priority are applied on top of ones with a lower priority
+    pub priority: i32,
+    /// Seconds to fade in over
+    pub blend_in: f32,
+    /// Seconds to fade out over after being released
+    pub blend_out: f32,
+}
+
+impl CinematicOverride
+{
+    /// Creates a new `CinematicOverride` with priority `0` and no blending
+    pub const fn new(values: EnvironmentOverride) -> Self {
+        Self {
+            values,
+            priority: 0,
+            blend_in: 0.0,
+            blend_out: 0.0,
+        }
+    }
+
+    /// Creates a new `CinematicOverride` that overrides every value with the ones in `environment`
+    pub fn full(environment: Environment) -> Self {
+        Self::new(EnvironmentOverride::default()
+            .with_axial_tilt(environment.axial_tilt)
+            .with_latitude(environment.latitude)
+            .with_time_of_day(environment.time_of_day)
+            .with_date(environment.time_of_year))
+    }
+
+    /// Sets the priority
+    pub const fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets how many seconds the override takes to fade in
+    pub const fn with_blend_in(mut self, seconds: f32) -> Self {
+        self.blend_in = seconds;
+        self
+    }
+
+    /// Sets how many seconds the override takes to fade out after being released
+    pub const fn with_blend_out(mut self, seconds: f32) -> Self {
+        self.blend_out = seconds;
+        self
+    }
+}
+
+/// Identifies a [`CinematicOverride`] pushed onto [`CinematicOverrides`], used to release it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub struct CinematicOverrideId(u64);
+
+/// A pushed [`CinematicOverride`] and how far it has faded in
+#[derive(Clone, Copy, Debug)]
+#[derive(Reflect)]
+struct ActiveOverride {
+    id: CinematicOverrideId,
+    settings: CinematicOverride,
+    weight: f32,
+    released: bool,
+}
+
+/// Stack of temporary [`CinematicOverride`]s layered on top of the [`Environment`]
+/// 
+/// Cutscenes and scripted moments push overrides here instead of writing to the [`Environment`]
+/// resource directly, so they don't fight with each other or with a [`SolarClock`](crate::SolarClock)
+/// over the resource. The [`Sun`](crate::Sun) points wherever the resolved values say, while the
+/// [`Environment`] itself keeps its own values and picks up again once every override is gone.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{CinematicOverride, CinematicOverrides, EnvironmentOverride};
+/// fn start_cutscene(mut overrides: ResMut<CinematicOverrides>) {
+///     let sunset = overrides.push(
+///         CinematicOverride::new(EnvironmentOverride::default().with_hours_since_noon(6.5))
+///             .with_priority(10)
+///             .with_blend_in(2.0)
+///             .with_blend_out(3.0)
+///     );
+///     // ... and later, when the cutscene ends
+///     overrides.release(sunset);
+/// }
+/// ```
+/// 
+/// Added by the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin)
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct CinematicOverrides
+{
+    overrides: Vec<ActiveOverride>,
+    next_id: u64,
+}
+
+impl CinematicOverrides
+{
+    /// Pushes a new override, returning an id that can be used to release it later
+    pub fn push(&mut self, settings: CinematicOverride) -> CinematicOverrideId {
+        let id = CinematicOverrideId(self.next_id);
+        self.next_id += 1;
+        let weight = if settings.blend_in > 0.0 { 0.0 } else { 1.0 };
+        self.overrides.push(ActiveOverride { id, settings, weight, released: false });
+        // stable sort keeps overrides with the same priority in the order they were pushed
+        self.overrides.sort_by_key(|active| active.settings.priority);
+        id
+    }
+
+    /// Starts fading an override out, removing it once it's gone. Does nothing if the id has
+    /// already been released
+    pub fn release(&mut self, id: CinematicOverrideId) {
+        if let Some(active) = self.overrides.iter_mut().find(|active| active.id == id) {
+            active.released = true;
+        }
+    }
+
+    /// Like [`release`](Self::release), but fades the override out over `seconds` instead of its
+    /// own [`blend_out`](CinematicOverride::blend_out)
+    pub fn release_over(&mut self, id: CinematicOverrideId, seconds: f32) {
+        if let Some(active) = self.overrides.iter_mut().find(|active| active.id == id) {
+            active.settings.blend_out = seconds;
+            active.released = true;
+        }
+    }
+
+    /// Releases every override
+    pub fn release_all(&mut self) {
+        for active in &mut self.overrides {
+            active.released = true;
+        }
+    }
+
+    /// Whether there are no overrides, including ones still fading out
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Applies every override on top of `environment` in priority order, blended by how far each
+    /// has faded in
+    pub fn resolve(&self, environment: &Environment) -> Environment {
+        let mut resolved = *environment;
+        for active in &self.overrides {
+            let values = &active.settings.values;
+            let weight = active.weight;
+            if let Some(axial_tilt) = values.axial_tilt {
+                resolved.axial_tilt = resolved.axial_tilt.lerp(axial_tilt, weight);
+            }
+            if let Some(latitude) = values.latitude {
+                resolved.latitude = resolved.latitude.lerp(latitude, weight);
+            }
+            if let Some(time_of_day) = values.time_of_day {
+                resolved.time_of_day = lerp_angle(resolved.time_of_day, time_of_day, weight);
+            }
+            if let Some(time_of_year) = values.time_of_year {
+                resolved.time_of_year = lerp_angle(resolved.time_of_year, time_of_year, weight);
+            }
+        }
+        resolved
+    }
+
+    /// Fades overrides in and out, removing released ones once they've faded out completely.
+    /// Returns whether any weight changed or any override was removed
+    fn advance(&mut self, delta: f32) -> bool {
+        let mut changed = false;
+        for active in &mut self.overrides {
+            let weight = if active.released {
+                if active.settings.blend_out > 0.0 { active.weight - delta / active.settings.blend_out } else { 0.0 }
+            } else if active.settings.blend_in > 0.0 {
+                active.weight + delta / active.settings.blend_in
+            } else {
+                1.0
+            }.clamp(0.0, 1.0);
+            changed |= weight != active.weight;
+            active.weight = weight;
+        }
+        let count = self.overrides.len();
+        self.overrides.retain(|active| !(active.released && active.weight <= 0.0));
+        changed || self.overrides.len() != count
+    }
+}
+
+/// Interpolates between two angles in radians the short way around
+fn lerp_angle(from: f32, to: f32, weight: f32) -> f32 {
+    let difference = wrap_angle(to - from);
+    from + difference * weight
+}
+
+/// Fades [`CinematicOverrides`] in and out
+pub(crate) fn advance_cinematic_overrides(
+    mut overrides: ResMut<CinematicOverrides>,
+    time: Res<Time>,
+){
+    // settled overrides aren't marked as changed, so suns only update while something blends
+    if !overrides.is_empty() && overrides.bypass_change_detection().advance(time.delta_secs()) {
+        overrides.set_changed();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn priorities_and_blending() {
+        let environment = Environment::default().with_latitude(0.0);
+        let mut overrides = CinematicOverrides::default();
+        let high = overrides.push(
+            CinematicOverride::new(EnvironmentOverride::default().with_latitude(1.0))
+                .with_priority(5)
+                .with_blend_out(2.0)
+        );
+        overrides.push(CinematicOverride::new(EnvironmentOverride::default().with_latitude(0.5)));
+        // the higher priority wins even though it was pushed first
+        assert_eq!(overrides.resolve(&environment).latitude, 1.0);
+        overrides.release(high);
+        overrides.advance(1.0);
+        assert!(abs_diff_eq!(overrides.resolve(&environment).latitude, 0.75));
+        overrides.advance(1.0);
+        assert_eq!(overrides.resolve(&environment).latitude, 0.5);
+        overrides.release_all();
+        overrides.advance(0.1);
+        assert!(overrides.is_empty());
+    }
+
+    #[test]
+    fn settled_overrides_stay_unchanged() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::RealisticSunDirectionPlugin::default()));
+        app.world_mut().resource_mut::<CinematicOverrides>()
+            .push(CinematicOverride::new(EnvironmentOverride::default().with_latitude(1.0)));
+        app.update();
+        let changed = |app: &App| app.world().get_resource_change_ticks::<CinematicOverrides>().unwrap().changed;
+        let settled = changed(&app);
+        app.update();
+        app.update();
+        assert_eq!(changed(&app), settled);
+    }
+
+    #[test]
+    fn time_blends_the_short_way() {
+        assert!(abs_diff_eq!(lerp_angle(3.0, -3.0, 0.5).abs(), PI, epsilon = 1e-5));
+    }
+}
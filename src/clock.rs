@@ -0,0 +1,879 @@
+//! Contains the [`SolarClock`], [`TimeFlow`], [`SunTimeIntegrator`] and [`SunTimeScale`] resources
+//! and the systems that advance [`Environment`] with them
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::{Environment, PlanetPreset};
+use crate::conversion::*;
+use crate::real_time;
+
+
+/// Moves time forward in the [`Environment`] resource automatically
+/// 
+/// Without this resource the values in [`Environment`] only change when you change them yourself.
+/// Insert it to have the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin)
+/// advance the time of day and year every frame, either at a fixed speed or by following the
+/// operating system's clock.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{SolarClock, WallClockSync};
+/// # let mut app = App::new();
+/// // One day passes every 20 real minutes
+/// app.insert_resource(SolarClock::default().with_real_minutes_per_day(20.0));
+/// // Or always show the real sun in London, right now
+/// app.insert_resource(SolarClock::wall_clock(
+///     WallClockSync::default().with_longitude_deg(-0.13)
+/// ));
+/// ```
+/// 
+/// Time values are wrapped back into the `-PI` to `PI` range as they advance so they don't lose
+/// floating point precision over long play sessions. With the `f64` feature the time is also
+/// accumulated in `f64` between frames, so servers running for in-game years don't drift. This
+/// only covers time advanced by the clock, the [`Environment`] itself still stores `f32`.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SolarClock
+{
+    /// Speed [`time_of_day`](Environment::time_of_day) advances at, in radians per second
+    pub day_speed: f32,
+
+    /// Speed [`time_of_year`](Environment::time_of_year) advances at, in radians per second
+    pub year_speed: f32,
+
+    /// When set, the clock follows the operating system's clock instead of advancing at
+    /// [`day_speed`](SolarClock::day_speed) and [`year_speed`](SolarClock::year_speed)
+    pub wall_clock: Option<WallClockSync>,
+}
+
+impl SolarClock
+{
+    /// Creates a new `SolarClock` that advances at the same speed as real time on Earth, with one
+    /// day every 24 hours and one year every 365.24 days
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::SolarClock;
+    /// let clock = SolarClock::realtime();
+    /// ```
+    /// 
+    /// Unlike [`wall_clock`](SolarClock::wall_clock) this only matches the *speed* of real time,
+    /// starting from whatever time is already in the [`Environment`]
+    pub const fn realtime() -> Self {
+        Self::default_const()
+            .with_real_minutes_per_day(24.0 * 60.0)
+            .with_real_minutes_per_year(24.0 * 60.0 * 365.2422)
+    }
+
+    /// Creates a new `SolarClock` that follows the operating system's clock
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{SolarClock, WallClockSync};
+    /// // Follows the real time in New York, but an hour ahead
+    /// let clock = SolarClock::wall_clock(
+    ///     WallClockSync::default()
+    ///         .with_longitude_deg(-74.0)
+    ///         .with_offset_hours(1.0)
+    /// );
+    /// ```
+    pub const fn wall_clock(sync: WallClockSync) -> Self {
+        Self {
+            day_speed: 0.0,
+            year_speed: 0.0,
+            wall_clock: Some(sync),
+        }
+    }
+
+    /// Sets how many real minutes it takes for one full day to pass
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::SolarClock;
+    /// // One in-game day lasts 20 real minutes
+    /// let clock = SolarClock::default()
+    ///     .with_real_minutes_per_day(20.0);
+    /// ```
+    /// 
+    /// A value of `0.0` or less stops time of day from advancing
+    pub const fn with_real_minutes_per_day(mut self, minutes: f32) -> Self {
+        self.day_speed = speed_from_minutes(minutes);
+        self
+    }
+
+    /// Sets how many real minutes it takes for one full year to pass
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::SolarClock;
+    /// // One in-game day lasts 20 real minutes, with 
+    /// // 30 days in a year
+    /// let clock = SolarClock::default()
+    ///     .with_real_minutes_per_day(20.0)
+    ///     .with_real_minutes_per_year(20.0 * 30.0);
+    /// ```
+    /// 
+    /// A value of `0.0` or less stops time of year from advancing
+    pub const fn with_real_minutes_per_year(mut self, minutes: f32) -> Self {
+        self.year_speed = speed_from_minutes(minutes);
+        self
+    }
+
+    /// How many real minutes it takes for one full day to pass, or [`f32::INFINITY`] if time of
+    /// day isn't advancing
+    pub fn real_minutes_per_day(&self) -> f32 {
+        minutes_from_speed(self.day_speed)
+    }
+
+    /// How many real minutes it takes for one full year to pass, or [`f32::INFINITY`] if time of
+    /// year isn't advancing
+    pub fn real_minutes_per_year(&self) -> f32 {
+        minutes_from_speed(self.year_speed)
+    }
+
+    /// Changes how many real minutes it takes for one full day to pass at runtime
+    /// 
+    /// ```no_run
+    /// # use bevy::ecs::system::ResMut;
+    /// # use kj_bevy_realistic_sun::SolarClock;
+    /// // Speeds the day up while the player is asleep
+    /// fn sleep(mut clock: ResMut<SolarClock>) {
+    ///     clock.set_real_minutes_per_day(0.5);
+    /// }
+    /// ```
+    pub fn set_real_minutes_per_day(&mut self, minutes: f32) {
+        self.day_speed = speed_from_minutes(minutes);
+    }
+
+    /// Changes how many real minutes it takes for one full year to pass at runtime
+    pub fn set_real_minutes_per_year(&mut self, minutes: f32) {
+        self.year_speed = speed_from_minutes(minutes);
+    }
+
+    /// Same as [`Default::default`] but usable in `const` contexts
+    const fn default_const() -> Self {
+        Self {
+            day_speed: 0.0,
+            year_speed: 0.0,
+            wall_clock: None,
+        }
+    }
+}
+
+/// Converts a cycle length in real minutes to a speed in radians per second
+const fn speed_from_minutes(minutes: f32) -> f32 {
+    if minutes > 0.0 { TAU / (minutes * 60.0) } else { 0.0 }
+}
+
+/// Converts a speed in radians per second to a cycle length in real minutes
+fn minutes_from_speed(speed: f32) -> f32 {
+    if speed != 0.0 { TAU / (speed.abs() * 60.0) } else { f32::INFINITY }
+}
+
+/// Settings for a [`SolarClock`] that follows the operating system's clock
+/// 
+/// The time of day and year are set to the real values on Earth every frame, so the sun matches
+/// the real sun without any manual updates. An offset and speed multiplier can be applied on top
+/// of the real time.
+/// 
+/// **Note:** only time is synced, the [`Environment`]'s latitude and axial tilt are left alone
+#[derive(Clone, Copy, Debug)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct WallClockSync
+{
+    /// Longitude in radians, positive to the east of the prime meridian, used to work out the local
+    /// solar time of day
+    /// 
+    /// Only used when the [`Environment`]'s own [`longitude`](Environment::longitude) is `0.0`.
+    /// Otherwise the environment is given the clock time in its
+    /// [`utc_offset`](Environment::utc_offset) time zone and works out the solar time itself.
+    pub longitude: f32,
+
+    /// Offset in seconds added to the real time
+    pub offset: f64,
+
+    /// Multiplier for how fast time passes compared to the real clock. `1.0` is real time
+    /// 
+    /// The multiplier is applied from the moment the clock starts syncing, so changing it doesn't
+    /// make the time jump.
+    pub speed: f64,
+
+    /// Clock time (in seconds since the unix epoch) and synced time when syncing started or the
+    /// speed was last changed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    anchor: Option<(f64, f64, f64)>,
+}
+
+impl Default for WallClockSync {
+    fn default() -> Self {
+        Self {
+            longitude: 0.0,
+            offset: 0.0,
+            speed: 1.0,
+            anchor: None,
+        }
+    }
+}
+
+impl WallClockSync
+{
+    /// Sets the longitude in degrees, positive to the east of the prime meridian
+    pub const fn with_longitude_deg(mut self, longitude: f32) -> Self {
+        self.longitude = longitude * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the offset from the real time in hours
+    pub const fn with_offset_hours(mut self, offset: f64) -> Self {
+        self.offset = offset * 60.0 * 60.0;
+        self
+    }
+
+    /// Sets the speed multiplier compared to the real time, where `1.0` is real time
+    pub const fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Works out the synced time in seconds since the unix epoch, given the real time
+    fn synced_time(&mut self, now: f64) -> f64 {
+        let (anchor_now, anchor_synced, anchor_speed) = *self.anchor.get_or_insert((now, now, self.speed));
+        let synced = anchor_synced + (now - anchor_now) * anchor_speed;
+        if anchor_speed != self.speed {
+            self.anchor = Some((now, synced, self.speed));
+        }
+        synced + self.offset
+    }
+
+    /// Sets the time of day and year in `environment` to the synced time, in seconds since the
+    /// unix epoch
+    fn write_time(&self, environment: &mut Environment, synced: f64) {
+        environment.time_of_day = if environment.longitude != 0.0 {
+            // the environment places itself, so it only needs the clock time in its time zone
+            wrap_angle(real_time::time_of_day(synced, 0.0) + environment.utc_offset)
+        } else {
+            // solar time at the sync's longitude, shifted so the utc_offset cancels back out
+            wrap_angle(real_time::time_of_day(synced, self.longitude) + environment.utc_offset)
+        };
+        environment.time_of_year = real_time::time_of_year(synced);
+    }
+}
+
+/// Simple automatic time progression for the [`Environment`] resource
+/// 
+/// Insert it to have the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin)
+/// advance the time of day and year every frame, using a day length in seconds and a number of
+/// days per year, so you don't need to write your own system for it.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::TimeFlow;
+/// # let mut app = App::new();
+/// // One day every 20 seconds, 30 days in a year
+/// app.insert_resource(TimeFlow::default()
+///     .with_seconds_per_day(20.0)
+///     .with_days_per_year(30.0));
+/// ```
+/// 
+/// **Note:** this is an alternative to [`SolarClock`], not meant to be used alongside it. If both
+/// resources exist, the `SolarClock` wins and the `TimeFlow` is ignored, so time only advances
+/// once.
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct TimeFlow
+{
+    /// Real seconds it takes for one full day to pass. A value of `0.0` or less stops time of day
+    /// (and year) from advancing
+    pub seconds_per_day: f32,
+
+    /// Number of days in one full year. A value of `0.0` or less stops time of year from advancing
+    pub days_per_year: f32,
+
+    /// Multiplier for how fast time passes, where `1.0` is normal speed and negative values run
+    /// time backwards
+    pub speed: f32,
+
+    /// Stops time from advancing while `true`
+    pub paused: bool,
+}
+
+impl Default for TimeFlow {
+    /// 20 minute days and 365.24 days per year, at normal speed
+    fn default() -> Self {
+        Self {
+            seconds_per_day: 20.0 * 60.0,
+            days_per_year: 365.2422,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeFlow
+{
+    /// Sets how many real seconds it takes for one full day to pass
+    pub const fn with_seconds_per_day(mut self, seconds: f32) -> Self {
+        self.seconds_per_day = seconds;
+        self
+    }
+
+    /// Sets how many days there are in one full year
+    pub const fn with_days_per_year(mut self, days: f32) -> Self {
+        self.days_per_year = days;
+        self
+    }
+
+    /// Sets the number of days per year to match `preset`
+    /// 
+    /// The day length is left alone, since how long a day lasts in real seconds is up to the
+    /// game's pacing. Scale it by [`PlanetPreset::day_length_hours`] to keep real proportions.
+    pub const fn with_preset(self, preset: PlanetPreset) -> Self {
+        self.with_days_per_year(preset.days_per_year())
+    }
+
+    /// Sets the speed multiplier, where `1.0` is normal speed
+    pub const fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets whether time starts out paused
+    pub const fn with_paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+
+    /// Stops time from advancing
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Lets time advance again after [`pause`](TimeFlow::pause)
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Speed time of day advances at, in radians per second, including the speed multiplier
+    pub fn day_speed(&self) -> f32 {
+        if self.paused || self.seconds_per_day <= 0.0 {
+            return 0.0;
+        }
+        TAU / self.seconds_per_day * self.speed
+    }
+
+    /// Speed time of year advances at, in radians per second, including the speed multiplier
+    pub fn year_speed(&self) -> f32 {
+        if self.days_per_year <= 0.0 {
+            return 0.0;
+        }
+        self.day_speed() / self.days_per_year
+    }
+}
+
+/// Time of day and year driven by elapsed seconds you feed in yourself, counting every day and year
+/// that passes
+/// 
+/// Instead of advancing with Bevy's [`Time`] every frame like [`TimeFlow`], call
+/// [`advance`](SunTimeIntegrator::advance) with however many seconds passed for your world, for
+/// time dilation, time travel or other fantasy time. The phases of the day and year are kept
+/// wrapped in `f64`, with the wraps counted exactly in [`days_elapsed`](SunTimeIntegrator::days_elapsed)
+/// and [`years_elapsed`](SunTimeIntegrator::years_elapsed) instead of being lost in the radians.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunTimeIntegrator;
+/// # let mut app = App::new();
+/// # #[derive(Resource)]
+/// # struct Ship { lorentz_factor: f64 }
+/// app.insert_resource(SunTimeIntegrator::default().with_seconds_per_day(600.0));
+/// // Time on the planet runs faster while the ship is near the speed of light
+/// fn advance_planet_time(mut integrator: ResMut<SunTimeIntegrator>, ship: Res<Ship>, time: Res<Time>) {
+///     integrator.advance(time.delta_secs_f64() * ship.lorentz_factor);
+///     println!("{} days have passed on the planet", integrator.days_elapsed());
+/// }
+/// ```
+/// 
+/// The time in the [`Environment`] is overwritten whenever the integrator changes, so set the
+/// starting time with [`with_environment_time`](SunTimeIntegrator::with_environment_time) rather
+/// than in the environment. Days are counted at solar midnight and years when the time of year
+/// wraps at [`DATE_WINTER`](Environment::DATE_WINTER). [`SunTimeScale`] doesn't apply, since
+/// you choose the seconds yourself.
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunTimeIntegrator
+{
+    /// Seconds it takes for one full day to pass. A value of `0.0` or less stops time from
+    /// advancing
+    pub seconds_per_day: f64,
+
+    /// Number of days in one full year. A value of `0.0` or less stops time of year from advancing
+    pub days_per_year: f64,
+
+    /// How far through the day it is, from `0.0` at solar midnight to just under `1.0`
+    day_phase: f64,
+
+    /// How far through the year it is, from `0.0` to just under `1.0`
+    year_phase: f64,
+
+    /// Number of times the day phase has wrapped, negative when rewound past the start
+    days_elapsed: i64,
+
+    /// Number of times the year phase has wrapped, negative when rewound past the start
+    years_elapsed: i64,
+}
+
+impl Default for SunTimeIntegrator {
+    /// 20 minute days and 365.24 days per year, starting at solar noon on the summer solstice
+    fn default() -> Self {
+        Self {
+            seconds_per_day: 20.0 * 60.0,
+            days_per_year: 365.2422,
+            day_phase: 0.5,
+            year_phase: 0.5,
+            days_elapsed: 0,
+            years_elapsed: 0,
+        }
+    }
+}
+
+impl SunTimeIntegrator
+{
+    /// Sets how many seconds it takes for one full day to pass
+    pub const fn with_seconds_per_day(mut self, seconds: f64) -> Self {
+        self.seconds_per_day = seconds;
+        self
+    }
+
+    /// Sets how many days there are in one full year
+    pub const fn with_days_per_year(mut self, days: f64) -> Self {
+        self.days_per_year = days;
+        self
+    }
+
+    /// Sets the number of days per year to match `preset`, see [`TimeFlow::with_preset`]
+    pub const fn with_preset(self, preset: PlanetPreset) -> Self {
+        self.with_days_per_year(preset.days_per_year() as f64)
+    }
+
+    /// Starts from the time of day and year in `environment`
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, SunTimeIntegrator};
+    /// let environment = Environment::default()
+    ///     .with_hours_since_noon(-6.0);
+    /// let integrator = SunTimeIntegrator::default()
+    ///     .with_environment_time(&environment);
+    /// ```
+    pub fn with_environment_time(mut self, environment: &Environment) -> Self {
+        self.day_phase = phase_from_angle(environment.time_of_day);
+        self.year_phase = phase_from_angle(environment.time_of_year);
+        self
+    }
+
+    /// Moves time forward by `delta_seconds`, or backwards if negative, counting any days and
+    /// years that pass
+    pub fn advance(&mut self, delta_seconds: f64) {
+        if self.seconds_per_day <= 0.0 || delta_seconds == 0.0 {
+            return;
+        }
+        let days = delta_seconds / self.seconds_per_day;
+        self.day_phase = wrap_phase(self.day_phase + days, &mut self.days_elapsed);
+        if self.days_per_year > 0.0 {
+            self.year_phase = wrap_phase(self.year_phase + days / self.days_per_year, &mut self.years_elapsed);
+        }
+    }
+
+    /// Number of full days that have passed, counted at solar midnight
+    pub const fn days_elapsed(&self) -> i64 {
+        self.days_elapsed
+    }
+
+    /// Number of full years that have passed
+    pub const fn years_elapsed(&self) -> i64 {
+        self.years_elapsed
+    }
+
+    /// How far through the day it is, from `0.0` at solar midnight through `0.5` at solar noon to
+    /// just under `1.0`
+    pub const fn day_phase(&self) -> f64 {
+        self.day_phase
+    }
+
+    /// How far through the year it is, from `0.0` to just under `1.0`
+    pub const fn year_phase(&self) -> f64 {
+        self.year_phase
+    }
+
+    /// Total years that have passed including the current one, used to advance precession
+    fn total_years(&self) -> f64 {
+        self.years_elapsed as f64 + self.year_phase
+    }
+}
+
+/// Wraps `phase` into the `0.0` to `1.0` range, adding the number of wraps to `counter`
+fn wrap_phase(phase: f64, counter: &mut i64) -> f64 {
+    let wraps = phase.floor();
+    let mut wrapped = phase - wraps;
+    *counter += wraps as i64;
+    // tiny negative phases round up to a full cycle
+    if wrapped >= 1.0 {
+        wrapped = 0.0;
+        *counter += 1;
+    }
+    wrapped
+}
+
+/// Converts a time value in radians in the `-PI` to `PI` range into a phase from `0.0` to `1.0`
+/// 
+/// Measured against the `f32` value of `PI` the [`Environment`]'s times are defined with, so
+/// constants like [`TIME_MIDNIGHT`](Environment::TIME_MIDNIGHT) land on exact phases
+pub(crate) fn phase_from_angle(angle: f32) -> f64 {
+    let (pi, tau) = (std::f32::consts::PI as f64, std::f32::consts::TAU as f64);
+    (angle as f64 + pi).rem_euclid(tau) / tau
+}
+
+/// Converts a phase from `0.0` to `1.0` back into a time value in radians, the reverse of
+/// [`phase_from_angle`]
+pub(crate) fn angle_from_phase(phase: f64) -> f32 {
+    let (pi, tau) = (std::f32::consts::PI as f64, std::f32::consts::TAU as f64);
+    (phase * tau - pi) as f32
+}
+
+/// Scales how fast the [`SolarClock`] and [`TimeFlow`] move time forward, for pausing, time-lapses
+/// and rewinding
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{sun_time_paused, SunTimeScale, TimeFlow};
+/// # let mut app = App::new();
+/// # fn show_pause_menu() {}
+/// app.insert_resource(TimeFlow::default());
+/// // A 60x time-lapse
+/// app.insert_resource(SunTimeScale::default().with_scale(60.0));
+/// // Other systems can check if the sun is paused
+/// app.add_systems(Update, show_pause_menu.run_if(sun_time_paused));
+/// ```
+/// 
+/// Multiplies the speeds of both clocks, on top of [`TimeFlow::speed`]. Negative scales run time
+/// backwards smoothly. A [`SolarClock`] following the [`wall_clock`](SolarClock::wall_clock) has
+/// its own speed and isn't affected.
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunTimeScale
+{
+    /// Multiplier for how fast time passes, where `1.0` is normal speed and negative values run
+    /// time backwards
+    pub scale: f32,
+
+    /// Stops time from advancing while `true`, without losing the scale
+    pub paused: bool,
+}
+
+impl Default for SunTimeScale {
+    fn default() -> Self {
+        Self { scale: 1.0, paused: false }
+    }
+}
+
+impl SunTimeScale
+{
+    /// Sets the time scale multiplier
+    pub const fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets whether time starts out paused
+    pub const fn with_paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+
+    /// Stops time from advancing
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Lets time advance again after [`pause`](SunTimeScale::pause)
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Multiplier actually applied to the clocks, which is `0.0` while paused
+    pub fn effective_scale(&self) -> f32 {
+        if self.paused { 0.0 } else { self.scale }
+    }
+
+    /// Whether time is standing still, either paused or with a scale of `0.0`
+    pub fn is_paused(&self) -> bool {
+        self.effective_scale() == 0.0
+    }
+
+    /// Whether time is running backwards
+    pub fn is_rewinding(&self) -> bool {
+        self.effective_scale() < 0.0
+    }
+}
+
+/// Run condition that is `true` while a [`SunTimeScale`] resource stops time
+/// 
+/// `false` if there is no [`SunTimeScale`], since the clocks then run at normal speed
+pub fn sun_time_paused(time_scale: Option<Res<SunTimeScale>>) -> bool {
+    time_scale.is_some_and(|time_scale| time_scale.is_paused())
+}
+
+/// Run condition that is `true` while a [`SunTimeScale`] resource runs time backwards
+pub fn sun_time_rewinding(time_scale: Option<Res<SunTimeScale>>) -> bool {
+    time_scale.is_some_and(|time_scale| time_scale.is_rewinding())
+}
+
+/// Multiplier from an optional [`SunTimeScale`], `1.0` if there isn't one
+fn time_scale_factor(time_scale: Option<Res<SunTimeScale>>) -> f32 {
+    time_scale.map_or(1.0, |time_scale| time_scale.effective_scale())
+}
+
+/// Time of day and year a clock last wrote to the [`Environment`], kept in `f64` with the `f64`
+/// feature so steps smaller than the `f32` rounding error aren't lost
+#[derive(Default)]
+pub(crate) struct PreciseTime
+{
+    #[cfg(feature = "f64")]
+    last: Option<(f64, f64)>,
+}
+
+impl PreciseTime
+{
+    /// Moves the time in `environment` forward by the deltas in radians, wrapping the results
+    fn advance(&mut self, environment: &mut Environment, day_delta: f64, year_delta: f64) {
+        #[cfg(feature = "f64")]
+        {
+            let (time_of_day, time_of_year) = match self.last {
+                // carries on from the precise values unless something else changed the environment
+                Some((time_of_day, time_of_year)) if time_of_day as f32 == environment.time_of_day
+                    && time_of_year as f32 == environment.time_of_year => (time_of_day, time_of_year),
+                _ => (environment.time_of_day as f64, environment.time_of_year as f64),
+            };
+            let time_of_day = wrap_angle_f64(time_of_day + day_delta);
+            let time_of_year = wrap_angle_f64(time_of_year + year_delta);
+            self.last = Some((time_of_day, time_of_year));
+            environment.time_of_day = time_of_day as f32;
+            environment.time_of_year = time_of_year as f32;
+        }
+        #[cfg(not(feature = "f64"))]
+        {
+            environment.time_of_day = wrap_angle(environment.time_of_day + day_delta as f32);
+            environment.time_of_year = wrap_angle(environment.time_of_year + year_delta as f32);
+        }
+        environment.precession.advance(year_delta as f32);
+    }
+}
+
+/// Advances the time values in the [`Environment`] resource using the [`SolarClock`] resource
+pub(crate) fn advance_solar_clock(
+    mut clock: ResMut<SolarClock>,
+    mut environment: ResMut<Environment>,
+    mut precise: Local<PreciseTime>,
+    time: Res<Time>,
+    time_scale: Option<Res<SunTimeScale>>,
+){
+    if let Some(sync) = clock.wall_clock.as_mut() {
+        let synced = sync.synced_time(real_time::unix_now());
+        sync.write_time(&mut environment, synced);
+        return;
+    }
+    let scale = time_scale_factor(time_scale);
+    // a stopped clock leaves the environment untouched so it isn't marked as changed
+    if (clock.day_speed == 0.0 && clock.year_speed == 0.0) || scale == 0.0 {
+        return;
+    }
+    let delta = time.delta_secs_f64() * scale as f64;
+    precise.advance(&mut environment, clock.day_speed as f64 * delta, clock.year_speed as f64 * delta);
+}
+
+
+/// Advances the time values in the [`Environment`] resource using the [`TimeFlow`] resource
+pub(crate) fn advance_time_flow(
+    flow: Res<TimeFlow>,
+    mut environment: ResMut<Environment>,
+    mut precise: Local<PreciseTime>,
+    time: Res<Time>,
+    time_scale: Option<Res<SunTimeScale>>,
+){
+    let scale = time_scale_factor(time_scale);
+    if flow.paused || flow.speed == 0.0 || scale == 0.0 {
+        return;
+    }
+    let delta = time.delta_secs_f64() * scale as f64;
+    precise.advance(&mut environment, flow.day_speed() as f64 * delta, flow.year_speed() as f64 * delta);
+}
+
+/// Writes the time from the [`SunTimeIntegrator`] resource into the [`Environment`] resource
+pub(crate) fn apply_sun_time_integrator(
+    integrator: Res<SunTimeIntegrator>,
+    mut environment: ResMut<Environment>,
+    mut last_total_years: Local<Option<f64>>,
+){
+    if !integrator.is_changed() {
+        return;
+    }
+    let time_of_day = angle_from_phase(integrator.day_phase);
+    let time_of_year = angle_from_phase(integrator.year_phase);
+    let total_years = integrator.total_years();
+    let year_delta = last_total_years.replace(total_years).map_or(0.0, |last| total_years - last);
+    if environment.time_of_day == time_of_day && environment.time_of_year == time_of_year && year_delta == 0.0 {
+        return;
+    }
+    environment.time_of_day = time_of_day;
+    environment.time_of_year = time_of_year;
+    environment.precession.advance((year_delta * std::f64::consts::TAU) as f32);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn minutes_per_day() {
+        let clock = SolarClock::default().with_real_minutes_per_day(20.0);
+        assert!(abs_diff_eq!(clock.day_speed, TAU / 1200.0));
+        assert!(abs_diff_eq!(clock.real_minutes_per_day(), 20.0, epsilon = 1e-4));
+        assert_eq!(clock.real_minutes_per_year(), f32::INFINITY);
+        let realtime = SolarClock::realtime();
+        assert!(abs_diff_eq!(realtime.real_minutes_per_day(), 24.0 * 60.0, epsilon = 0.01));
+    }
+
+    #[test]
+    fn wall_clock_speed() {
+        let mut sync = WallClockSync::default().with_speed(2.0);
+        assert!(abs_diff_eq!(sync.synced_time(1000.0), 1000.0));
+        assert!(abs_diff_eq!(sync.synced_time(1010.0), 1020.0));
+        // changing speed doesn't make the time jump
+        sync.speed = 1.0;
+        assert!(abs_diff_eq!(sync.synced_time(1020.0), 1040.0));
+        assert!(abs_diff_eq!(sync.synced_time(1030.0), 1050.0));
+    }
+
+    #[test]
+    fn wall_clock_with_environment_longitude() {
+        // 2024-03-20 12:00 UTC is 7 AM in New York
+        let noon_utc = 1_710_936_000.0;
+        let mut environment = Environment::default()
+            .with_longitude_deg(-74.0)
+            .with_utc_offset_hours(-5.0);
+        // the sync's own longitude is ignored once the environment has one
+        WallClockSync::default().with_longitude_deg(120.0).write_time(&mut environment, noon_utc);
+        assert!(abs_diff_eq!(environment.time_of_day * RAD_TO_HOURS, -5.0, epsilon = 1e-4));
+        let solar_hours = environment.solar_time_of_day() * RAD_TO_HOURS;
+        assert!(abs_diff_eq!(solar_hours, -74.0 / 15.0, epsilon = 1e-4), "{}", solar_hours);
+        // without one, the environment shows the solar time at the sync's longitude
+        let mut environment = Environment::default().with_utc_offset_hours(-5.0);
+        WallClockSync::default().with_longitude_deg(-74.0).write_time(&mut environment, noon_utc);
+        let solar_hours = environment.solar_time_of_day() * RAD_TO_HOURS;
+        assert!(abs_diff_eq!(solar_hours, -74.0 / 15.0, epsilon = 1e-4), "{}", solar_hours);
+    }
+
+    #[cfg(feature = "real_time")]
+    #[test]
+    fn wall_clock_keeps_from_utc_local() {
+        use chrono::{TimeZone, Utc};
+        // 2024-03-20 11:00 UTC is noon in Zagreb, an hour ahead of UTC and 16 degrees east
+        let datetime = Utc.with_ymd_and_hms(2024, 3, 20, 11, 0, 0).unwrap();
+        let mut environment = Environment::from_utc(datetime, 45.8, 16.0).with_utc_offset_hours(1.0);
+        WallClockSync::default().write_time(&mut environment, real_time::unix_seconds(&datetime));
+        assert!(abs_diff_eq!(environment.time_of_day, 0.0, epsilon = 1e-5), "{}", environment.time_of_day);
+        // solar noon is at 12:00 local clock time, give or take the longitude within the time zone
+        // and the equation of time
+        let noon = environment.solar_to_clock_time(0.0) * RAD_TO_HOURS;
+        let expected = (15.0 - 16.0) / 15.0 - environment.equation_of_time() * RAD_TO_HOURS;
+        assert!(abs_diff_eq!(noon, expected, epsilon = 1e-3), "{} {}", noon, expected);
+    }
+
+    #[test]
+    fn time_flow_speeds() {
+        let mut flow = TimeFlow::default()
+            .with_seconds_per_day(20.0)
+            .with_days_per_year(30.0)
+            .with_speed(2.0);
+        assert!(abs_diff_eq!(flow.day_speed(), TAU / 10.0));
+        assert!(abs_diff_eq!(flow.year_speed(), TAU / 300.0));
+        flow.pause();
+        assert_eq!(flow.day_speed(), 0.0);
+        assert_eq!(flow.year_speed(), 0.0);
+        flow.resume();
+        flow.days_per_year = 0.0;
+        assert_eq!(flow.year_speed(), 0.0);
+    }
+
+    #[test]
+    fn solar_clock_wins_over_time_flow() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::RealisticSunDirectionPlugin::default()));
+        app.insert_resource(SolarClock { day_speed: 0.0, year_speed: 0.0, ..default() });
+        app.insert_resource(TimeFlow::default().with_seconds_per_day(1.0));
+        let start = app.world().resource::<Environment>().time_of_day;
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<Environment>().time_of_day, start);
+    }
+
+    #[test]
+    fn time_scale_rewinds_and_pauses() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::RealisticSunDirectionPlugin::default()));
+        app.insert_resource(TimeFlow::default().with_seconds_per_day(1.0));
+        app.insert_resource(SunTimeScale::default().with_scale(-1.0));
+        app.update();
+        app.update();
+        assert!(app.world().resource::<Environment>().time_of_day < 0.0);
+        assert!(app.world_mut().run_system_cached(sun_time_rewinding).unwrap());
+        app.world_mut().resource_mut::<SunTimeScale>().pause();
+        app.world_mut().clear_trackers();
+        app.update();
+        assert!(!app.world().is_resource_changed::<Environment>());
+        assert!(app.world_mut().run_system_cached(sun_time_paused).unwrap());
+    }
+
+    #[test]
+    fn integrator_counts_wraps() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::RealisticSunDirectionPlugin::default()));
+        let start = Environment::default().with_hours_since_noon(-6.0);
+        app.insert_resource(SunTimeIntegrator::default()
+            .with_seconds_per_day(10.0)
+            .with_days_per_year(4.0)
+            .with_environment_time(&start));
+        app.world_mut().resource_mut::<SunTimeIntegrator>().advance(10.0 * 9.5);
+        app.update();
+        let integrator = *app.world().resource::<SunTimeIntegrator>();
+        assert_eq!(integrator.days_elapsed(), 9);
+        assert_eq!(integrator.years_elapsed(), 2);
+        assert!(abs_diff_eq!(integrator.day_phase(), 0.75, epsilon = 1e-9));
+        // 6 hours after noon
+        assert!(abs_diff_eq!(app.world().resource::<Environment>().time_of_day, FRAC_PI_2, epsilon = 1e-5));
+        // rewinding past the start counts backwards
+        app.world_mut().resource_mut::<SunTimeIntegrator>().advance(-10.0 * 10.0);
+        let integrator = *app.world().resource::<SunTimeIntegrator>();
+        assert_eq!(integrator.days_elapsed(), -1);
+        assert!(abs_diff_eq!(integrator.day_phase(), 0.75, epsilon = 1e-9));
+    }
+
+    #[cfg(feature = "f64")]
+    #[test]
+    fn small_steps_dont_drift() {
+        let mut precise = PreciseTime::default();
+        let mut environment = Environment::default().with_time_of_day(1.0);
+        // smaller than an f32 step at 1.0, which would round up every frame
+        for _ in 0..100_000 {
+            precise.advance(&mut environment, 1e-7, 0.0);
+        }
+        assert!(abs_diff_eq!(environment.time_of_day, 1.01, epsilon = 1e-5), "{}", environment.time_of_day);
+    }
+}
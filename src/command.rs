@@ -0,0 +1,124 @@
+//! Contains the [`EnvironmentCommand`] event for skipping time from gameplay code
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+use crate::{Environment, SolarClock, TimeFlow};
+use crate::conversion::*;
+
+
+/// Moves time in the [`Environment`] forward from gameplay code, for beds, fast travel and
+/// skipping cutscenes
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::EnvironmentCommand;
+/// fn sleep(mut commands: Commands) {
+///     commands.trigger(EnvironmentCommand::AdvanceToNextSunrise);
+/// }
+/// ```
+/// 
+/// Handled by the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin), so the
+/// solar math doesn't need to be repeated. Time only ever moves forward (apart from a negative
+/// [`AdvanceHours`](EnvironmentCommand::AdvanceHours)), and the time of year moves along with the
+/// days that pass. The year length is taken from the [`TimeFlow`] or [`SolarClock`] resource when
+/// there is one, and is Earth's otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Event)]
+pub enum EnvironmentCommand
+{
+    /// Moves time forward by a number of hours
+    AdvanceHours(f32),
+    /// Moves time forward until the clock reads this many hours after midnight, from `0.0` to
+    /// `24.0`, the next day if that time has already passed today
+    SetTimeOfDay(f32),
+    /// Moves time forward to the next sunrise. Does nothing during the midnight sun or polar night
+    AdvanceToNextSunrise,
+    /// Moves time forward to the next sunset. Does nothing during the midnight sun or polar night
+    AdvanceToNextSunset,
+}
+
+/// Length of the year in days, from whichever resource is advancing time
+fn days_per_year(time_flow: Option<&TimeFlow>, solar_clock: Option<&SolarClock>) -> f32 {
+    const EARTH_DAYS_PER_YEAR: f32 = 365.2422;
+    if let Some(time_flow) = time_flow.filter(|time_flow| time_flow.days_per_year > 0.0) {
+        return time_flow.days_per_year;
+    }
+    match solar_clock {
+        Some(clock) if clock.day_speed != 0.0 && clock.year_speed != 0.0 => clock.day_speed / clock.year_speed,
+        _ => EARTH_DAYS_PER_YEAR,
+    }
+}
+
+/// Moves time forward by `delta` radians of time of day, with the year following along
+fn advance(environment: &Environment, delta: f32, days_per_year: f32) -> Environment {
+    let mut environment = *environment;
+    environment.time_of_day = wrap_angle(environment.time_of_day + delta);
+    environment.time_of_year = wrap_angle(environment.time_of_year + delta / days_per_year);
+    environment.precession.advance(delta / days_per_year);
+    environment
+}
+
+/// Time of day until the next time `target` comes around, in radians, always more than `0.0`
+fn until(time_of_day: f32, target: f32) -> f32 {
+    let delta = (target - time_of_day).rem_euclid(TAU);
+    if delta > 0.0 { delta } else { TAU }
+}
+
+/// Moves to the next time the clock reaches `target`, found by `target` on the day it lands on
+fn advance_to(environment: &Environment, days_per_year: f32, target: impl Fn(&Environment) -> Option<f32>) -> Option<Environment> {
+    let first = advance(environment, until(environment.time_of_day, target(environment)?), days_per_year);
+    // the date moved along with the time, so correct for the event shifting between the days
+    let shift = wrap_angle(target(&first)? - first.time_of_day);
+    Some(advance(&first, shift, days_per_year))
+}
+
+/// Applies [`EnvironmentCommand`]s to the [`Environment`] resource
+pub(crate) fn apply_environment_command(
+    command: On<EnvironmentCommand>,
+    mut environment: ResMut<Environment>,
+    time_flow: Option<Res<TimeFlow>>,
+    solar_clock: Option<Res<SolarClock>>,
+){
+    let days_per_year = days_per_year(time_flow.as_deref(), solar_clock.as_deref());
+    let updated = match *command {
+        EnvironmentCommand::AdvanceHours(hours) => Some(advance(&environment, hours * HOURS_TO_RAD, days_per_year)),
+        EnvironmentCommand::SetTimeOfDay(hours) => {
+            let target = hours * HOURS_TO_RAD - PI;
+            Some(advance(&environment, until(environment.time_of_day, target), days_per_year))
+        },
+        EnvironmentCommand::AdvanceToNextSunrise => advance_to(&environment, days_per_year, Environment::sunrise_time),
+        EnvironmentCommand::AdvanceToNextSunset => advance_to(&environment, days_per_year, Environment::sunset_time),
+    };
+    if let Some(updated) = updated {
+        *environment = updated;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::abs_diff_eq;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn skips_to_sunrise() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(50.0)
+            .with_hours_since_noon(10.0));
+        app.insert_resource(TimeFlow::default().with_days_per_year(10.0));
+        let start = *app.world().resource::<Environment>();
+        app.world_mut().trigger(EnvironmentCommand::AdvanceToNextSunrise);
+        let environment = *app.world().resource::<Environment>();
+        assert!(abs_diff_eq!(environment.solar_elevation(), 0.0, epsilon = 1e-3), "{}", environment.solar_elevation());
+        assert!(environment.with_hours_since_noon(-5.0).solar_elevation() < environment.with_hours_since_noon(-3.0).solar_elevation());
+        // a bit less than half a day passed, so the year moved along by a bit less than a twentieth
+        let year_passed = wrap_angle(environment.time_of_year - start.time_of_year);
+        assert!(year_passed > 0.0 && year_passed < TAU / 20.0, "{}", year_passed);
+        app.world_mut().trigger(EnvironmentCommand::SetTimeOfDay(7.5));
+        let environment = *app.world().resource::<Environment>();
+        assert!(abs_diff_eq!(environment.time_of_day, -4.5 * HOURS_TO_RAD, epsilon = 1e-5));
+    }
+}
@@ -0,0 +1,170 @@
+//! Contains the [`SunCompass`] resource, which publishes the sun's compass bearing for HUDs and
+//! navigation
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment};
+
+
+/// Compass bearing and elevation of the sun in degrees, for HUD compasses and telling direction by
+/// the sun
+///
+/// Updated by the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin) every time
+/// the environment changes, including any active [`CinematicOverrides`]. The bearing is measured
+/// on the planet's compass, so it doesn't depend on the
+/// [`SunReferenceFrame`](crate::SunReferenceFrame).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunCompass;
+/// # #[derive(Component)]
+/// # struct Player;
+/// fn place_sun_marker(compass: Res<SunCompass>, players: Query<&Transform, With<Player>>) {
+///     let Ok(player) = players.single() else { return };
+///     let (heading, _, _) = player.rotation.to_euler(EulerRot::YXZ);
+///     // yaw turns counterclockwise, compass bearings go clockwise
+///     let offset = compass.relative_bearing(-heading.to_degrees());
+///     let direction = compass.direction_to_cardinal().name();
+///     println!("Sun marker {offset:.0}° from the middle of the compass, in the {direction}");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunCompass
+{
+    /// Compass bearing of the sun in degrees, clockwise from north, from `0.0` to just under
+    /// `360.0`. See [`Environment::solar_azimuth_deg`]
+    pub bearing: f32,
+
+    /// Angle of the sun above the horizon in degrees, negative when it's below the horizon
+    pub elevation: f32,
+}
+
+impl SunCompass
+{
+    /// Works out the compass reading of the sun in `environment`
+    pub fn from_environment(environment: &Environment) -> Self {
+        Self {
+            bearing: environment.solar_azimuth_deg().rem_euclid(360.0),
+            elevation: environment.solar_elevation_deg(),
+        }
+    }
+
+    /// Nearest of the eight cardinal and intercardinal directions to the sun
+    pub fn direction_to_cardinal(&self) -> CardinalDirection {
+        CardinalDirection::from_bearing(self.bearing)
+    }
+
+    /// Bearing of the sun relative to a `heading` in degrees clockwise from north, from `-180.0`
+    /// to `180.0`, negative when the sun is to the left
+    pub fn relative_bearing(&self, heading: f32) -> f32 {
+        (self.bearing - heading + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+/// One of the eight cardinal and intercardinal compass directions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum CardinalDirection
+{
+    /// Bearing of `0` degrees, the `-Z` axis with the default
+    /// [`SunReferenceFrame`](crate::SunReferenceFrame)
+    North,
+    /// Bearing of `45` degrees
+    NorthEast,
+    /// Bearing of `90` degrees, the `+X` axis with the default frame
+    East,
+    /// Bearing of `135` degrees
+    SouthEast,
+    /// Bearing of `180` degrees
+    South,
+    /// Bearing of `225` degrees
+    SouthWest,
+    /// Bearing of `270` degrees
+    West,
+    /// Bearing of `315` degrees
+    NorthWest,
+}
+
+impl CardinalDirection
+{
+    /// Every direction, clockwise from north
+    pub const ALL: [Self; 8] = [
+        Self::North, Self::NorthEast, Self::East, Self::SouthEast,
+        Self::South, Self::SouthWest, Self::West, Self::NorthWest,
+    ];
+
+    /// Nearest direction to a compass `bearing` in degrees clockwise from north
+    pub fn from_bearing(bearing: f32) -> Self {
+        let index = (bearing.rem_euclid(360.0) / 45.0).round() as usize % 8;
+        Self::ALL[index]
+    }
+
+    /// Compass bearing of the direction in degrees clockwise from north
+    pub const fn bearing(self) -> f32 {
+        self as u8 as f32 * 45.0
+    }
+
+    /// Name of the direction, like `"South West"`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::North => "North",
+            Self::NorthEast => "North East",
+            Self::East => "East",
+            Self::SouthEast => "South East",
+            Self::South => "South",
+            Self::SouthWest => "South West",
+            Self::West => "West",
+            Self::NorthWest => "North West",
+        }
+    }
+
+    /// Short name of the direction, like `"SW"`
+    pub const fn abbreviation(self) -> &'static str {
+        match self {
+            Self::North => "N",
+            Self::NorthEast => "NE",
+            Self::East => "E",
+            Self::SouthEast => "SE",
+            Self::South => "S",
+            Self::SouthWest => "SW",
+            Self::West => "W",
+            Self::NorthWest => "NW",
+        }
+    }
+}
+
+/// Keeps the [`SunCompass`] resource in line with the [`Environment`]
+pub(crate) fn update_sun_compass(
+    mut compass: ResMut<SunCompass>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    if !(environment.is_changed() || cinematic_overrides.is_changed()) {
+        return;
+    }
+    compass.set_if_neq(SunCompass::from_environment(&cinematic_overrides.resolve(&environment)));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn afternoon_sun_in_the_west() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default()
+            .with_latitude_deg(45.0)
+            .with_hours_since_noon(5.0));
+        app.update();
+        let compass = *app.world().resource::<SunCompass>();
+        assert!(compass.bearing > 180.0 && compass.bearing < 300.0, "{}", compass.bearing);
+        assert!(matches!(compass.direction_to_cardinal(), CardinalDirection::SouthWest | CardinalDirection::West));
+        assert_eq!(CardinalDirection::from_bearing(350.0), CardinalDirection::North);
+        assert_eq!(SunCompass { bearing: 10.0, elevation: 0.0 }.relative_bearing(350.0), 20.0);
+    }
+}
@@ -0,0 +1,100 @@
+//! Run conditions for running systems at certain times of day
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment};
+use crate::conversion::*;
+
+
+/// Run condition that is `true` while the sun is above the [`Environment`]'s horizon offset
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{sun_is_daytime, sun_is_night};
+/// # let mut app = App::new();
+/// # fn open_market() {}
+/// # fn light_streetlights() {}
+/// app.add_systems(Update, (
+///     open_market.run_if(sun_is_daytime),
+///     light_streetlights.run_if(sun_is_night),
+/// ));
+/// ```
+/// 
+/// Includes any active [`CinematicOverrides`], the same as [`Environment::is_daytime`] on the
+/// environment the suns are showing. `false` if there is no [`Environment`]
+pub fn sun_is_daytime(
+    environment: Option<Res<Environment>>, cinematic_overrides: Option<Res<CinematicOverrides>>,
+) -> bool {
+    resolved(environment, cinematic_overrides).is_some_and(|environment| environment.is_daytime())
+}
+
+/// Run condition that is `true` while the sun is below the [`Environment`]'s horizon offset, the
+/// opposite of [`sun_is_daytime`]
+/// 
+/// `false` if there is no [`Environment`]
+pub fn sun_is_night(
+    environment: Option<Res<Environment>>, cinematic_overrides: Option<Res<CinematicOverrides>>,
+) -> bool {
+    resolved(environment, cinematic_overrides).is_some_and(|environment| !environment.is_daytime())
+}
+
+/// Creates a run condition that is `true` while the sun is higher than `elevation` degrees above
+/// the flat horizon
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::sun_above_deg;
+/// # let mut app = App::new();
+/// # fn spawn_monsters() {}
+/// // Monsters come out once it's properly dark, after civil twilight
+/// app.add_systems(Update, spawn_monsters.run_if(not(sun_above_deg(-6.0))));
+/// ```
+/// 
+/// Includes any active [`CinematicOverrides`]. `false` if there is no [`Environment`]
+pub fn sun_above_deg(
+    elevation: f32,
+) -> impl FnMut(Option<Res<Environment>>, Option<Res<CinematicOverrides>>) -> bool + Clone {
+    move |environment, cinematic_overrides| {
+        resolved(environment, cinematic_overrides)
+            .is_some_and(|environment| environment.is_sun_above(elevation * DEG_TO_RAD))
+    }
+}
+
+/// The [`Environment`] with any [`CinematicOverrides`] applied, if there is one
+fn resolved(
+    environment: Option<Res<Environment>>, cinematic_overrides: Option<Res<CinematicOverrides>>,
+) -> Option<Environment> {
+    let environment = environment?;
+    Some(match cinematic_overrides {
+        Some(cinematic_overrides) => cinematic_overrides.resolve(&environment),
+        None => *environment,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[derive(Default, Resource)]
+    struct Ran(Vec<&'static str>);
+
+    #[test]
+    fn conditions_follow_sun() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.init_resource::<Ran>();
+        app.add_systems(Update, (
+            (|mut ran: ResMut<Ran>| ran.0.push("day")).run_if(sun_is_daytime),
+            (|mut ran: ResMut<Ran>| ran.0.push("night")).run_if(sun_is_night),
+            (|mut ran: ResMut<Ran>| ran.0.push("twilight")).run_if(sun_above_deg(-6.0).and(sun_is_night)),
+        ).chain());
+        app.insert_resource(Environment::default());
+        app.update();
+        // 20 minutes after sunset on the equator the sun is about 5 degrees down
+        app.insert_resource(Environment::default().with_hours_since_noon(6.33));
+        app.update();
+        app.insert_resource(Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT));
+        app.update();
+        assert_eq!(app.world().resource::<Ran>().0, ["day", "night", "twilight", "night"]);
+    }
+}
@@ -1,11 +1,166 @@
-//! Some constants used for unit conversion
-use std::f32::consts::TAU;
+//! Constants and functions for converting the radians stored in
+//! [`Environment`](crate::Environment) to and from more common units
+//! 
+//! ```no_run
+//! # use kj_bevy_realistic_sun::Environment;
+//! use kj_bevy_realistic_sun::conversion::*;
+//! # let environment = Environment::default();
+//! let latitude_deg = environment.latitude * RAD_TO_DEG;
+//! // "14:30"
+//! let clock = format_time_of_day(2.5 * HOURS_TO_RAD);
+//! ```
+use std::f32::consts::{PI, TAU};
+use bevy::color::Color;
+use bevy::math::Vec3;
 
 
+/// Multiply by this to convert degrees to radians
 pub const DEG_TO_RAD: f32 = TAU / 360.0;
+/// Multiply by this to convert hours of a day to radians of time of day
 pub const HOURS_TO_RAD: f32 = TAU / 24.0;
+/// Multiply by this to convert minutes of a day to radians of time of day
+pub const MINUTES_TO_RAD: f32 = HOURS_TO_RAD / 60.0;
+/// Multiply by this to convert radians to degrees
 pub const RAD_TO_DEG: f32 = 360.0 / TAU;
+/// Multiply by this to convert radians of time of day to hours of a day
 pub const RAD_TO_HOURS: f32 = 24.0 / TAU;
+/// Multiply by this to convert radians of time of day to minutes of a day
+pub const RAD_TO_MINUTES: f32 = RAD_TO_HOURS * 60.0;
+
+
+/// Converts a number of days into radians of time of year, for a year `days_per_year` days long
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::Environment;
+/// # use kj_bevy_realistic_sun::conversion::days_to_rad;
+/// // 10 days after the summer solstice on a planet with a 90 day year
+/// let environment = Environment::default()
+///     .with_date(Environment::DATE_SUMMER + days_to_rad(10.0, 90.0));
+/// ```
+pub fn days_to_rad(days: f32, days_per_year: f32) -> f32 {
+    days / days_per_year * TAU
+}
+
+/// Converts radians of time of year into a number of days, for a year `days_per_year` days long
+pub fn rad_to_days(time_of_year: f32, days_per_year: f32) -> f32 {
+    time_of_year / TAU * days_per_year
+}
+
+/// Formats a [`time_of_day`](crate::Environment::time_of_day) in radians as a 24 hour clock time,
+/// like `"06:30"`
+/// 
+/// The time is rounded to the nearest minute and wrapped into a single day, so `0.0` (noon) is
+/// `"12:00"` and `PI` (midnight) is `"00:00"`
+pub fn format_time_of_day(time_of_day: f32) -> String {
+    let minutes = ((time_of_day + PI).rem_euclid(TAU) * RAD_TO_MINUTES).round() as u32 % (24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Converts a color temperature in Kelvin into the color of a black body glowing at that
+/// temperature
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::conversion::kelvin_to_color;
+/// // Warm candle light
+/// let candle = kelvin_to_color(1900.0);
+/// ```
+/// 
+/// Brightest channel is always `1.0`, and temperatures are clamped to the `1000.0` to `40000.0`
+/// range. Uses Tanner Helland's curve fit, which is close enough for lighting but not for
+/// colorimetry. Pairs with
+/// [`Environment::sun_color_temperature_kelvin`](crate::Environment::sun_color_temperature_kelvin)
+pub fn kelvin_to_color(kelvin: f32) -> Color {
+    let temperature = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let red = if temperature <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (temperature - 60.0).powf(-0.13320476)
+    };
+    let green = if temperature <= 66.0 {
+        99.4708 * temperature.ln() - 161.11957
+    } else {
+        288.12217 * (temperature - 60.0).powf(-0.07551485)
+    };
+    let blue = if temperature >= 66.0 {
+        255.0
+    } else if temperature <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (temperature - 10.0).ln() - 305.0448
+    };
+    Color::srgb(
+        (red / 255.0).clamp(0.0, 1.0),
+        (green / 255.0).clamp(0.0, 1.0),
+        (blue / 255.0).clamp(0.0, 1.0),
+    )
+}
+
+/// Converts equatorial coordinates into horizontal coordinates at `latitude`, all in radians
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::conversion::*;
+/// // The sun on the equinox, two hours after noon, seen from 52 degrees north
+/// let (altitude, azimuth) = equatorial_to_horizontal(0.0, 2.0 * HOURS_TO_RAD, 52.0 * DEG_TO_RAD);
+/// ```
+/// 
+/// Takes `declination` north of the celestial equator and `hour_angle` westwards from the local
+/// meridian, and returns `(altitude, azimuth)` with the azimuth clockwise from north in the `0.0`
+/// to `TAU` range, matching [`Environment::sun_horizontal`](crate::Environment::sun_horizontal)
+pub fn equatorial_to_horizontal(declination: f32, hour_angle: f32, latitude: f32) -> (f32, f32) {
+    let altitude = (latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+    let azimuth = (-hour_angle.sin() * declination.cos())
+        .atan2(declination.sin() * latitude.cos() - declination.cos() * latitude.sin() * hour_angle.cos())
+        .rem_euclid(TAU);
+    (altitude, azimuth)
+}
+
+/// Converts horizontal coordinates at `latitude` into equatorial coordinates, all in radians
+/// 
+/// The opposite of [`equatorial_to_horizontal`], returning `(declination, hour_angle)` with the
+/// hour angle in the `-PI` to `PI` range
+pub fn horizontal_to_equatorial(altitude: f32, azimuth: f32, latitude: f32) -> (f32, f32) {
+    let declination = (latitude.sin() * altitude.sin() + latitude.cos() * altitude.cos() * azimuth.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+    let hour_angle = (-azimuth.sin() * altitude.cos())
+        .atan2(altitude.sin() * latitude.cos() - altitude.cos() * latitude.sin() * azimuth.cos());
+    (declination, hour_angle)
+}
+
+
+/// Converts a world space direction into a compass azimuth and elevation above the horizon in
+/// radians. Azimuth is measured clockwise from north (`-Z`) towards east (`+X`)
+pub(crate) fn direction_to_azimuth_elevation(direction: Vec3) -> (f32, f32) {
+    let direction = direction.normalize_or_zero();
+    let azimuth = direction.x.atan2(-direction.z).rem_euclid(TAU);
+    let elevation = direction.y.clamp(-1.0, 1.0).asin();
+    (azimuth, elevation)
+}
+
+/// Converts a compass azimuth and elevation above the horizon in radians into a world space
+/// direction. Azimuth is measured clockwise from north (`-Z`) towards east (`+X`)
+pub(crate) fn azimuth_elevation_to_direction(azimuth: f32, elevation: f32) -> Vec3 {
+    Vec3::new(
+        azimuth.sin() * elevation.cos(),
+        elevation.sin(),
+        -azimuth.cos() * elevation.cos(),
+    )
+}
+
+/// Wraps an angle in radians into the `-PI` to `PI` range the [`Environment`](crate::Environment)
+/// keeps its times in
+pub(crate) fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// [`wrap_angle`] for `f64` angles, used where time is tracked more precisely than the
+/// [`Environment`](crate::Environment) stores it
+pub(crate) fn wrap_angle_f64(angle: f64) -> f64 {
+    use std::f64::consts::{PI, TAU};
+    (angle + PI).rem_euclid(TAU) - PI
+}
 
 
 #[cfg(test)]
@@ -30,4 +185,23 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn time_formatting() {
+        assert_eq!(format_time_of_day(0.0), "12:00");
+        assert_eq!(format_time_of_day(-PI), "00:00");
+        assert_eq!(format_time_of_day(-5.5 * HOURS_TO_RAD), "06:30");
+        assert_eq!(format_time_of_day(TAU + 11.999 * HOURS_TO_RAD), "00:00");
+        assert!(ulps_eq!(rad_to_days(days_to_rad(30.0, 360.0), 360.0), 30.0));
+    }
+
+    #[test]
+    fn kelvin_colors() {
+        let white = kelvin_to_color(6600.0).to_srgba();
+        assert!(white.red > 0.99 && white.green > 0.97 && white.blue > 0.99, "{white:?}");
+        let warm = kelvin_to_color(2000.0).to_srgba();
+        assert!(warm.red > warm.green && warm.green > warm.blue, "{warm:?}");
+        let cool = kelvin_to_color(12000.0).to_srgba();
+        assert!(cool.blue > cool.red, "{cool:?}");
+    }
 }
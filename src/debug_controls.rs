@@ -0,0 +1,174 @@
+//! Contains the [`DebugTimeControlPlugin`], a drop in set of keyboard controls for the environment
+use bevy::prelude::*;
+use crate::Environment;
+
+
+/// Adds keyboard controls for changing the [`Environment`] at runtime, for debugging
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{DebugTimeControlPlugin, RealisticSunDirectionPlugin};
+/// # let mut app = App::new();
+/// app.add_plugins((RealisticSunDirectionPlugin::default(), DebugTimeControlPlugin));
+/// ```
+/// 
+/// Key         | Control
+/// ------------|------------------------
+/// Q           | Advance time of day
+/// A           | Reverse time of day
+/// W           | Advance time of year
+/// S           | Reverse time of year
+/// E           | Increase latitude
+/// D           | Decrease latitude
+/// R           | Increase axial tilt
+/// F           | Decrease axial tilt
+/// Shift       | Increase speed of environment changes
+/// Ctrl        | Decrease speed of environment changes
+/// 
+/// Keys and speeds can be changed by inserting your own [`DebugTimeControls`] resource. Only
+/// available with the `debug_controls` feature, so the cheat can be left out of release builds by
+/// only enabling the feature for development.
+pub struct DebugTimeControlPlugin;
+impl Plugin for DebugTimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DebugTimeControls>();
+        app.init_resource::<DebugTimeControls>();
+        app.add_systems(Update, process_debug_time_input.before(crate::SunUpdateSet::UpdateLights));
+    }
+}
+
+/// Keybindings and speeds for the [`DebugTimeControlPlugin`]
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::DebugTimeControls;
+/// # let mut app = App::new();
+/// // Use the number keys for time instead
+/// app.insert_resource(DebugTimeControls {
+///     advance_time_of_day: KeyCode::Digit1,
+///     reverse_time_of_day: KeyCode::Digit2,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Clone, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct DebugTimeControls
+{
+    /// Key that moves the time of day forward
+    pub advance_time_of_day: KeyCode,
+    /// Key that moves the time of day backward
+    pub reverse_time_of_day: KeyCode,
+    /// Key that moves the time of year forward
+    pub advance_time_of_year: KeyCode,
+    /// Key that moves the time of year backward
+    pub reverse_time_of_year: KeyCode,
+    /// Key that moves the latitude north
+    pub increase_latitude: KeyCode,
+    /// Key that moves the latitude south
+    pub decrease_latitude: KeyCode,
+    /// Key that increases the axial tilt
+    pub increase_axial_tilt: KeyCode,
+    /// Key that decreases the axial tilt
+    pub decrease_axial_tilt: KeyCode,
+    /// Keys that switch to [`fast_speed`](DebugTimeControls::fast_speed) while held
+    pub fast_modifiers: Vec<KeyCode>,
+    /// Keys that switch to [`slow_speed`](DebugTimeControls::slow_speed) while held. Takes
+    /// priority over the fast modifiers
+    pub slow_modifiers: Vec<KeyCode>,
+    /// Speed values change at in radians per second
+    pub normal_speed: f32,
+    /// Speed values change at in radians per second while holding a fast modifier
+    pub fast_speed: f32,
+    /// Speed values change at in radians per second while holding a slow modifier
+    pub slow_speed: f32,
+}
+
+impl Default for DebugTimeControls {
+    fn default() -> Self {
+        Self {
+            advance_time_of_day: KeyCode::KeyQ,
+            reverse_time_of_day: KeyCode::KeyA,
+            advance_time_of_year: KeyCode::KeyW,
+            reverse_time_of_year: KeyCode::KeyS,
+            increase_latitude: KeyCode::KeyE,
+            decrease_latitude: KeyCode::KeyD,
+            increase_axial_tilt: KeyCode::KeyR,
+            decrease_axial_tilt: KeyCode::KeyF,
+            fast_modifiers: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            slow_modifiers: vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+            normal_speed: 0.4,
+            fast_speed: 2.0,
+            slow_speed: 0.05,
+        }
+    }
+}
+
+/// Takes keyboard input and updates the [`Environment`] accordingly
+fn process_debug_time_input(
+    mut environment: ResMut<Environment>,
+    controls: Res<DebugTimeControls>,
+    input: Option<Res<ButtonInput<KeyCode>>>,
+    time: Res<Time>,
+){
+    let Some(input) = input else {
+        return;
+    };
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        input.pressed(positive) as i8 as f32 - input.pressed(negative) as i8 as f32
+    };
+    let time_of_day_input = axis(controls.advance_time_of_day, controls.reverse_time_of_day);
+    let time_of_year_input = axis(controls.advance_time_of_year, controls.reverse_time_of_year);
+    let latitude_input = axis(controls.increase_latitude, controls.decrease_latitude);
+    let axial_tilt_input = axis(controls.increase_axial_tilt, controls.decrease_axial_tilt);
+    if time_of_day_input == 0.0 && time_of_year_input == 0.0
+        && latitude_input == 0.0 && axial_tilt_input == 0.0 {
+        return;
+    }
+    let speed = if input.any_pressed(controls.slow_modifiers.iter().copied()) { controls.slow_speed }
+        else if input.any_pressed(controls.fast_modifiers.iter().copied()) { controls.fast_speed }
+        else { controls.normal_speed };
+    let step = speed * time.delta_secs();
+    // apply inputs, looping the times and clamping the angles
+    environment.time_of_day += time_of_day_input * step;
+    environment.time_of_year += time_of_year_input * step;
+    environment.latitude += latitude_input * step;
+    environment.axial_tilt += axial_tilt_input * step;
+    environment.normalize();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use bevy::time::TimeUpdateStrategy;
+    use approx::abs_diff_eq;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn keys_change_environment() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default(), DebugTimeControlPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(0.1)));
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyQ);
+        app.update();
+        let environment = *app.world().resource::<Environment>();
+        assert!(abs_diff_eq!(environment.time_of_day, 0.04, epsilon = 1e-5), "{}", environment.time_of_day);
+        // holding shift switches to the fast speed
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ShiftLeft);
+        app.update();
+        let environment = *app.world().resource::<Environment>();
+        assert!(abs_diff_eq!(environment.time_of_day, 0.24, epsilon = 1e-5), "{}", environment.time_of_day);
+        // rebound keys are used instead of the defaults
+        app.insert_resource(DebugTimeControls { reverse_time_of_year: KeyCode::Digit1, ..default() });
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release_all();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Digit1);
+        app.update();
+        let environment = *app.world().resource::<Environment>();
+        assert!(abs_diff_eq!(environment.time_of_day, 0.24, epsilon = 1e-5), "{}", environment.time_of_day);
+        assert!(environment.time_of_year < 0.0, "{}", environment.time_of_year);
+    }
+}
@@ -0,0 +1,210 @@
+//! Contains the [`SunDebugGizmosPlugin`], a gizmo overlay showing where the sun is and where it's
+//! going
+use std::f32::consts::{FRAC_PI_2, PI};
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment};
+use crate::conversion::*;
+
+
+/// Draws the horizon, compass directions, sun and today's sun path with gizmos, for debugging
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{RealisticSunDirectionPlugin, SunDebugGizmosPlugin};
+/// # let mut app = App::new();
+/// app.add_plugins((RealisticSunDirectionPlugin::default(), SunDebugGizmosPlugin));
+/// ```
+/// 
+/// Everything is drawn on a sphere around the active camera so it stays in view wherever the
+/// camera goes:
+/// 
+/// * a white ring along the horizon
+/// * lines marking north (red), east, south and west
+/// * a yellow sphere where the sun is right now
+/// * an orange line along the path the sun takes today
+/// * optionally, the [sky grid](draw_sky_grid)
+/// 
+/// The size and number of path samples can be changed, and the overlay toggled, with the
+/// [`SunDebugGizmos`] resource. Only available with the `debug_gizmos` feature, and needs Bevy's
+/// gizmo plugin (part of `DefaultPlugins`) to show anything.
+pub struct SunDebugGizmosPlugin;
+impl Plugin for SunDebugGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SunDebugGizmos>();
+        app.init_resource::<SunDebugGizmos>();
+        app.add_systems(Update, draw_sun_debug_gizmos.after(crate::SunUpdateSet::UpdateLights));
+    }
+}
+
+/// Settings for the [`SunDebugGizmosPlugin`]
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunDebugGizmos;
+/// fn toggle_sun_gizmos(mut gizmos: ResMut<SunDebugGizmos>, input: Res<ButtonInput<KeyCode>>) {
+///     if input.just_pressed(KeyCode::F3) {
+///         gizmos.enabled = !gizmos.enabled;
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct SunDebugGizmos
+{
+    /// Whether the overlay is drawn
+    pub enabled: bool,
+    /// Distance from the camera everything is drawn at
+    pub radius: f32,
+    /// Number of points along today's sun path
+    pub path_samples: usize,
+    /// Whether to draw the [sky grid](draw_sky_grid) as well. Defaults to `false`
+    pub sky_grid: bool,
+}
+
+impl Default for SunDebugGizmos {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            radius: 10.0,
+            path_samples: 96,
+            sky_grid: false,
+        }
+    }
+}
+
+/// Draws the overlay around the first active camera, or the origin if there isn't one
+fn draw_sun_debug_gizmos(
+    mut gizmos: Gizmos,
+    settings: Res<SunDebugGizmos>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    if !settings.enabled {
+        return;
+    }
+    let center = cameras.iter()
+        .find(|(camera, _)| camera.is_active)
+        .map_or(Vec3::ZERO, |(_, transform)| transform.translation());
+    let radius = settings.radius;
+    let environment = cinematic_overrides.resolve(&environment);
+    gizmos.circle(Isometry3d::new(center, Quat::from_rotation_x(FRAC_PI_2)), radius, css::WHITE);
+    let cardinals = [
+        (Vec3::NEG_Z, css::RED),
+        (Vec3::X, css::LIGHT_GRAY),
+        (Vec3::Z, css::LIGHT_GRAY),
+        (Vec3::NEG_X, css::LIGHT_GRAY),
+    ];
+    for (direction, color) in cardinals {
+        gizmos.line(center + direction * radius * 0.9, center + direction * radius * 1.1, color);
+    }
+    let path = environment.sample_day_path(settings.path_samples.max(2))
+        .into_iter()
+        .map(|direction| center + direction * radius);
+    gizmos.linestrip(path, css::ORANGE);
+    gizmos.sphere(center + environment.sun_direction() * radius, radius * 0.03, css::YELLOW);
+    if settings.sky_grid {
+        draw_sky_grid(&mut gizmos, &environment, center, radius);
+    }
+}
+
+/// Draws a grid of the sky for `environment` on a sphere of `radius` around `center`
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{draw_sky_grid, Environment};
+/// fn draw_my_debug_view(mut gizmos: Gizmos, environment: Res<Environment>) {
+///     draw_sky_grid(&mut gizmos, &environment, Vec3::ZERO, 20.0);
+/// }
+/// ```
+/// 
+/// Draws:
+/// 
+/// * gray rings of altitude every 15 degrees above the horizon, and lines of azimuth every 30
+///   degrees from the horizon up to the zenith
+/// * a cyan line along the celestial equator, which is the sun's path at the equinoxes
+/// * a gold line along the sun's path on the summer solstice and a blue one on the winter solstice
+/// 
+/// The paths follow the same sun model as the [`Sun`](crate::Sun) lights, so they line up with
+/// [`Environment::sample_day_path`]. Only available with the `debug_gizmos` feature.
+pub fn draw_sky_grid(gizmos: &mut Gizmos, environment: &Environment, center: Vec3, radius: f32) {
+    const SAMPLES: usize = 96;
+    const ALTITUDE_STEP: f32 = 15.0 * DEG_TO_RAD;
+    const AZIMUTH_STEP: f32 = 30.0 * DEG_TO_RAD;
+    let grid_color = css::DIM_GRAY;
+    for ring in 1..6 {
+        let altitude = ring as f32 * ALTITUDE_STEP;
+        let ring_center = center + Vec3::Y * altitude.sin() * radius;
+        gizmos.circle(Isometry3d::new(ring_center, Quat::from_rotation_x(FRAC_PI_2)), altitude.cos() * radius, grid_color);
+    }
+    for line in 0..12 {
+        let azimuth = line as f32 * AZIMUTH_STEP;
+        let points = (0..=SAMPLES / 4)
+            .map(|sample| sample as f32 / (SAMPLES / 4) as f32 * FRAC_PI_2)
+            .map(|altitude| center + azimuth_elevation_to_direction(azimuth, altitude) * radius);
+        gizmos.linestrip(points, grid_color);
+    }
+    let paths = [
+        (PI / 2.0, css::AQUA),
+        (0.0, css::GOLD),
+        (PI, css::LIGHT_BLUE),
+    ];
+    for (time_of_year, color) in paths {
+        let path = environment.with_date(time_of_year).sample_day_path(SAMPLES)
+            .into_iter()
+            .map(|direction| center + direction * radius);
+        gizmos.linestrip(path, color);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::gizmos::GizmoPlugin;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn draws_without_camera() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::asset::AssetPlugin::default(), GizmoPlugin));
+        app.add_plugins((RealisticSunDirectionPlugin::default(), SunDebugGizmosPlugin));
+        app.update();
+        let settings = *app.world().resource::<SunDebugGizmos>();
+        assert!(settings.enabled && !settings.sky_grid);
+        assert!(app.world().resource::<AppTypeRegistry>().read().contains(std::any::TypeId::of::<SunDebugGizmos>()));
+        app.world_mut().resource_mut::<SunDebugGizmos>().sky_grid = true;
+        app.update();
+        assert!(app.world().resource::<SunDebugGizmos>().sky_grid);
+        app.world_mut().resource_mut::<SunDebugGizmos>().enabled = false;
+        app.update();
+        assert!(!app.world().resource::<SunDebugGizmos>().enabled);
+    }
+
+    #[test]
+    fn sky_grid_lies_on_sphere() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::asset::AssetPlugin::default(), GizmoPlugin));
+        let center = Vec3::new(1.0, 2.0, 3.0);
+        let environment = Environment::default()
+            .with_latitude_deg(45.0)
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH);
+        let (positions, colors) = app.world_mut().run_system_once(move |mut gizmos: Gizmos| {
+            draw_sky_grid(&mut gizmos, &environment, center, 5.0);
+            let buffer = gizmos.buffer();
+            (buffer.strip_positions.clone(), buffer.strip_colors.clone())
+        }).unwrap();
+        let points = || positions.iter().zip(&colors).filter(|(position, _)| position.is_finite());
+        assert!(points().all(|(position, _)| (position.distance(center) - 5.0).abs() < 1e-3));
+        // the summer solstice path climbs higher than the winter one
+        let highest = |color: Srgba| points()
+            .filter(|(_, point_color)| **point_color == LinearRgba::from(color))
+            .map(|(position, _)| position.y - center.y)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let (summer, equinox, winter) = (highest(css::GOLD), highest(css::AQUA), highest(css::LIGHT_BLUE));
+        assert!(summer > equinox && equinox > winter, "{} {} {}", summer, equinox, winter);
+    }
+}
@@ -0,0 +1,217 @@
+//! Contains the [`SolarEclipse`] resource and [`SolarEclipseEvent`] for eclipses of the sun by the
+//! [`Moon`](crate::Moon)
+use std::f32::consts::PI;
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, Sun};
+use crate::conversion::*;
+
+
+/// Detects the moon passing in front of the sun, and optionally dims the sun while it does
+/// 
+/// While this resource exists, the angle between the sun and moon in the [`Environment`] is
+/// checked every frame, [`obscuration`](SolarEclipse::obscuration) is set to how much of the sun's
+/// disk is covered, and [`SolarEclipseEvent`]s are triggered as it changes. Defaults to the
+/// apparent sizes of Earth's sun and moon, with dimming turned on.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{Environment, LunarOrbit, SolarEclipse};
+/// # let mut app = App::new();
+/// // A new moon right in front of the sun at noon, for a scripted eclipse
+/// app.insert_resource(Environment::default()
+///     .with_lunar_orbit(LunarOrbit::EARTH.with_phase_offset(0.0))
+///     .with_date(Environment::DATE_SUMMER));
+/// // A bigger moon, so the eclipse is total
+/// app.insert_resource(SolarEclipse::default().with_moon_angular_radius_deg(0.3));
+/// ```
+/// 
+/// **Note:** only the global [`Environment`] is checked, so suns with their own
+/// [`SunEnvironment`](crate::SunEnvironment) are dimmed along with the rest
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct SolarEclipse
+{
+    /// Apparent radius of the sun's disk in radians
+    pub sun_angular_radius: f32,
+
+    /// Apparent radius of the moon's disk in radians
+    pub moon_angular_radius: f32,
+
+    /// Whether to scale the illuminance of every [`Sun`] by the uncovered part of its disk
+    pub dim_sun: bool,
+
+    /// Fraction of the sun's disk currently covered by the moon, from `0.0` to `1.0`, updated
+    /// every frame
+    pub obscuration: f32,
+}
+
+impl Default for SolarEclipse {
+    fn default() -> Self {
+        Self {
+            sun_angular_radius: 0.2666 * DEG_TO_RAD,
+            moon_angular_radius: 0.2591 * DEG_TO_RAD,
+            dim_sun: true,
+            obscuration: 0.0,
+        }
+    }
+}
+
+impl SolarEclipse
+{
+    /// Sets the apparent radius of the sun's disk in degrees
+    pub const fn with_sun_angular_radius_deg(mut self, radius: f32) -> Self {
+        self.sun_angular_radius = radius * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the apparent radius of the moon's disk in degrees
+    pub const fn with_moon_angular_radius_deg(mut self, radius: f32) -> Self {
+        self.moon_angular_radius = radius * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets whether the sun lights are dimmed during an eclipse
+    pub const fn with_dim_sun(mut self, dim_sun: bool) -> Self {
+        self.dim_sun = dim_sun;
+        self
+    }
+
+    /// Fraction of the sun's disk covered by the moon in `environment`, from `0.0` to `1.0`
+    pub fn obscuration_at(&self, environment: &Environment) -> f32 {
+        let separation = environment.sun_direction().angle_between(environment.moon_direction());
+        disk_overlap(separation, self.sun_angular_radius, self.moon_angular_radius)
+    }
+}
+
+/// Triggered while the [`SolarEclipse`] obscuration changes
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SolarEclipseEvent;
+/// # let mut app = App::new();
+/// app.add_observer(|eclipse: On<SolarEclipseEvent>| {
+///     if eclipse.obscuration > 0.9 {
+///         println!("The birds have gone quiet");
+///     }
+/// });
+/// ```
+/// 
+/// The first event of an eclipse is when the moon starts covering the sun, and the last one has an
+/// obscuration of `0.0` once it has passed
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct SolarEclipseEvent
+{
+    /// Fraction of the sun's disk covered by the moon, from `0.0` to `1.0`
+    pub obscuration: f32,
+}
+
+/// Illuminance a [`Sun`] had before an eclipse dimmed it, and the dimmed value written to it
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub(crate) struct EclipseDimming
+{
+    base: f32,
+    dimmed: f32,
+}
+
+/// Fraction of a disk with `radius` covered by a disk with `cover_radius`, with their centers
+/// `separation` apart
+fn disk_overlap(separation: f32, radius: f32, cover_radius: f32) -> f32 {
+    if radius <= 0.0 || separation >= radius + cover_radius {
+        return 0.0;
+    }
+    if separation <= (radius - cover_radius).abs() {
+        return (cover_radius * cover_radius / (radius * radius)).min(1.0);
+    }
+    // area of the lens where the two circles overlap
+    let (d, r, c) = (separation, radius, cover_radius);
+    let lens = c * c * ((d * d + c * c - r * r) / (2.0 * d * c)).clamp(-1.0, 1.0).acos()
+        + r * r * ((d * d + r * r - c * c) / (2.0 * d * r)).clamp(-1.0, 1.0).acos()
+        - 0.5 * ((-d + c + r) * (d + c - r) * (d - c + r) * (d + c + r)).max(0.0).sqrt();
+    (lens / (PI * r * r)).clamp(0.0, 1.0)
+}
+
+/// Updates the [`SolarEclipse`] obscuration, triggers [`SolarEclipseEvent`]s and dims the suns
+pub(crate) fn update_solar_eclipse(
+    mut commands: Commands,
+    mut eclipse: ResMut<SolarEclipse>,
+    mut suns: Query<(Entity, &mut DirectionalLight, Option<&mut EclipseDimming>), With<Sun>>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let obscuration = eclipse.obscuration_at(&cinematic_overrides.resolve(&environment));
+    if eclipse.obscuration != obscuration {
+        if eclipse.obscuration > 0.0 || obscuration > 0.0 {
+            commands.trigger(SolarEclipseEvent { obscuration });
+        }
+        eclipse.obscuration = obscuration;
+    }
+    let dimming = if eclipse.dim_sun { obscuration } else { 0.0 };
+    for (entity, mut light, previous) in &mut suns {
+        match previous {
+            Some(mut previous) => {
+                // something else changed the illuminance since it was dimmed, so start from that
+                if light.illuminance != previous.dimmed {
+                    previous.base = light.illuminance;
+                }
+                if dimming > 0.0 {
+                    previous.dimmed = previous.base * (1.0 - dimming);
+                    light.illuminance = previous.dimmed;
+                } else {
+                    light.illuminance = previous.base;
+                    commands.entity(entity).remove::<EclipseDimming>();
+                }
+            },
+            None if dimming > 0.0 => {
+                let base = light.illuminance;
+                let dimmed = base * (1.0 - dimming);
+                light.illuminance = dimmed;
+                commands.entity(entity).insert(EclipseDimming { base, dimmed });
+            },
+            None => {},
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[derive(Default, Resource)]
+    struct Triggered(Vec<f32>);
+
+    #[test]
+    fn dims_and_restores() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.init_resource::<Triggered>();
+        app.add_observer(|eclipse: On<SolarEclipseEvent>, mut triggered: ResMut<Triggered>| {
+            triggered.0.push(eclipse.obscuration);
+        });
+        app.insert_resource(SolarEclipse::default());
+        let sun = app.world_mut().spawn((DirectionalLight { illuminance: 100.0, ..default() }, Sun)).id();
+        // new moon right in front of the sun, for an annular eclipse
+        app.update();
+        let obscuration = app.world().resource::<SolarEclipse>().obscuration;
+        let expected = (0.2591f32 / 0.2666).powi(2);
+        assert!((obscuration - expected).abs() < 1e-3, "{}", obscuration);
+        let illuminance = app.world().get::<DirectionalLight>(sun).unwrap().illuminance;
+        assert!((illuminance - 100.0 * (1.0 - expected)).abs() < 0.1, "{}", illuminance);
+        // a few days later the moon has moved on
+        app.insert_resource(Environment::default().with_date(0.1));
+        app.update();
+        assert_eq!(app.world().get::<DirectionalLight>(sun).unwrap().illuminance, 100.0);
+        assert_eq!(app.world().resource::<Triggered>().0, [obscuration, 0.0]);
+    }
+
+    #[test]
+    fn partial_overlap() {
+        assert_eq!(disk_overlap(2.0, 1.0, 1.0), 0.0);
+        assert!((disk_overlap(1.0, 1.0, 1.0) - 0.391).abs() < 1e-3);
+        assert_eq!(disk_overlap(0.0, 1.0, 2.0), 1.0);
+    }
+}
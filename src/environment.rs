@@ -1,6 +1,7 @@
 //! Contains the [`Environment`] resource and its code
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
 use bevy::prelude::*;
+use crate::{AxialPrecession, Calendar, CalendarDate, EccentricityModel, Hemisphere, Irradiance, LightQuality, Location, LunarOrbit, MoonPhase, PlanetPreset, RotationDirection, Season, StarOffset};
 use crate::conversion::*;
 
 
@@ -42,10 +43,15 @@ use crate::conversion::*;
 /// an equivalent in some other more common unit for that value like degrees, but if you access or
 /// set the values directly they *must* be in radians.
 #[derive(Clone, Copy, Debug, Default)]
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
 pub struct Environment
 {
     /// Axial tilt of the planet being simulated, in radians
+    /// 
+    /// Works from `-PI/2` to `PI/2`. For a planet tipped further over, use
+    /// [`with_obliquity`](Environment::with_obliquity)
     pub axial_tilt: f32,
     
     /// Latitude in radians
@@ -65,6 +71,11 @@ pub struct Environment
     /// will loop back around to a point until floating point precision starts causing problems, so
     /// I recommend normalizing your time of day to `-PI` to `PI` range. Positive/increasing values
     /// are forward in time, and negative/decreasing values are backward
+    /// 
+    /// This is the time on the clock in the environment's time zone. With the default
+    /// [`longitude`](Environment::longitude) and [`utc_offset`](Environment::utc_offset) of `0.0`
+    /// it is the local solar time, otherwise the sun uses
+    /// [`solar_time_of_day`](Environment::solar_time_of_day) instead.
     pub time_of_day: f32,
     
     /// Time of year in radians
@@ -75,6 +86,58 @@ pub struct Environment
     /// `PI`. Positive/increasing values are forward in time, and negative/decreasing
     /// values are backward
     pub time_of_year: f32,
+    
+    /// Orbit of the moon used to point lights with a [`Moon`](crate::Moon) component
+    pub lunar_orbit: LunarOrbit,
+    
+    /// Longitude in radians, positive to the east of the prime meridian
+    /// 
+    /// Together with [`utc_offset`](Environment::utc_offset) this turns the clock time in
+    /// [`time_of_day`](Environment::time_of_day) into local solar time. Sharing the same time of
+    /// day between environments at different longitudes drives them all from one global clock.
+    pub longitude: f32,
+    
+    /// Offset of the environment's time zone from UTC, in radians of time (`PI/12` per hour)
+    pub utc_offset: f32,
+    
+    /// Shape of the planet's orbit, used to apply the equation of time to the sun
+    /// 
+    /// `None` by default, keeping solar noon at the same clock time every day
+    pub eccentricity: Option<EccentricityModel>,
+    
+    /// Where the second star of a binary system is compared to the primary, used to point lights
+    /// with a [`SecondarySun`](crate::SecondarySun) component
+    pub secondary_sun: StarOffset,
+    
+    /// Elevation in radians the sun has to be above to count as up for
+    /// [`is_daytime`](Environment::is_daytime), for terrain like mountains that hides the sun
+    /// before it reaches the horizon
+    /// 
+    /// `0.0` by default, the flat horizon. Only affects the daytime checks, the sun lights move
+    /// the same either way
+    pub horizon_offset: f32,
+    
+    /// Declination of the sun in radians, overriding the one worked out from the
+    /// [`time_of_year`](Environment::time_of_year) and [`axial_tilt`](Environment::axial_tilt)
+    /// 
+    /// `None` by default. Set it to drive the sun's height through the year from your own orbital
+    /// model, while the latitude and time of day still give the daily arc. See
+    /// [`declination`](Environment::declination)
+    pub declination: Option<f32>,
+    
+    /// Wobble of the planet's axis, which moves the seasons through the year over long periods
+    /// of time
+    /// 
+    /// No precession by default, keeping the summer solstice at a time of year of `0.0`
+    pub precession: AxialPrecession,
+    
+    /// Which way the planet spins. [`Prograde`](RotationDirection::Prograde) by default, with the
+    /// sun rising in the east
+    /// 
+    /// A [`Retrograde`](RotationDirection::Retrograde) planet mirrors the sky east to west, and
+    /// places further east reach noon *earlier* rather than later, so the
+    /// [`longitude`](Environment::longitude) works backwards compared to Earth.
+    pub rotation_direction: RotationDirection,
 }
 
 impl Environment
@@ -121,7 +184,10 @@ impl Environment
     /// let environment = Environment::default()
     ///     .with_latitude(Environment::LATITUDE_NEW_JERSEY);
     /// ```
-    pub const LATITUDE_NEW_JERSEY: f32 = 40.82706 * DEG_TO_RAD;
+    /// 
+    /// Same as the latitude of [`Location::NEW_JERSEY`]. For other places, and to set the
+    /// longitude and time zone too, see [`with_location`](Environment::with_location)
+    pub const LATITUDE_NEW_JERSEY: f32 = Location::NEW_JERSEY.latitude;
 
     /// Value t set [`latitude`](Environment::latitude) to the equator
     ///
@@ -168,7 +234,8 @@ impl Environment
     /// ```
     /// 
     /// **Note:** while latitudes in the southern hemisphere *are* supported, the time of year in
-    /// the southern hemisphere is going to be opposite from the northern hemisphere.
+    /// the southern hemisphere is going to be opposite from the northern hemisphere. Use
+    /// [`with_season`](Environment::with_season) to pick a season for either hemisphere.
     pub const DATE_WINTER: f32 = -PI;
 
     /// Value to set [`time_of_year`](Environment::time_of_year) halfway between the winter
@@ -183,7 +250,8 @@ impl Environment
     /// ```
     /// 
     /// **Note:** while latitudes in the southern hemisphere *are* supported, the time of year in
-    /// the southern hemisphere is going to be opposite from the northern hemisphere.
+    /// the southern hemisphere is going to be opposite from the northern hemisphere. Use
+    /// [`with_season`](Environment::with_season) to pick a season for either hemisphere.
     pub const DATE_SPRING: f32 = -PI / 2.0;
 
     /// Value to set [`time_of_year`](Environment::time_of_year) to the summer solstice, when the
@@ -198,7 +266,8 @@ impl Environment
     /// ```
     /// 
     /// **Note:** while latitudes in the southern hemisphere *are* supported, the time of year in
-    /// the southern hemisphere is going to be opposite from the northern hemisphere.
+    /// the southern hemisphere is going to be opposite from the northern hemisphere. Use
+    /// [`with_season`](Environment::with_season) to pick a season for either hemisphere.
     pub const DATE_SUMMER: f32 = 0.0;
 
     /// Value to set [`time_of_year`](Environment::time_of_year) halfway between
@@ -213,9 +282,106 @@ impl Environment
     /// ```
     /// 
     /// **Note:** while latitudes in the southern hemisphere *are* supported, the time of year in
-    /// the southern hemisphere is going to be opposite from the northern hemisphere.
+    /// the southern hemisphere is going to be opposite from the northern hemisphere. Use
+    /// [`with_season`](Environment::with_season) to pick a season for either hemisphere.
     pub const DATE_AUTUMN: f32 = PI / 2.0;
 
+    /// Color temperature of sunlight in Kelvin with the sun on the horizon, used by
+    /// [`sun_color_temperature_kelvin`](Environment::sun_color_temperature_kelvin)
+    pub const SUN_KELVIN_HORIZON: f32 = 1850.0;
+
+    /// Color temperature of sunlight in Kelvin with the sun high in the sky, used by
+    /// [`sun_color_temperature_kelvin`](Environment::sun_color_temperature_kelvin)
+    pub const SUN_KELVIN_ZENITH: f32 = 5800.0;
+
+    /// Creates a new `Environment` set to Earth, right now, according to the system clock
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Creates a new `Environment` resource with Earth's axial
+    /// // tilt and the current time at the prime meridian
+    /// let environment = Environment::earth_now();
+    /// ```
+    /// 
+    /// Latitude is set to the equator and the time of day is the local solar time at the prime
+    /// meridian (Greenwich). To get the time somewhere else, use
+    /// [`earth_now_at`](Environment::earth_now_at) instead.
+    /// 
+    /// **Note:** time of day is mean solar time, so it can be up to around 16 minutes off from
+    /// where the real sun is depending on the time of year.
+    pub fn earth_now() -> Self {
+        Self::earth_now_at(0.0, 0.0)
+    }
+
+    /// Creates a new `Environment` set to Earth, right now, according to the system clock, at the
+    /// given latitude and longitude in degrees
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Creates a new `Environment` resource with the sun
+    /// // where it is right now in Tokyo
+    /// let environment = Environment::earth_now_at(35.68, 139.69);
+    /// ```
+    /// 
+    /// Longitude is positive to the east of the prime meridian and negative to the west. It is only
+    /// used to work out the local solar time of day and isn't stored in the resource.
+    pub fn earth_now_at(latitude: f32, longitude: f32) -> Self {
+        let now = crate::real_time::unix_now();
+        Self::default()
+            .with_axial_tilt(Self::AXIAL_TILT_EARTH)
+            .with_latitude_deg(latitude)
+            .with_time_of_day(crate::real_time::time_of_day(now, longitude * DEG_TO_RAD))
+            .with_date(crate::real_time::time_of_year(now))
+    }
+
+    /// Creates a new `Environment` set to Earth at a given date and time, at the given latitude
+    /// and longitude in degrees
+    /// 
+    /// ```no_run
+    /// # use chrono::{TimeZone, Utc};
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // The sun over the Eiffel Tower at 6 PM UTC on
+    /// // the 14th of July 2025
+    /// let datetime = Utc.with_ymd_and_hms(2025, 7, 14, 18, 0, 0).unwrap();
+    /// let environment = Environment::from_utc(datetime, 48.86, 2.29);
+    /// ```
+    /// 
+    /// Unlike [`earth_now_at`](Environment::earth_now_at) the longitude is stored in the resource
+    /// and [`time_of_day`](Environment::time_of_day) is the UTC clock time, and
+    /// [`EccentricityModel::EARTH`] is applied so the sun lines up with the real sun within a few
+    /// minutes. Any time zone works, the date and time are converted to UTC first. To keep the sun
+    /// synced with the system clock afterwards, insert a [`SolarClock::wall_clock`](crate::SolarClock::wall_clock).
+    /// Only available with the `real_time` feature.
+    #[cfg(feature = "real_time")]
+    pub fn from_utc<Tz: chrono::TimeZone>(datetime: chrono::DateTime<Tz>, latitude: f32, longitude: f32) -> Self {
+        let seconds = crate::real_time::unix_seconds(&datetime);
+        Self::default()
+            .with_axial_tilt(Self::AXIAL_TILT_EARTH)
+            .with_latitude_deg(latitude)
+            .with_longitude_deg(longitude)
+            .with_eccentricity(EccentricityModel::EARTH)
+            .with_time_of_day(crate::real_time::time_of_day(seconds, 0.0))
+            .with_date(crate::real_time::time_of_year(seconds))
+    }
+
+    /// Creates a new `Environment` with the axial tilt of `preset`
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, PlanetPreset};
+    /// // Creates a new `Environment` resource on Mars,
+    /// // near where the Curiosity rover landed
+    /// let environment = Environment::from_preset(PlanetPreset::Mars)
+    ///     .with_latitude_deg(-4.6);
+    /// ```
+    /// 
+    /// Everything else is left at its default value. The day and year length aren't part of the
+    /// environment, see [`TimeFlow::with_preset`](crate::TimeFlow::with_preset) for those.
+    pub fn from_preset(preset: PlanetPreset) -> Self {
+        Self::default()
+            .with_axial_tilt(preset.axial_tilt())
+            .with_rotation_direction(preset.rotation_direction())
+    }
+
     /// Sets the axial tilt of the environment planet in radians
     /// 
     /// ```no_run
@@ -249,6 +415,39 @@ impl Environment
         self.with_axial_tilt(axial_tilt * DEG_TO_RAD)
     }
 
+    /// Sets the tilt of the planet's axis against its orbit in radians, including tilts past `90`
+    /// degrees
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::conversion::DEG_TO_RAD;
+    /// # use kj_bevy_realistic_sun::{Environment, RotationDirection};
+    /// // Venus, tipped almost upside down, spins backwards with a small tilt
+    /// let environment = Environment::default()
+    ///     .with_obliquity(177.36 * DEG_TO_RAD);
+    /// assert_eq!(environment.rotation_direction, RotationDirection::Retrograde);
+    /// ```
+    /// 
+    /// An axis tilted past `90` degrees is the same as one tilted less than `90` degrees the other
+    /// way up, spinning the opposite way. So the [`axial_tilt`](Environment::axial_tilt) is folded
+    /// back into the `-PI/2` to `PI/2` range it works in, and the
+    /// [`rotation_direction`](Environment::rotation_direction) is overwritten to match.
+    pub fn with_obliquity(mut self, obliquity: f32) -> Self {
+        let obliquity = wrap_angle(obliquity);
+        let upside_down = obliquity.abs() > PI / 2.0;
+        self.axial_tilt = if upside_down { obliquity.signum() * PI - obliquity } else { obliquity };
+        self.rotation_direction = if upside_down { RotationDirection::Retrograde } else { RotationDirection::Prograde };
+        self
+    }
+
+    /// Tilt of the planet's axis against its orbit in radians, from `-PI` to `PI`, the reverse of
+    /// [`with_obliquity`](Environment::with_obliquity)
+    pub fn obliquity(&self) -> f32 {
+        match self.rotation_direction {
+            RotationDirection::Prograde => self.axial_tilt,
+            RotationDirection::Retrograde => self.axial_tilt.signum() * PI - self.axial_tilt,
+        }
+    }
+
     /// Sets the time of year of the enviroment in radians
     /// 
     /// ```no_run
@@ -263,6 +462,39 @@ impl Environment
         self
     }
 
+    /// Sets the time of year to the start of `season` in `hemisphere`
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, Hemisphere, Season};
+    /// // Creates a new `Environment` resource in Sydney
+    /// // at the start of the southern summer
+    /// let environment = Environment::default()
+    ///     .with_latitude_deg(-33.9)
+    ///     .with_season(Season::Summer, Hemisphere::Southern);
+    /// ```
+    /// 
+    /// Unlike the `DATE_` constants this takes the hemisphere into account, so summer is always
+    /// the warm season, and it follows any [`precession`](Environment::precession). See
+    /// [`Season::start_date`]
+    pub fn with_season(self, season: Season, hemisphere: Hemisphere) -> Self {
+        self.with_date(wrap_angle(season.start_date(hemisphere) + self.precession.angle))
+    }
+
+    /// Current season at the environment's [`latitude`](Environment::latitude)
+    pub fn season(&self) -> Season {
+        self.season_at_latitude(self.latitude)
+    }
+
+    /// Current season at a latitude in radians, for checking other places on the same planet
+    pub fn season_at_latitude(&self, latitude: f32) -> Season {
+        Season::from_time_of_year(self.seasonal_time_of_year(), Hemisphere::from_latitude(latitude))
+    }
+
+    /// Current season at a latitude in degrees
+    pub fn season_at_latitude_deg(&self, latitude: f32) -> Season {
+        self.season_at_latitude(latitude * DEG_TO_RAD)
+    }
+
     /// Sets the environment latitude in Radians
     /// 
     /// ```no_run
@@ -296,6 +528,239 @@ impl Environment
         self.with_latitude(latitude * DEG_TO_RAD)
     }
 
+    /// Sets the longitude in radians, positive to the east of the prime meridian
+    pub const fn with_longitude(mut self, longitude: f32) -> Self {
+        self.longitude = longitude;
+        self
+    }
+
+    /// Sets the longitude in degrees, positive to the east of the prime meridian
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // 3 PM on the clock in Tokyo, which is 9 hours ahead of UTC
+    /// let environment = Environment::default()
+    ///     .with_latitude_deg(35.68)
+    ///     .with_longitude_deg(139.69)
+    ///     .with_utc_offset_hours(9.0)
+    ///     .with_hours_since_noon(3.0);
+    /// ```
+    pub const fn with_longitude_deg(self, longitude: f32) -> Self {
+        self.with_longitude(longitude * DEG_TO_RAD)
+    }
+
+    /// Sets the latitude, longitude and UTC offset to a real-world `location`
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, Location};
+    /// // 3 PM on the clock in Tokyo
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_location(Location::TOKYO)
+    ///     .with_hours_since_noon(3.0);
+    /// ```
+    /// 
+    /// See the [`locations`](crate::locations) module for the built in places
+    pub const fn with_location(self, location: Location) -> Self {
+        self.with_latitude(location.latitude)
+            .with_longitude(location.longitude)
+            .with_utc_offset(location.utc_offset)
+    }
+
+    /// Sets the orbit of the moon
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, LunarOrbit};
+    /// // Starts the year with a full moon
+    /// let environment = Environment::default()
+    ///     .with_lunar_orbit(LunarOrbit::EARTH.with_phase_offset_deg(180.0));
+    /// ```
+    pub const fn with_lunar_orbit(mut self, lunar_orbit: LunarOrbit) -> Self {
+        self.lunar_orbit = lunar_orbit;
+        self
+    }
+
+    /// Sets the offset of the environment's time zone from UTC in radians of time
+    pub const fn with_utc_offset(mut self, utc_offset: f32) -> Self {
+        self.utc_offset = utc_offset;
+        self
+    }
+
+    /// Sets the offset of the environment's time zone from UTC in hours
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // New York, 5 hours behind UTC
+    /// let environment = Environment::default()
+    ///     .with_longitude_deg(-74.0)
+    ///     .with_utc_offset_hours(-5.0);
+    /// ```
+    pub const fn with_utc_offset_hours(self, utc_offset: f32) -> Self {
+        self.with_utc_offset(utc_offset * HOURS_TO_RAD)
+    }
+
+    /// Sets the shape of the planet's orbit, turning on the equation of time
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{EccentricityModel, Environment};
+    /// // Solar noon drifts over the year like it does on Earth
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_eccentricity(EccentricityModel::EARTH);
+    /// ```
+    pub const fn with_eccentricity(mut self, eccentricity: EccentricityModel) -> Self {
+        self.eccentricity = Some(eccentricity);
+        self
+    }
+
+    /// Sets where the secondary sun of a binary system is compared to the primary
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, StarOffset};
+    /// // A second sun trailing the first by half an hour, a little further north
+    /// let environment = Environment::default()
+    ///     .with_secondary_sun(StarOffset::default()
+    ///         .with_elongation_deg(-7.5)
+    ///         .with_inclination_deg(3.0));
+    /// ```
+    pub const fn with_secondary_sun(mut self, secondary_sun: StarOffset) -> Self {
+        self.secondary_sun = secondary_sun;
+        self
+    }
+
+    /// Sets the elevation in radians the sun has to be above to count as daytime
+    pub const fn with_horizon_offset(mut self, horizon_offset: f32) -> Self {
+        self.horizon_offset = horizon_offset;
+        self
+    }
+
+    /// Sets the elevation in degrees the sun has to be above to count as daytime
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // A valley where the mountains block the sun until it's 5 degrees up
+    /// let environment = Environment::default()
+    ///     .with_latitude_deg(46.0)
+    ///     .with_horizon_offset_deg(5.0);
+    /// ```
+    pub const fn with_horizon_offset_deg(self, horizon_offset: f32) -> Self {
+        self.with_horizon_offset(horizon_offset * DEG_TO_RAD)
+    }
+
+    /// Sets which way the planet spins, see [`rotation_direction`](Environment::rotation_direction)
+    pub const fn with_rotation_direction(mut self, rotation_direction: RotationDirection) -> Self {
+        self.rotation_direction = rotation_direction;
+        self
+    }
+
+    /// Sets the [`precession`](Environment::precession) of the planet's axis
+    pub const fn with_precession(mut self, precession: AxialPrecession) -> Self {
+        self.precession = precession;
+        self
+    }
+
+    /// Sets the sun's declination in radians directly, instead of working it out from the time of
+    /// year and axial tilt
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # fn my_orbit_declination(days: f32) -> f32 { days.sin() * 0.4 }
+    /// fn follow_my_orbit(mut environment: ResMut<Environment>, time: Res<Time>) {
+    ///     let declination = my_orbit_declination(time.elapsed_secs() / 60.0);
+    ///     *environment = environment.with_declination(declination);
+    /// }
+    /// ```
+    /// 
+    /// The [`time_of_year`](Environment::time_of_year) is kept for seasons, the calendar and the
+    /// moon, but no longer moves the sun. Set [`declination`](Environment::declination) back to
+    /// `None` to go back to the time of year.
+    pub const fn with_declination(mut self, declination: f32) -> Self {
+        self.declination = Some(declination);
+        self
+    }
+
+    /// Sets the sun's declination in degrees directly, see
+    /// [`with_declination`](Environment::with_declination)
+    pub const fn with_declination_deg(self, declination: f32) -> Self {
+        self.with_declination(declination * DEG_TO_RAD)
+    }
+
+    /// Declination of the sun in radians, the angle north of the celestial equator
+    /// 
+    /// Either the overridden [`declination`](Environment::declination), or worked out from the
+    /// [`time_of_year`](Environment::time_of_year) and [`axial_tilt`](Environment::axial_tilt).
+    /// The same as the first value of [`sun_equatorial`](Environment::sun_equatorial)
+    pub fn declination(&self) -> f32 {
+        -self.earth_tilt_angle()
+    }
+
+    /// Declination of the sun in degrees, see [`declination`](Environment::declination)
+    pub fn declination_deg(&self) -> f32 {
+        self.declination() * RAD_TO_DEG
+    }
+
+    /// Copy of the environment with the times wrapped into the `-PI` to `PI` range and the angles
+    /// clamped to valid values
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// fn fast_forward(mut environment: ResMut<Environment>, time: Res<Time>) {
+    ///     environment.time_of_day += time.delta_secs();
+    ///     *environment = environment.normalized();
+    /// }
+    /// ```
+    /// 
+    /// [`time_of_day`](Environment::time_of_day) and [`time_of_year`](Environment::time_of_year)
+    /// loop around so they don't lose floating point precision, while
+    /// [`latitude`](Environment::latitude) and [`axial_tilt`](Environment::axial_tilt) are
+    /// clamped to the `-PI/2` to `PI/2` range. The sun ends up in the same place unless the angles
+    /// were out of range. To do this every frame, see
+    /// [`RealisticSunDirectionPlugin::with_normalized_environment`](crate::RealisticSunDirectionPlugin::with_normalized_environment)
+    pub fn normalized(mut self) -> Self {
+        self.normalize();
+        self
+    }
+
+    /// Wraps the times and clamps the angles in place, see [`normalized`](Environment::normalized)
+    pub fn normalize(&mut self) {
+        self.time_of_day = wrap_angle(self.time_of_day);
+        self.time_of_year = wrap_angle(self.time_of_year);
+        self.latitude = self.latitude.clamp(-PI / 2.0, PI / 2.0);
+        self.axial_tilt = self.axial_tilt.clamp(-PI / 2.0, PI / 2.0);
+    }
+
+    /// Local solar time of day in radians, worked out from the clock time in
+    /// [`time_of_day`](Environment::time_of_day), the [`longitude`](Environment::longitude) and
+    /// the [`utc_offset`](Environment::utc_offset)
+    /// 
+    /// This is the time the sun position is calculated from, with `0.0` at local solar noon. With
+    /// an [`eccentricity`](Environment::eccentricity) set it is apparent solar time, which
+    /// includes the [`equation_of_time`](Environment::equation_of_time).
+    pub fn solar_time_of_day(&self) -> f32 {
+        self.mean_solar_time_of_day() + self.equation_of_time()
+    }
+
+    /// Difference between apparent and mean solar time in radians of time at the current time of
+    /// year, or `0.0` if there is no [`eccentricity`](Environment::eccentricity) set
+    /// 
+    /// Positive values mean the sun is ahead of the clock, reaching solar noon early.
+    pub fn equation_of_time(&self) -> f32 {
+        self.eccentricity.map_or(0.0, |eccentricity| eccentricity.equation_of_time(self.time_of_year, self.axial_tilt))
+    }
+
+    /// Local mean solar time of day in radians, which ignores the equation of time
+    fn mean_solar_time_of_day(&self) -> f32 {
+        self.time_of_day + self.rotation_direction.sign() * self.longitude - self.utc_offset
+    }
+
+    /// Angle the sun is west of the local meridian, which goes the opposite way to the solar time
+    /// of day on a retrograde planet
+    fn hour_angle(&self, solar_time_of_day: f32) -> f32 {
+        self.rotation_direction.sign() * solar_time_of_day
+    }
+
     /// Sets the current solar time of day in radians. `0.0` is local solar noon, with `PI`/`-PI` at
     /// midnight. Positive values are forwards in time and negative values are backwards.
     /// 
@@ -324,4 +789,1036 @@ impl Environment
     pub const fn with_hours_since_noon(self, time_of_day: f32) -> Self {
         self.with_time_of_day(time_of_day * HOURS_TO_RAD)
     }
+
+    /// Sets the time of day and time of year from a date and clock time in `calendar`
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Calendar, Environment};
+    /// // 6:45 PM on Christmas Eve 2030 in London
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(51.5)
+    ///     .with_longitude_deg(-0.13)
+    ///     .with_calendar_date(&Calendar::gregorian(), 2030, 12, 24, 18, 45);
+    /// ```
+    /// 
+    /// `month` and `day` start at `1`, and the clock time is in the environment's time zone, so set
+    /// the [`utc_offset`](Environment::utc_offset) first. Days past the end of a month carry on
+    /// into the next month.
+    pub fn with_calendar_date(mut self, calendar: &Calendar, year: i32, month: u32, day: u32, hour: u32, minute: u32) -> Self {
+        let hours = hour as f64 + minute as f64 / 60.0;
+        let utc_hours = hours - (self.utc_offset * RAD_TO_HOURS) as f64;
+        self.time_of_day = (hours as f32 - 12.0) * HOURS_TO_RAD;
+        self.time_of_year = calendar.time_of_year(year, month, day, utc_hours);
+        self
+    }
+
+    /// Date and clock time in `calendar` for the current time of day and time of year
+    /// 
+    /// ```no_run
+    /// # use bevy::ecs::system::Res;
+    /// # use kj_bevy_realistic_sun::{Calendar, Environment};
+    /// fn show_date(environment: Res<Environment>, calendar: Res<Calendar>) {
+    ///     let date = environment.calendar_date(&calendar);
+    ///     let month = calendar.month_name(date.month).unwrap_or_default();
+    ///     println!("{} {}, {:02}:{:02}", month, date.day, date.hour, date.minute);
+    /// }
+    /// ```
+    /// 
+    /// The clock time is rounded to the nearest minute. The year isn't stored in the environment,
+    /// so leap days are counted as if it was the calendar's [`epoch_year`](Calendar::epoch_year).
+    pub fn calendar_date(&self, calendar: &Calendar) -> CalendarDate {
+        let minutes = ((self.time_of_day + PI).rem_euclid(TAU) * RAD_TO_HOURS * 60.0).round() as u32;
+        let utc_offset_hours = (self.utc_offset * RAD_TO_HOURS) as f64;
+        let (month, day) = calendar.month_and_day(self.time_of_year, minutes as f64 / 60.0, utc_offset_hours);
+        let minutes = minutes % (24 * 60);
+        CalendarDate { month, day, hour: minutes / 60, minute: minutes % 60 }
+    }
+
+    /// Sets the time of day and time of year to the values that point the sun light as close as
+    /// possible to `direction`, which is the direction the light *travels* in (the same direction
+    /// a [`Sun`](crate::Sun)'s [`Transform::forward`] faces)
+    /// 
+    /// ```no_run
+    /// # use bevy::math::Vec3;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Creates a new `Environment` resource with the time of day
+    /// // and year set so the sun light shines down and to the north
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(30.0)
+    ///     .with_light_direction(Vec3::new(0.0, -1.0, -1.0));
+    /// ```
+    /// 
+    /// [`latitude`](Environment::latitude) and [`axial_tilt`](Environment::axial_tilt) are left as
+    /// they are, so set those first. If the direction can't be reached with the current latitude
+    /// and axial tilt, the closest direction that can be reached is used instead. Each sun
+    /// direction is reached twice a year, once in the spring and once in the autumn, so the time
+    /// of year is kept in whichever half of the year it is already in. With a
+    /// [`declination`](Environment::declination) set, that is solved for instead of the time of year.
+    pub fn with_light_direction(mut self, direction: Vec3) -> Self {
+        let Some((earth_tilt_angle, solar_time_of_day)) = self.solve_light_direction(direction) else {
+            return self;
+        };
+        if self.declination.is_some() {
+            self.declination = Some(-earth_tilt_angle);
+        } else if self.axial_tilt != 0.0 {
+            let time_of_year_cos = (-2.0 * earth_tilt_angle / self.axial_tilt).clamp(-1.0, 1.0);
+            let time_of_year = time_of_year_cos.acos();
+            let time_of_year = if self.seasonal_time_of_year().sin() < 0.0 { -time_of_year } else { time_of_year };
+            self.time_of_year = wrap_angle(time_of_year + self.precession.angle);
+        }
+        self.time_of_day = self.solar_to_clock_time(solar_time_of_day);
+        self
+    }
+
+    /// Solves for the earth tilt angle and local solar time of day that point the light in
+    /// `direction` at this latitude, or `None` if `direction` is zero
+    pub(crate) fn solve_light_direction(&self, direction: Vec3) -> Option<(f32, f32)> {
+        let direction = direction.try_normalize()?;
+        // undo the latitude rotation to get the direction relative to the planet's axis
+        let local_direction = Quat::from_rotation_x(-self.latitude) * direction;
+        let earth_tilt_angle = (-local_direction.z).clamp(-1.0, 1.0).asin();
+        let hour_angle = local_direction.x.atan2(-local_direction.y);
+        Some((earth_tilt_angle, self.hour_angle(hour_angle)))
+    }
+
+    /// Sets the time of day and time of year to the values that would point a [`Sun`](crate::Sun)
+    /// the same way as `transform`
+    /// 
+    /// Useful for adopting the crate in a scene where the sun light has already been posed by hand
+    /// or imported from another tool, without having to re-light the scene.
+    /// 
+    /// ```no_run
+    /// # use bevy::math::Vec3;
+    /// # use bevy::transform::components::Transform;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // A sun light posed by hand somewhere else
+    /// let hand_posed_sun = Transform::default()
+    ///     .looking_to(Vec3::new(1.0, -2.0, 0.5), Vec3::Y);
+    /// // Creates a new `Environment` resource that points the sun the same way
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude(Environment::LATITUDE_NEW_JERSEY)
+    ///     .with_sun_transform(&hand_posed_sun);
+    /// ```
+    /// 
+    /// See [`with_light_direction`](Environment::with_light_direction) for how the values are solved
+    pub fn with_sun_transform(self, transform: &Transform) -> Self {
+        self.with_light_direction(*transform.forward())
+    }
+
+    /// Unit vector pointing from the world *towards* the sun
+    /// 
+    /// Lets you query where the sun is without needing an entity with a [`Sun`](crate::Sun)
+    /// component, for things like "is this spot sunny" checks or shader uniforms. The sun light
+    /// travels in the opposite direction.
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// let environment = Environment::default()
+    ///     .with_latitude_deg(30.0)
+    ///     .with_hours_since_noon(-2.0);
+    /// if environment.sun_direction().y > 0.0 {
+    ///     println!("The sun is up");
+    /// }
+    /// ```
+    /// 
+    /// See [`calculate_sun_direction`] to calculate it without an `Environment`
+    pub fn sun_direction(&self) -> Vec3 {
+        self.light_rotation() * Vec3::Y
+    }
+
+    /// Angle of the sun above the horizon in radians, negative when it's below the horizon
+    /// 
+    /// ```no_run
+    /// # use bevy::ecs::system::Res;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Solar panels make the most power with the sun straight overhead
+    /// fn solar_panel_efficiency(environment: Res<Environment>) -> f32 {
+    ///     environment.solar_elevation().sin().max(0.0)
+    /// }
+    /// ```
+    /// 
+    /// Calculated from the same model used to point [`Sun`](crate::Sun) lights, so it always
+    /// matches what the player sees (apart from any overrides or offsets applied to the light).
+    /// To get the elevation in degrees, use [`solar_elevation_deg`](Environment::solar_elevation_deg)
+    pub fn solar_elevation(&self) -> f32 {
+        direction_to_azimuth_elevation(self.sun_direction()).1
+    }
+
+    /// Angle of the sun above the horizon in degrees, negative when it's below the horizon
+    pub fn solar_elevation_deg(&self) -> f32 {
+        self.solar_elevation() * RAD_TO_DEG
+    }
+
+    /// Compass direction of the sun in radians, from `0.0` to `TAU`
+    /// 
+    /// Measured clockwise from north (the `-Z` axis), so east (the `+X` axis) is `PI/2` and south
+    /// (the `+Z` axis) is `PI`. To get the azimuth in degrees, use
+    /// [`solar_azimuth_deg`](Environment::solar_azimuth_deg)
+    /// 
+    /// **Note:** the azimuth is meaningless when the sun is straight up or down, and will be `0.0`
+    pub fn solar_azimuth(&self) -> f32 {
+        direction_to_azimuth_elevation(self.sun_direction()).0
+    }
+
+    /// Compass direction of the sun in degrees, from `0.0` to `360.0`
+    pub fn solar_azimuth_deg(&self) -> f32 {
+        self.solar_azimuth() * RAD_TO_DEG
+    }
+
+    /// Whether the sun is above the [`horizon_offset`](Environment::horizon_offset)
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # #[derive(Component)]
+    /// # struct Vampire;
+    /// fn burn_vampires(environment: Res<Environment>, vampires: Query<Entity, With<Vampire>>) {
+    ///     if environment.is_daytime() {
+    ///         for vampire in &vampires {
+    ///             println!("{} is burning", vampire);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    /// Meant as the one check gameplay code asks for whether the sun is up, so things like solar
+    /// panels and vampires agree with each other. To check against another elevation, use
+    /// [`is_sun_above`](Environment::is_sun_above)
+    pub fn is_daytime(&self) -> bool {
+        self.is_sun_above(self.horizon_offset)
+    }
+
+    /// Whether the sun is higher than `elevation` radians above the flat horizon
+    pub fn is_sun_above(&self, elevation: f32) -> bool {
+        self.solar_elevation() > elevation
+    }
+
+    /// Whether the sun is higher than `elevation` degrees above the flat horizon
+    pub fn is_sun_above_deg(&self, elevation: f32) -> bool {
+        self.is_sun_above(elevation * DEG_TO_RAD)
+    }
+
+    /// Kind of light the sun gives at its current height, like golden hour or blue hour, see
+    /// [`LightQuality`]
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::{Environment, LightQuality};
+    /// let environment = Environment::default()
+    ///     .with_latitude_deg(45.0)
+    ///     .with_hours_since_noon(5.5);
+    /// let golden = environment.light_quality() == LightQuality::GoldenHour;
+    /// ```
+    pub fn light_quality(&self) -> LightQuality {
+        LightQuality::from_elevation_deg(self.solar_elevation_deg())
+    }
+
+    /// Position of the sun in equatorial coordinates, as `(declination, hour_angle)` in radians
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # use kj_bevy_realistic_sun::conversion::RAD_TO_DEG;
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_hours_since_noon(-2.0);
+    /// let (declination, hour_angle) = environment.sun_equatorial();
+    /// println!("declination {:.2}, hour angle {:.2}", declination * RAD_TO_DEG, hour_angle * RAD_TO_DEG);
+    /// ```
+    /// 
+    /// Declination is the angle north of the celestial equator, and the hour angle is measured
+    /// westwards from the local meridian in the `-PI` to `PI` range, so it is negative in the
+    /// morning and positive in the afternoon. Turn them into horizontal coordinates at any
+    /// latitude with [`equatorial_to_horizontal`](crate::conversion::equatorial_to_horizontal).
+    pub fn sun_equatorial(&self) -> (f32, f32) {
+        (-self.earth_tilt_angle(), wrap_angle(self.hour_angle(self.solar_time_of_day())))
+    }
+
+    /// Position of the sun in horizontal coordinates, as `(altitude, azimuth)` in radians
+    /// 
+    /// Same as [`solar_elevation`](Environment::solar_elevation) and
+    /// [`solar_azimuth`](Environment::solar_azimuth), in the order astronomy code usually uses
+    pub fn sun_horizontal(&self) -> (f32, f32) {
+        let (azimuth, altitude) = direction_to_azimuth_elevation(self.sun_direction());
+        (altitude, azimuth)
+    }
+
+    /// Point on the planet where the sun is straight overhead, as `(latitude, longitude)` in
+    /// radians
+    /// 
+    /// The latitude is the sun's declination, and the longitude is in the `-PI` to `PI` range,
+    /// positive to the east of the prime meridian like [`longitude`](Environment::longitude)
+    pub fn subsolar_point(&self) -> (f32, f32) {
+        let (declination, hour_angle) = self.sun_equatorial();
+        (declination, wrap_angle(self.longitude - hour_angle))
+    }
+
+    /// Angle of the sun above the horizon in radians at any `latitude` and `longitude` on the
+    /// planet, at the same moment as this environment
+    /// 
+    /// Handy for shading the night side of a world map, see
+    /// [`sample_terminator`](Environment::sample_terminator) for the outline
+    pub fn solar_elevation_at(&self, latitude: f32, longitude: f32) -> f32 {
+        let (declination, subsolar_longitude) = self.subsolar_point();
+        equatorial_to_horizontal(declination, longitude - subsolar_longitude, latitude).0
+    }
+
+    /// `samples` points along the day/night terminator, the line on the planet where the sun is on
+    /// the horizon, as `Vec2::new(longitude, latitude)` in radians
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # use std::f32::consts::PI;
+    /// // Outline of the night side on a 1024x512 equirectangular world map
+    /// fn terminator_on_map(environment: Res<Environment>) -> Vec<Vec2> {
+    ///     environment.sample_terminator(128)
+    ///         .into_iter()
+    ///         .map(|point| Vec2::new((point.x / PI + 1.0) * 512.0, (0.5 - point.y / PI) * 512.0))
+    ///         .collect()
+    /// }
+    /// ```
+    /// 
+    /// Longitudes are spread evenly from `-PI` to `PI`, so the first and last samples meet at the
+    /// edges of the map. The night side is the half of the map away from the
+    /// [`subsolar_point`](Environment::subsolar_point). At the equinoxes the terminator runs from
+    /// pole to pole along two meridians, and the samples jump between `-PI/2` and `PI/2` latitude
+    /// where it crosses them.
+    pub fn sample_terminator(&self, samples: usize) -> Vec<Vec2> {
+        let steps = samples.saturating_sub(1).max(1) as f32;
+        let (declination, subsolar_longitude) = self.subsolar_point();
+        (0..samples)
+            .map(|sample| {
+                let longitude = -PI + sample as f32 / steps * TAU;
+                // the sun's height sin(lat)sin(dec) + cos(lat)cos(dec)cos(hour), solved for zero
+                let hour_angle = longitude - subsolar_longitude;
+                let latitude = (-hour_angle.cos() * declination.cos() * declination.sin().signum())
+                    .atan2(declination.sin().abs());
+                Vec2::new(longitude, latitude)
+            })
+            .collect()
+    }
+
+    /// Approximate power of direct sunlight in watts per square meter on a surface facing along
+    /// `normal`, on a clear day on Earth
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Crops on flat ground grow faster the more sun they get
+    /// fn crop_growth_rate(environment: Res<Environment>) -> f32 {
+    ///     environment.irradiance_on(Vec3::Y) / 1000.0
+    /// }
+    /// ```
+    /// 
+    /// `0.0` at night and for surfaces facing away from the sun. Uses [`Irradiance::EARTH`], see
+    /// [`Irradiance`] for other atmospheres and how it is worked out
+    pub fn irradiance_on(&self, normal: Vec3) -> f32 {
+        Irradiance::EARTH.on_surface(self.sun_direction(), normal)
+    }
+
+    /// Approximate color temperature of direct sunlight in Kelvin, from the height of the sun
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # use kj_bevy_realistic_sun::conversion::kelvin_to_color;
+    /// // Tints fog the same color as the sunlight
+    /// fn fog_color(environment: Res<Environment>) -> Color {
+    ///     kelvin_to_color(environment.sun_color_temperature_kelvin())
+    /// }
+    /// ```
+    /// 
+    /// Goes from [`SUN_KELVIN_HORIZON`](Self::SUN_KELVIN_HORIZON) with the sun on the horizon
+    /// (and below it) towards [`SUN_KELVIN_ZENITH`](Self::SUN_KELVIN_ZENITH) as it climbs, most of
+    /// the change happening in the first 20 degrees. Use
+    /// [`kelvin_to_color`](crate::conversion::kelvin_to_color) to turn it into a [`Color`] for
+    /// lights, emissive materials, fog or white balance
+    pub fn sun_color_temperature_kelvin(&self) -> f32 {
+        let elevation = self.solar_elevation().max(0.0) * RAD_TO_DEG;
+        let warmth = (-elevation / 10.0).exp();
+        Self::SUN_KELVIN_ZENITH + (Self::SUN_KELVIN_HORIZON - Self::SUN_KELVIN_ZENITH) * warmth
+    }
+
+    /// Direction shadows point along flat ground, as a unit vector on the XZ plane
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Where the tip of a sundial's shadow points, or `None` at night
+    /// fn sundial_hand(environment: Res<Environment>) -> Option<Vec3> {
+    ///     environment.shadow_direction_on_ground()
+    /// }
+    /// ```
+    /// 
+    /// Points directly away from the sun. Returns `None` when the sun is below the horizon, or
+    /// straight overhead and shadows have no direction
+    pub fn shadow_direction_on_ground(&self) -> Option<Vec3> {
+        let direction = self.sun_direction();
+        if direction.y <= 0.0 {
+            return None;
+        }
+        (-direction.with_y(0.0)).try_normalize()
+    }
+
+    /// Length of the shadow cast on flat ground by an upright object, as a multiple of the
+    /// object's height
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// let environment = Environment::default()
+    ///     .with_latitude_deg(45.0);
+    /// // At noon on the equinox at 45 degrees north, a 2 meter
+    /// // pole casts a 2 meter shadow
+    /// let shadow_length = environment.shadow_length_factor().unwrap() * 2.0;
+    /// ```
+    /// 
+    /// `0.0` with the sun straight overhead, growing without limit as the sun nears the horizon.
+    /// Returns `None` when the sun is below the horizon and there is no shadow to measure
+    pub fn shadow_length_factor(&self) -> Option<f32> {
+        let direction = self.sun_direction();
+        (direction.y > 0.0).then(|| direction.with_y(0.0).length() / direction.y)
+    }
+
+    /// `samples` directions *towards* the sun spread evenly over the current day, starting and
+    /// ending at local solar midnight
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(40.0);
+    /// // Points for drawing the sun's arc across the sky today, 20 units away
+    /// let arc: Vec<_> = environment.sample_day_path(48)
+    ///     .into_iter()
+    ///     .filter(|direction| direction.y >= 0.0)
+    ///     .map(|direction| direction * 20.0)
+    ///     .collect();
+    /// ```
+    /// 
+    /// The first and last samples are both at midnight so the path can be drawn as a closed loop.
+    /// The time of year is kept at its current value for the whole day.
+    pub fn sample_day_path(&self, samples: usize) -> Vec<Vec3> {
+        let steps = samples.saturating_sub(1).max(1) as f32;
+        let midnight = self.solar_to_clock_time(-PI);
+        (0..samples)
+            .map(|sample| self.with_time_of_day(midnight + sample as f32 / steps * TAU).sun_direction())
+            .collect()
+    }
+
+    /// `samples` directions *towards* the sun at the current clock time of day, spread evenly over
+    /// the year starting at the current time of year
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// // Where the sun is at 9 AM on each day of the year
+    /// let analemma = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(40.0)
+    ///     .with_hours_since_noon(-3.0)
+    ///     .sample_analemma(365);
+    /// ```
+    /// 
+    /// **Note:** without an [`eccentricity`](Environment::eccentricity) there is no equation of
+    /// time, so rather than the figure of eight seen on Earth, the analemma is a single arc traced
+    /// back and forth over the year
+    pub fn sample_analemma(&self, samples: usize) -> Vec<Vec3> {
+        let count = samples.max(1) as f32;
+        (0..samples)
+            .map(|sample| self.with_date(self.time_of_year + sample as f32 / count * TAU).sun_direction())
+            .collect()
+    }
+
+    /// Clock time of day the sun rises at in radians, in the same format as
+    /// [`time_of_day`](Environment::time_of_day), or `None` during polar day or polar night
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::conversion::RAD_TO_HOURS;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(50.0);
+    /// if let Some(sunset) = environment.sunset_time() {
+    ///     // hours past midnight, for a clock display
+    ///     let hours = sunset * RAD_TO_HOURS + 12.0;
+    ///     println!("Sun sets at {:02}:{:02}", hours as u32, (hours.fract() * 60.0) as u32);
+    /// }
+    /// ```
+    /// 
+    /// Solved for the current time of year, with the sun's center on a flat horizon, so it matches
+    /// the times of [`SunriseEvent`](crate::SunriseEvent) and [`SunsetEvent`](crate::SunsetEvent)
+    pub fn sunrise_time(&self) -> Option<f32> {
+        self.sunset_hour_angle().map(|hour_angle| self.solar_to_clock_time(-hour_angle))
+    }
+
+    /// Clock time of day the sun sets at in radians, in the same format as
+    /// [`time_of_day`](Environment::time_of_day), or `None` during polar day or polar night
+    /// 
+    /// See [`sunrise_time`](Environment::sunrise_time) for more details
+    pub fn sunset_time(&self) -> Option<f32> {
+        self.sunset_hour_angle().map(|hour_angle| self.solar_to_clock_time(hour_angle))
+    }
+
+    /// Length of time the sun is above the horizon in radians of time (`PI/12` per hour), or
+    /// `None` during polar day or polar night
+    pub fn daylight_duration(&self) -> Option<f32> {
+        self.sunset_hour_angle().map(|hour_angle| hour_angle * 2.0)
+    }
+
+    /// Whether the sun stays above the horizon all day at the current time of year, the midnight
+    /// sun of high latitudes in summer
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// let environment = Environment::default()
+    ///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+    ///     .with_latitude_deg(85.0)
+    ///     .with_date(Environment::DATE_SUMMER);
+    /// if environment.is_polar_day() {
+    ///     println!("The sun won't set today");
+    /// }
+    /// ```
+    /// 
+    /// Uses the sun's center on a flat horizon like [`sunrise_time`](Environment::sunrise_time),
+    /// which is `None` on the same days. See [`PolarDayStartEvent`](crate::PolarDayStartEvent) for
+    /// events when it starts and ends
+    pub fn is_polar_day(&self) -> bool {
+        self.sunset_hour_angle().is_none() && self.noon_height() > 0.0
+    }
+
+    /// Whether the sun stays below the horizon all day at the current time of year, the polar
+    /// night of high latitudes in winter
+    /// 
+    /// See [`is_polar_day`](Environment::is_polar_day) for more details
+    pub fn is_polar_night(&self) -> bool {
+        self.sunset_hour_angle().is_none() && self.noon_height() <= 0.0
+    }
+
+    /// Height of the sun at solar noon, the sine of its elevation, so positive when the sun is up
+    fn noon_height(&self) -> f32 {
+        // the sun's height at an hour angle of zero, from the same formula as `sunset_hour_angle`
+        (self.latitude + self.earth_tilt_angle()).cos()
+    }
+
+    /// Local solar time of day the sun sets at, or `None` if it doesn't cross the horizon today
+    fn sunset_hour_angle(&self) -> Option<f32> {
+        let earth_tilt_angle = self.earth_tilt_angle();
+        // the sun's height is cos(t)cos(tilt)cos(lat) - sin(tilt)sin(lat), solved for zero
+        let cos_hour_angle = earth_tilt_angle.tan() * self.latitude.tan();
+        (-1.0..=1.0).contains(&cos_hour_angle).then(|| cos_hour_angle.acos())
+    }
+
+    /// Converts local solar time of day into clock time at the current time of year, wrapped into
+    /// the `-PI` to `PI` range
+    pub(crate) fn solar_to_clock_time(&self, solar_time_of_day: f32) -> f32 {
+        let time_of_day = solar_time_of_day - self.equation_of_time() - self.rotation_direction.sign() * self.longitude + self.utc_offset;
+        wrap_angle(time_of_day)
+    }
+
+    /// Unit vector pointing from the world *towards* the moon
+    /// 
+    /// Works the same way as [`sun_direction`](Environment::sun_direction), with the moon ahead of
+    /// the sun by however far along its [`lunar_orbit`](Environment::lunar_orbit) it is
+    pub fn moon_direction(&self) -> Vec3 {
+        self.orbiting_body_direction(&self.lunar_orbit)
+    }
+
+    /// Unit vector pointing from the world towards a moon on `orbit`
+    pub(crate) fn orbiting_body_direction(&self, orbit: &LunarOrbit) -> Vec3 {
+        let elongation = orbit.elongation(self.time_of_year);
+        let inclination = (self.time_of_year + elongation).sin() * orbit.inclination;
+        self.offset_body_rotation(elongation, inclination) * Vec3::Y
+    }
+
+    /// Unit vector pointing from the world *towards* the secondary sun
+    /// 
+    /// Works the same way as [`sun_direction`](Environment::sun_direction), with the secondary sun
+    /// offset from the primary by the [`secondary_sun`](Environment::secondary_sun) offset
+    pub fn secondary_sun_direction(&self) -> Vec3 {
+        self.secondary_sun_rotation() * Vec3::Y
+    }
+
+    /// Rotation of a [`SecondarySun`](crate::SecondarySun) light, the same way as
+    /// [`light_rotation`](Environment::light_rotation) is for the primary
+    pub(crate) fn secondary_sun_rotation(&self) -> Quat {
+        self.offset_body_rotation(self.secondary_sun.elongation, self.secondary_sun.inclination)
+    }
+
+    /// Rotation to the sky of a body `elongation` radians ahead of the sun along the planet's orbit
+    /// and `inclination` radians north of the orbital plane
+    pub(crate) fn offset_body_rotation(&self, elongation: f32, inclination: f32) -> Quat {
+        let orbit_angle = self.seasonal_time_of_year() + elongation;
+        let earth_tilt_angle = -orbit_angle.cos() / 2.0 * self.axial_tilt - inclination;
+        sky_rotation(self.latitude, self.hour_angle(self.solar_time_of_day()) - elongation, earth_tilt_angle)
+    }
+
+    /// Phase of the moon from `0.0` to `1.0`, where `0.0` is a new moon, `0.25` the first quarter,
+    /// `0.5` a full moon and `0.75` the last quarter
+    /// 
+    /// ```no_run
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # let environment = Environment::default();
+    /// # let frame_count = 8;
+    /// // Picks a frame from a moon sprite sheet
+    /// let frame = (environment.moon_phase() * frame_count as f32) as usize % frame_count;
+    /// ```
+    /// 
+    /// See [`moon_phase_name`](Environment::moon_phase_name) for the named phase, and
+    /// [`moon_illumination`](Environment::moon_illumination) for how much of the moon is lit
+    pub fn moon_phase(&self) -> f32 {
+        self.lunar_orbit.elongation(self.time_of_year) / TAU
+    }
+
+    /// Named phase of the moon, see [`MoonPhase`]
+    pub fn moon_phase_name(&self) -> MoonPhase {
+        MoonPhase::from_phase(self.moon_phase())
+    }
+
+    /// Fraction of the moon's visible face that is lit, from `0.0` at a new moon to `1.0` at a
+    /// full moon
+    pub fn moon_illumination(&self) -> f32 {
+        (1.0 - self.lunar_orbit.elongation(self.time_of_year).cos()) / 2.0
+    }
+
+    /// Rotation of the fixed stars, used by [`CelestialSphere`](crate::CelestialSphere) entities
+    /// 
+    /// Turns directions from the sphere's local space into world space. The stars turn once a day
+    /// around the celestial pole, plus one extra turn over the year as the sun moves against them.
+    pub fn celestial_sphere_rotation(&self) -> Quat {
+        let sidereal_rotation = Quat::from_rotation_z(self.hour_angle(self.mean_solar_time_of_day()) + self.time_of_year);
+        Quat::from_rotation_x(self.latitude) * sidereal_rotation
+    }
+
+    /// Rotation that turns [`Vec3::NEG_Y`] into the direction the sun light travels in
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::Environment;
+    /// # let environment = Environment::default();
+    /// // The same as the light rotation, built from its parts
+    /// let rotation = environment.latitude_rotation()
+    ///     * environment.daily_rotation()
+    ///     * environment.tilt_rotation();
+    /// let light_direction = rotation * Vec3::NEG_Y;
+    /// ```
+    /// 
+    /// Made up of [`latitude_rotation`](Environment::latitude_rotation),
+    /// [`daily_rotation`](Environment::daily_rotation) and
+    /// [`tilt_rotation`](Environment::tilt_rotation) applied in that order, which can be composed
+    /// differently for custom skies, like spinning a sky texture with only the daily rotation
+    pub fn light_rotation(&self) -> Quat {
+        sky_rotation(self.latitude, self.hour_angle(self.solar_time_of_day()), self.earth_tilt_angle())
+    }
+
+    /// Rotation around the `X` axis by the angle the planet's axis leans away from the sun at the
+    /// current time of year, the first part of [`light_rotation`](Environment::light_rotation)
+    /// 
+    /// Its angle is the sun's [`declination`](Environment::declination) with the sign flipped
+    pub fn tilt_rotation(&self) -> Quat {
+        Quat::from_rotation_x(self.earth_tilt_angle())
+    }
+
+    /// Rotation around the `Z` axis (the planet's axis) by the hour angle of the sun, the spin of
+    /// the planet through the day and the second part of
+    /// [`light_rotation`](Environment::light_rotation)
+    /// 
+    /// Includes the [`rotation_direction`](Environment::rotation_direction), longitude, UTC offset
+    /// and equation of time, the same as the sun light
+    pub fn daily_rotation(&self) -> Quat {
+        Quat::from_rotation_z(self.hour_angle(self.solar_time_of_day()))
+    }
+
+    /// Rotation around the `X` axis by the [`latitude`](Environment::latitude), tipping the
+    /// planet's axis up to where it is in the local sky, the last part of
+    /// [`light_rotation`](Environment::light_rotation)
+    pub fn latitude_rotation(&self) -> Quat {
+        Quat::from_rotation_x(self.latitude)
+    }
+
+    /// Angle the planet's axis leans away from the sun at the current time of year, which is the
+    /// sun's declination with the sign flipped
+    fn earth_tilt_angle(&self) -> f32 {
+        match self.declination {
+            Some(declination) => -declination,
+            None => -self.seasonal_time_of_year().cos() / 2.0 * self.axial_tilt,
+        }
+    }
+
+    /// Time of year measured from the summer solstice, after the axis has precessed
+    fn seasonal_time_of_year(&self) -> f32 {
+        self.time_of_year - self.precession.angle
+    }
+}
+
+/// Keeps the [`Environment`] resource normalized, for
+/// [`RealisticSunDirectionPlugin::with_normalized_environment`](crate::RealisticSunDirectionPlugin::with_normalized_environment)
+pub(crate) fn normalize_environment(mut environment: ResMut<Environment>) {
+    let normalized = environment.normalized();
+    // only write when something moved, so the environment isn't marked as changed every frame
+    if normalized.time_of_day != environment.time_of_day
+        || normalized.time_of_year != environment.time_of_year
+        || normalized.latitude != environment.latitude
+        || normalized.axial_tilt != environment.axial_tilt {
+        *environment = normalized;
+    }
+}
+
+/// Rotation from the planet's orbital frame to the local sky at `latitude`, for a body at the
+/// given hour angle and earth tilt angle
+fn sky_rotation(latitude: f32, time_of_day: f32, earth_tilt_angle: f32) -> Quat {
+    let earth_tilt_rotation = Quat::from_rotation_x(earth_tilt_angle);
+    let time_of_day_rotation = Quat::from_rotation_z(time_of_day);
+    let latitude_rotation = Quat::from_rotation_x(latitude);
+    latitude_rotation * time_of_day_rotation * earth_tilt_rotation
+}
+
+/// Gives a [`Sun`](crate::Sun) its own environment instead of the global [`Environment`] resource
+/// 
+/// Lets several suns in the same world be driven by different parameters, like one per planet or
+/// one per split-screen view at different latitudes. Change the values in this component the
+/// same way you would change the [`Environment`] resource.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{Environment, Sun, SunEnvironment};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // A sun for a second planet, with a much steeper axial tilt
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     SunEnvironment(Environment::default()
+///         .with_axial_tilt_deg(60.0)
+///         .with_latitude_deg(-20.0)),
+/// ));
+/// ```
+/// 
+/// **Note:** only the global [`Environment`] is advanced by a [`SolarClock`](crate::SolarClock) or
+/// [`TimeFlow`](crate::TimeFlow) and affected by
+/// [`CinematicOverrides`](crate::CinematicOverrides), so the time in a `SunEnvironment` needs to be
+/// updated by your own systems. It also works on a [`Moon`](crate::Moon).
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SunEnvironment(pub Environment);
+
+/// Calculates the unit vector pointing from the world *towards* the sun, using the same
+/// parameters as the [`Environment`] resource, all in radians
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{calculate_sun_direction, Environment};
+/// # use kj_bevy_realistic_sun::conversion::{DEG_TO_RAD, HOURS_TO_RAD};
+/// // Two hours before noon on the spring equinox, 30 degrees north, on Earth
+/// let direction = calculate_sun_direction(
+///     -2.0 * HOURS_TO_RAD,
+///     Environment::DATE_SPRING,
+///     30.0 * DEG_TO_RAD,
+///     Environment::AXIAL_TILT_EARTH,
+/// );
+/// ```
+/// 
+/// Same as [`Environment::sun_direction`], see [`Environment`] for what each value means
+pub fn calculate_sun_direction(time_of_day: f32, time_of_year: f32, latitude: f32, axial_tilt: f32) -> Vec3 {
+    Environment {
+        axial_tilt,
+        latitude,
+        time_of_day,
+        time_of_year,
+        ..Default::default()
+    }.sun_direction()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn sun_direction_at_noon() {
+        // straight up at the equator at noon with no tilt
+        assert!(calculate_sun_direction(0.0, 0.0, 0.0, 0.0).abs_diff_eq(Vec3::Y, 1e-6));
+        // towards the south at noon, north of the equator
+        let environment = Environment::default().with_latitude_deg(45.0);
+        let direction = environment.sun_direction();
+        assert!(abs_diff_eq!(direction.y, direction.z, epsilon = 1e-6));
+        assert!(direction.z > 0.0);
+        assert!(abs_diff_eq!(direction.length(), 1.0, epsilon = 1e-6));
+    }
+
+    #[test]
+    fn elevation_and_azimuth() {
+        // sun rises in the east at the equinox
+        let sunrise = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(45.0)
+            .with_date(Environment::DATE_SPRING)
+            .with_hours_since_noon(-6.0);
+        assert!(abs_diff_eq!(sunrise.solar_elevation(), 0.0, epsilon = 1e-5));
+        assert!(abs_diff_eq!(sunrise.solar_azimuth_deg(), 90.0, epsilon = 1e-3));
+        // and is 45 degrees up in the south at noon
+        let noon = sunrise.with_time_of_day(Environment::TIME_NOON);
+        assert!(abs_diff_eq!(noon.solar_elevation_deg(), 45.0, epsilon = 1e-3));
+        assert!(abs_diff_eq!(noon.solar_azimuth_deg(), 180.0, epsilon = 1e-3));
+    }
+
+    #[test]
+    fn equatorial_and_horizontal() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(40.0)
+            .with_longitude_deg(12.0)
+            .with_date(0.4)
+            .with_hours_since_noon(-3.5);
+        let (declination, hour_angle) = environment.sun_equatorial();
+        assert!(abs_diff_eq!(hour_angle, environment.solar_time_of_day(), epsilon = 1e-6));
+        let (altitude, azimuth) = environment.sun_horizontal();
+        let (converted_altitude, converted_azimuth) = equatorial_to_horizontal(declination, hour_angle, environment.latitude);
+        assert!(abs_diff_eq!(converted_altitude, altitude, epsilon = 1e-5));
+        assert!(abs_diff_eq!(converted_azimuth, azimuth, epsilon = 1e-5));
+        let (back_declination, back_hour_angle) = horizontal_to_equatorial(altitude, azimuth, environment.latitude);
+        assert!(abs_diff_eq!(back_declination, declination, epsilon = 1e-5));
+        assert!(abs_diff_eq!(back_hour_angle, hour_angle, epsilon = 1e-5));
+    }
+
+    #[test]
+    fn declination_override() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(40.0)
+            .with_hours_since_noon(-2.0);
+        assert!(abs_diff_eq!(environment.declination(), environment.sun_equatorial().0));
+        let overridden = environment.with_declination_deg(-10.0);
+        assert!(abs_diff_eq!(overridden.declination_deg(), -10.0, epsilon = 1e-4));
+        // the time of year no longer moves the sun
+        assert!(overridden.sun_direction().abs_diff_eq(overridden.with_date(2.0).sun_direction(), 1e-6));
+        // noon elevation is 90 - latitude + declination
+        assert!(abs_diff_eq!(overridden.with_time_of_day(0.0).solar_elevation_deg(), 40.0, epsilon = 1e-3));
+        let solved = overridden.with_declination(0.0).with_light_direction(-overridden.sun_direction());
+        assert!(abs_diff_eq!(solved.declination(), overridden.declination(), epsilon = 1e-5));
+    }
+
+    #[test]
+    fn precession_moves_solstice() {
+        let precession = AxialPrecession::new(100.0).with_angle(0.5);
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_precession(precession);
+        let still = environment.with_precession(AxialPrecession::default());
+        assert!(abs_diff_eq!(environment.with_date(0.5).declination(), still.declination()));
+        assert_eq!(environment.with_date(0.5).season(), Season::Summer);
+        assert_eq!(environment.with_date(0.4).season(), Season::Spring);
+        let mut advanced = precession;
+        advanced.advance(TAU * 10.0);
+        assert!(abs_diff_eq!(advanced.angle, 0.5 + TAU / 10.0, epsilon = 1e-5));
+    }
+
+    #[test]
+    fn retrograde_sun_rises_in_west() {
+        let morning = Environment::default()
+            .with_latitude_deg(20.0)
+            .with_hours_since_noon(-3.0);
+        let retrograde = morning.with_rotation_direction(RotationDirection::Retrograde);
+        assert!(morning.sun_direction().x > 0.0);
+        assert!(retrograde.sun_direction().abs_diff_eq(morning.sun_direction() * Vec3::new(-1.0, 1.0, 1.0), 1e-5));
+        let solved = retrograde.with_time_of_day(1.0).with_light_direction(-retrograde.sun_direction());
+        assert!(abs_diff_eq!(solved.time_of_day, retrograde.time_of_day, epsilon = 1e-4));
+        // further east reaches noon earlier
+        let east = retrograde.with_longitude_deg(15.0);
+        assert!(abs_diff_eq!(east.solar_time_of_day(), retrograde.solar_time_of_day() - 15.0 * DEG_TO_RAD, epsilon = 1e-5));
+    }
+
+    #[test]
+    fn terminator() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_longitude_deg(-30.0)
+            .with_date(0.3)
+            .with_hours_since_noon(2.0);
+        let (latitude, longitude) = environment.subsolar_point();
+        let overhead = environment.with_latitude(latitude).with_longitude(longitude);
+        assert!(abs_diff_eq!(overhead.sun_direction().y, 1.0, epsilon = 1e-5));
+        assert!(abs_diff_eq!(environment.solar_elevation_at(0.0, environment.longitude), environment.solar_elevation(), epsilon = 1e-5));
+        for point in environment.sample_terminator(16) {
+            assert!(abs_diff_eq!(environment.solar_elevation_at(point.y, point.x), 0.0, epsilon = 1e-5), "{point}");
+        }
+    }
+
+    #[test]
+    fn daytime_with_horizon_offset() {
+        // 3 degrees up, a little after sunrise at the equator
+        let morning = Environment::default().with_time_of_day(-PI / 2.0 + 3.0 * DEG_TO_RAD);
+        assert!(morning.is_daytime());
+        assert!(morning.is_sun_above_deg(2.9));
+        assert!(!morning.is_sun_above_deg(3.1));
+        assert!(!morning.with_horizon_offset_deg(5.0).is_daytime());
+        assert!(!morning.with_time_of_day(PI).is_daytime());
+    }
+
+    #[test]
+    fn sunrise_and_sunset() {
+        let equator = Environment::default();
+        assert!(abs_diff_eq!(equator.sunrise_time().unwrap(), -PI / 2.0, epsilon = 1e-5));
+        assert!(abs_diff_eq!(equator.daylight_duration().unwrap(), PI, epsilon = 1e-5));
+        let summer = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(50.0)
+            .with_longitude_deg(10.0)
+            .with_utc_offset_hours(2.0);
+        let sunset = summer.sunset_time().unwrap();
+        assert!(summer.daylight_duration().unwrap() > PI);
+        assert!(abs_diff_eq!(summer.with_time_of_day(sunset).solar_elevation(), 0.0, epsilon = 1e-4));
+        let sunrise = summer.sunrise_time().unwrap();
+        assert!(abs_diff_eq!(summer.with_time_of_day(sunrise).solar_elevation(), 0.0, epsilon = 1e-4));
+        // midnight sun and polar night
+        let arctic = summer.with_latitude_deg(80.0);
+        assert_eq!(arctic.sunset_time(), None);
+        assert_eq!(arctic.with_date(Environment::DATE_WINTER).daylight_duration(), None);
+        assert!(arctic.is_polar_day() && !arctic.is_polar_night());
+        assert!(arctic.with_date(Environment::DATE_WINTER).is_polar_night());
+        assert!(!summer.is_polar_day() && !summer.is_polar_night());
+    }
+
+    #[test]
+    fn shadows_on_ground() {
+        let environment = Environment::default().with_latitude_deg(45.0);
+        assert!(abs_diff_eq!(environment.shadow_length_factor().unwrap(), 1.0, epsilon = 1e-5));
+        // the sun is to the south at noon, so shadows point north
+        assert!(environment.shadow_direction_on_ground().unwrap().abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        let overhead = Environment::default();
+        assert!(abs_diff_eq!(overhead.shadow_length_factor().unwrap(), 0.0, epsilon = 1e-5));
+        let night = environment.with_hours_since_noon(12.0);
+        assert_eq!(night.shadow_direction_on_ground(), None);
+        assert_eq!(night.shadow_length_factor(), None);
+    }
+
+    #[test]
+    fn sampled_paths() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(40.0)
+            .with_longitude_deg(15.0)
+            .with_hours_since_noon(-3.0);
+        let day = environment.sample_day_path(5);
+        assert_eq!(day.len(), 5);
+        assert!(day[0].abs_diff_eq(day[4], 1e-5));
+        assert!(day[2].abs_diff_eq(environment.with_time_of_day(environment.solar_to_clock_time(0.0)).sun_direction(), 1e-5));
+        let analemma = environment.sample_analemma(4);
+        assert!(analemma[0].abs_diff_eq(environment.sun_direction(), 1e-5));
+        assert!(analemma[2].abs_diff_eq(environment.with_date(Environment::DATE_WINTER).sun_direction(), 1e-5));
+    }
+
+    #[test]
+    fn equation_of_time_moves_solar_noon() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_date(135.0 / 365.2422 * TAU);
+        assert_eq!(environment.equation_of_time(), 0.0);
+        let eccentric = environment.with_eccentricity(EccentricityModel::EARTH);
+        // early November, the sun reaches solar noon about 16 minutes before the clock does
+        let noon = eccentric.solar_to_clock_time(0.0) * RAD_TO_HOURS * 60.0;
+        assert!((noon + 16.4).abs() < 1.0, "{}", noon);
+        assert!(abs_diff_eq!(eccentric.with_time_of_day(noon / 60.0 * HOURS_TO_RAD).solar_time_of_day(), 0.0, epsilon = 1e-5));
+    }
+
+    #[test]
+    fn longitude_and_utc_offset() {
+        // 1 PM on the clock, two hours ahead of UTC, at 15 degrees east is solar noon
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(45.0)
+            .with_longitude_deg(15.0)
+            .with_utc_offset_hours(2.0)
+            .with_hours_since_noon(1.0);
+        assert!(abs_diff_eq!(environment.solar_time_of_day(), 0.0, epsilon = 1e-6));
+        let solar = environment.with_longitude(0.0).with_utc_offset(0.0).with_time_of_day(0.0);
+        assert!(environment.sun_direction().abs_diff_eq(solar.sun_direction(), 1e-5));
+        // solving keeps the clock time
+        let solved = environment.with_light_direction(-environment.sun_direction());
+        assert!(abs_diff_eq!(solved.time_of_day, environment.time_of_day, epsilon = 1e-4));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(30.0)
+            .with_hours_since_noon(-2.0);
+        let serialized = ron::to_string(&environment).unwrap();
+        let deserialized: Environment = ron::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.latitude, environment.latitude);
+        assert_eq!(deserialized.time_of_day, environment.time_of_day);
+        // missing values fall back to their defaults
+        let partial: Environment = ron::from_str("(latitude: 1.0)").unwrap();
+        assert_eq!(partial.latitude, 1.0);
+        assert_eq!(partial.lunar_orbit.period, LunarOrbit::EARTH.period);
+    }
+
+    #[test]
+    fn color_temperature_rises_with_sun() {
+        let environment = Environment::default().with_latitude_deg(45.0);
+        let night = environment.with_hours_since_noon(12.0).sun_color_temperature_kelvin();
+        let morning = environment.with_hours_since_noon(-5.0).sun_color_temperature_kelvin();
+        let noon = environment.sun_color_temperature_kelvin();
+        assert_eq!(night, Environment::SUN_KELVIN_HORIZON);
+        assert!(night < morning && morning < noon, "{night} {morning} {noon}");
+        assert!(noon <= Environment::SUN_KELVIN_ZENITH);
+    }
+
+    #[test]
+    fn rotation_parts_compose_light_rotation() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(35.0)
+            .with_date(1.2)
+            .with_hours_since_noon(-4.5);
+        let composed = environment.latitude_rotation() * environment.daily_rotation() * environment.tilt_rotation();
+        assert!((composed * Vec3::NEG_Y).abs_diff_eq(-environment.sun_direction(), 1e-5));
+    }
+
+    #[test]
+    fn light_direction_round_trip() {
+        let tests = vec![
+            Environment::default(),
+            Environment::default()
+                .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+                .with_latitude(Environment::LATITUDE_NEW_JERSEY)
+                .with_hours_since_noon(-3.0)
+                .with_date(1.0),
+            Environment::default()
+                .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+                .with_latitude_deg(-35.0)
+                .with_hours_since_noon(5.0)
+                .with_date(-2.5),
+            Environment::default()
+                .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+                .with_latitude_deg(20.0)
+                .with_eccentricity(EccentricityModel::EARTH)
+                .with_hours_since_noon(2.0)
+                .with_date(2.3),
+        ];
+        for environment in tests {
+            let direction = environment.light_rotation() * Vec3::NEG_Y;
+            let solved = Environment { eccentricity: environment.eccentricity, ..default() }
+                .with_axial_tilt(environment.axial_tilt)
+                .with_latitude(environment.latitude)
+                .with_date(environment.time_of_year)
+                .with_light_direction(direction);
+            let solved_direction = solved.light_rotation() * Vec3::NEG_Y;
+            assert!(
+                solved_direction.abs_diff_eq(direction, 1e-4),
+                "Expected {:?} to solve back to {:?}, but {:?} was computed",
+                environment, direction, solved_direction,
+            );
+            assert!(abs_diff_eq!(solved.time_of_day, environment.time_of_day, epsilon = 1e-4));
+            assert!(abs_diff_eq!(solved.time_of_year, environment.time_of_year, epsilon = 1e-3));
+        }
+    }
 }
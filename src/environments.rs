@@ -0,0 +1,160 @@
+//! Contains the [`Environments`] resource, a collection of environments that suns pick from with an
+//! [`EnvironmentKey`]
+use std::collections::HashMap;
+use bevy::prelude::*;
+use crate::{Environment, SunEnvironment};
+
+
+/// Identifies an [`Environment`] in the [`Environments`] collection
+/// 
+/// Any number can be used, like one per level or planet in your game
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EnvironmentId(pub u32);
+
+/// A collection of [`Environment`]s set up ahead of time, for games that stream between levels
+/// or planets that each have their own sky
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{Environment, EnvironmentId, EnvironmentKey, Environments, Sun};
+/// const HOME: EnvironmentId = EnvironmentId(0);
+/// const DESERT: EnvironmentId = EnvironmentId(1);
+/// 
+/// fn setup(mut commands: Commands) {
+///     commands.insert_resource(Environments::default()
+///         .with(HOME, Environment::default().with_latitude_deg(50.0))
+///         .with(DESERT, Environment::default().with_latitude_deg(23.0).with_hours_since_noon(3.0)));
+///     commands.spawn((DirectionalLight::default(), Sun, EnvironmentKey(HOME)));
+/// }
+/// 
+/// fn travel_to_desert(mut suns: Query<&mut EnvironmentKey>) {
+///     for mut key in &mut suns {
+///         key.0 = DESERT;
+///     }
+/// }
+/// ```
+/// 
+/// Each entity with an [`EnvironmentKey`] gets a [`SunEnvironment`] copied from the collection
+/// whenever the key or the collection changes, so it works anywhere a `SunEnvironment` does.
+/// Switching a sun's key switches its sky instantly, without touching the global [`Environment`].
+/// 
+/// **Note:** like a `SunEnvironment`, the environments in the collection aren't advanced by a
+/// [`SolarClock`](crate::SolarClock) or [`TimeFlow`](crate::TimeFlow), so their times need to be
+/// updated by your own systems
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct Environments
+{
+    environments: HashMap<EnvironmentId, Environment>,
+}
+
+impl Environments
+{
+    /// Adds an environment to the collection
+    pub fn with(mut self, id: EnvironmentId, environment: Environment) -> Self {
+        self.insert(id, environment);
+        self
+    }
+
+    /// Adds or replaces an environment, returning the one it replaced
+    pub fn insert(&mut self, id: EnvironmentId, environment: Environment) -> Option<Environment> {
+        self.environments.insert(id, environment)
+    }
+
+    /// Removes an environment, returning it if it was there
+    pub fn remove(&mut self, id: EnvironmentId) -> Option<Environment> {
+        self.environments.remove(&id)
+    }
+
+    /// The environment with the given id, if there is one
+    pub fn get(&self, id: EnvironmentId) -> Option<&Environment> {
+        self.environments.get(&id)
+    }
+
+    /// The environment with the given id to change, if there is one
+    pub fn get_mut(&mut self, id: EnvironmentId) -> Option<&mut Environment> {
+        self.environments.get_mut(&id)
+    }
+
+    /// Every environment in the collection, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (EnvironmentId, &Environment)> {
+        self.environments.iter().map(|(id, environment)| (*id, environment))
+    }
+}
+
+/// Picks which environment in the [`Environments`] collection an entity follows
+/// 
+/// Keeps the entity's [`SunEnvironment`] in line with the collection. If there's no environment
+/// with the key's id the `SunEnvironment` is removed, and the entity follows the global
+/// [`Environment`] until there is one. Removing the key removes the `SunEnvironment` too.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EnvironmentKey(pub EnvironmentId);
+
+/// Copies environments from the [`Environments`] collection into each keyed entity's
+/// [`SunEnvironment`]
+pub(crate) fn apply_environment_keys(
+    mut commands: Commands,
+    mut keyed: Query<(Entity, Ref<EnvironmentKey>, Option<&mut SunEnvironment>)>,
+    environments: Res<Environments>,
+){
+    let collection_changed = environments.is_changed();
+    for (entity, key, sun_environment) in &mut keyed {
+        if !(collection_changed || key.is_changed()) {
+            continue;
+        }
+        match (environments.get(key.0), sun_environment) {
+            (Some(environment), Some(mut sun_environment)) => sun_environment.0 = *environment,
+            (Some(environment), None) => {
+                commands.entity(entity).insert(SunEnvironment(*environment));
+            },
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<SunEnvironment>();
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+/// Removes the [`SunEnvironment`] that came from a removed [`EnvironmentKey`]
+pub(crate) fn on_environment_key_removed(remove: On<Remove, EnvironmentKey>, mut commands: Commands) {
+    // the entity may be on its way to being despawned
+    if let Ok(mut entity) = commands.get_entity(remove.entity) {
+        entity.try_remove::<SunEnvironment>();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn suns_follow_their_key() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let morning = Environment::default().with_hours_since_noon(-3.0);
+        let evening = Environment::default().with_hours_since_noon(4.0);
+        app.insert_resource(Environments::default()
+            .with(EnvironmentId(0), morning)
+            .with(EnvironmentId(1), evening));
+        let sun = app.world_mut().spawn((Sun, EnvironmentKey(EnvironmentId(0)))).id();
+        app.update();
+        app.update();
+        let forward = |app: &App| app.world().get::<Transform>(sun).unwrap().forward();
+        assert!(forward(&app).abs_diff_eq(-morning.sun_direction(), 1e-5));
+        app.world_mut().get_mut::<EnvironmentKey>(sun).unwrap().0 = EnvironmentId(1);
+        app.update();
+        app.update();
+        assert!(forward(&app).abs_diff_eq(-evening.sun_direction(), 1e-5));
+        app.world_mut().entity_mut(sun).remove::<EnvironmentKey>();
+        app.update();
+        assert!(app.world().get::<SunEnvironment>(sun).is_none());
+    }
+}
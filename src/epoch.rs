@@ -0,0 +1,120 @@
+//! Contains [`EpochTime`], a single number for storing the time of day and year in save games
+use bevy::prelude::*;
+use crate::Environment;
+use crate::clock::{angle_from_phase, phase_from_angle};
+
+
+/// Seconds since the start of your world, for saving the time as one number that only goes up
+/// 
+/// The [`Environment`] stores the time as two wrapping angles, which lose track of how many days
+/// have passed and can drift apart when saved and loaded separately. An `EpochTime` counts whole
+/// seconds instead, and converts to the time of day and year given how long your days and years
+/// are.
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{Environment, EpochTime};
+/// const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+/// const DAYS_PER_YEAR: f64 = 365.2422;
+/// // Loading a save
+/// let saved = EpochTime(1_000_000);
+/// let environment = saved.to_environment(Environment::default(), SECONDS_PER_DAY, DAYS_PER_YEAR);
+/// // Saving, in the same year that was loaded
+/// let year = saved.years_elapsed(SECONDS_PER_DAY, DAYS_PER_YEAR);
+/// let saved = EpochTime::from_environment(&environment, SECONDS_PER_DAY, DAYS_PER_YEAR, year);
+/// ```
+/// 
+/// The epoch is solar midnight at [`DATE_WINTER`](Environment::DATE_WINTER), the same as a
+/// [`SunTimeIntegrator`](crate::SunTimeIntegrator) with both phases at `0.0`. Days are assumed to
+/// be a fixed length, so the time is mean solar time like
+/// [`time_of_day`](Environment::time_of_day).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EpochTime(pub u64);
+
+impl EpochTime
+{
+    /// How far through the day it is, from `0.0` at solar midnight to just under `1.0`
+    pub fn day_phase(self, seconds_per_day: f64) -> f64 {
+        if seconds_per_day <= 0.0 {
+            return 0.0;
+        }
+        (self.0 as f64).rem_euclid(seconds_per_day) / seconds_per_day
+    }
+
+    /// How far through the year it is, from `0.0` at the winter solstice to just under `1.0`
+    pub fn year_phase(self, seconds_per_day: f64, days_per_year: f64) -> f64 {
+        let seconds_per_year = seconds_per_day * days_per_year;
+        if seconds_per_year <= 0.0 {
+            return 0.0;
+        }
+        (self.0 as f64).rem_euclid(seconds_per_year) / seconds_per_year
+    }
+
+    /// Number of full days since the epoch
+    pub fn days_elapsed(self, seconds_per_day: f64) -> u64 {
+        if seconds_per_day <= 0.0 {
+            return 0;
+        }
+        (self.0 as f64 / seconds_per_day).floor() as u64
+    }
+
+    /// Number of full years since the epoch
+    pub fn years_elapsed(self, seconds_per_day: f64, days_per_year: f64) -> u64 {
+        let seconds_per_year = seconds_per_day * days_per_year;
+        if seconds_per_year <= 0.0 {
+            return 0;
+        }
+        (self.0 as f64 / seconds_per_year).floor() as u64
+    }
+
+    /// Sets the time of day and year in `environment` to this time
+    /// 
+    /// Time of year is left alone if `days_per_year` is `0.0` or less, and the whole environment
+    /// if `seconds_per_day` is
+    pub fn to_environment(self, environment: Environment, seconds_per_day: f64, days_per_year: f64) -> Environment {
+        if seconds_per_day <= 0.0 {
+            return environment;
+        }
+        let environment = environment.with_time_of_day(angle_from_phase(self.day_phase(seconds_per_day)));
+        if days_per_year <= 0.0 {
+            return environment;
+        }
+        environment.with_date(angle_from_phase(self.year_phase(seconds_per_day, days_per_year)))
+    }
+
+    /// Works out the time in `environment` as seconds since the epoch
+    /// 
+    /// The environment only knows the time within the current year, so the number of
+    /// `years_elapsed` has to be given, usually from the [`EpochTime`] the environment was loaded
+    /// from. The time of day picks the exact day, so the result doesn't suffer from the rounding
+    /// of [`time_of_year`](Environment::time_of_year).
+    pub fn from_environment(environment: &Environment, seconds_per_day: f64, days_per_year: f64, years_elapsed: u64) -> Self {
+        let day_phase = phase_from_angle(environment.time_of_day);
+        let year_phase = phase_from_angle(environment.time_of_year);
+        let approximate_days = (years_elapsed as f64 + year_phase) * days_per_year.max(0.0);
+        let days = (approximate_days - day_phase).round() + day_phase;
+        Self((days * seconds_per_day).round().max(0.0) as u64)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_environment() {
+        let (seconds_per_day, days_per_year) = (1200.0, 30.5);
+        // partway through the fifth year
+        let epoch = EpochTime(4 * 1200 * 30 + 1200 * 20 + 900 + 1200 * 2);
+        assert_eq!(epoch.days_elapsed(seconds_per_day), 4 * 30 + 22);
+        assert_eq!(epoch.years_elapsed(seconds_per_day, days_per_year), 4);
+        assert_eq!(epoch.day_phase(seconds_per_day), 0.75);
+        let environment = epoch.to_environment(Environment::default(), seconds_per_day, days_per_year);
+        // 6 hours after noon
+        assert!((environment.time_of_day - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(EpochTime::from_environment(&environment, seconds_per_day, days_per_year, 4), epoch);
+    }
+}
@@ -0,0 +1,139 @@
+//! Contains the [`FacesSun`] component for props that turn to face the sun
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, SunEnvironment, SunReferenceFrame};
+
+
+/// Turns an entity so its forward (`-Z`) side faces *towards* the sun, for sunflowers, solar
+/// panels, mirrors and other heliotropic props
+/// 
+/// Unlike a [`Sun`](crate::Sun), which points along the direction the light travels, this points
+/// back up at the sun. An [`axis`](FacesSun::axis) limits the entity to turning around one axis,
+/// like a solar panel on a single-axis tracker or a turret that only yaws.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::FacesSun;
+/// fn spawn_props(mut commands: Commands) {
+///     // a sunflower head that follows the sun across the sky
+///     commands.spawn((FacesSun::default(), Transform::from_xyz(0.0, 1.5, 0.0)));
+///     // a solar panel that turns east to west around a north-south axle
+///     commands.spawn(FacesSun::around_axis(Vec3::Z));
+///     // a statue that only turns on the spot
+///     commands.spawn(FacesSun::yaw_only());
+/// }
+/// ```
+/// 
+/// Follows the [`Environment`] resource with any [`CinematicOverrides`], or the entity's own
+/// [`SunEnvironment`] if it has one. Only the rotation is written, and only when it changes.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct FacesSun
+{
+    /// World space axis to turn around, or `None` to turn freely. Doesn't need to be normalized
+    pub axis: Option<Vec3>,
+    /// Whether to stay where it is while the sun is below the horizon, instead of facing it
+    /// through the ground. Defaults to `true`
+    pub hold_below_horizon: bool,
+}
+
+impl Default for FacesSun {
+    fn default() -> Self {
+        Self {
+            axis: None,
+            hold_below_horizon: true,
+        }
+    }
+}
+
+impl FacesSun
+{
+    /// Creates a new `FacesSun` that only turns around `axis`
+    pub const fn around_axis(axis: Vec3) -> Self {
+        Self {
+            axis: Some(axis),
+            hold_below_horizon: true,
+        }
+    }
+
+    /// Creates a new `FacesSun` that only turns around the vertical axis, staying upright
+    pub const fn yaw_only() -> Self {
+        Self::around_axis(Vec3::Y)
+    }
+
+    /// Sets whether to stay where it is while the sun is below the horizon
+    pub const fn with_hold_below_horizon(mut self, hold_below_horizon: bool) -> Self {
+        self.hold_below_horizon = hold_below_horizon;
+        self
+    }
+
+    /// Rotation that faces `sun_direction` within the constraints, or `None` if there is no
+    /// sensible way to face it
+    pub fn rotation_towards(&self, sun_direction: Vec3) -> Option<Quat> {
+        self.rotation_towards_in(sun_direction, &SunReferenceFrame::Y_UP)
+    }
+
+    /// Rotation that faces `sun_direction` in the Y-up frame of the [`Environment`] within the
+    /// constraints, for a world using `reference_frame`
+    pub fn rotation_towards_in(
+        &self, sun_direction: Vec3, reference_frame: &SunReferenceFrame,
+    ) -> Option<Quat> {
+        if self.hold_below_horizon && sun_direction.y <= 0.0 {
+            return None;
+        }
+        let sun_direction = reference_frame.to_world(sun_direction);
+        let (facing, up) = match self.axis.and_then(Vec3::try_normalize) {
+            // the sun's direction flattened onto the plane the entity turns in
+            Some(axis) => (sun_direction.reject_from_normalized(axis).try_normalize()?, axis),
+            None => (sun_direction, reference_frame.up),
+        };
+        Some(Transform::IDENTITY.looking_to(facing, up).rotation)
+    }
+}
+
+/// Turns every entity with a [`FacesSun`] towards the sun
+pub(crate) fn update_sun_facing(
+    mut entities: Query<(&FacesSun, &mut Transform, Option<&SunEnvironment>)>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    let global_direction = cinematic_overrides.resolve(&environment).sun_direction();
+    for (faces_sun, mut transform, sun_environment) in &mut entities {
+        let sun_direction = sun_environment.map_or(global_direction, |sun_environment| sun_environment.0.sun_direction());
+        let Some(rotation) = faces_sun.rotation_towards_in(sun_direction, &reference_frame) else {
+            continue;
+        };
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn faces_sun_with_constraints() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_latitude_deg(30.0).with_hours_since_noon(-3.0);
+        app.insert_resource(environment);
+        let free = app.world_mut().spawn(FacesSun::default()).id();
+        let yaw = app.world_mut().spawn(FacesSun::yaw_only()).id();
+        let night = Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT);
+        let held = app.world_mut().spawn((FacesSun::default(), SunEnvironment(night))).id();
+        app.update();
+        let transform = |entity| *app.world().get::<Transform>(entity).unwrap();
+        let sun_direction = environment.sun_direction();
+        assert!(transform(free).forward().abs_diff_eq(sun_direction, 1e-5));
+        let yaw = transform(yaw);
+        assert!(yaw.up().abs_diff_eq(Vec3::Y, 1e-5));
+        assert!(yaw.forward().abs_diff_eq(sun_direction.with_y(0.0).normalize(), 1e-5));
+        assert_eq!(transform(held).rotation, Quat::IDENTITY);
+    }
+}
@@ -0,0 +1,159 @@
+//! Contains the [`FiniteSun`] component for placing a visible sun in the sky
+use bevy::prelude::*;
+use crate::{Sun, SunState};
+
+
+/// Places a visible sun mesh or billboard in the sky, lined up with the sun light
+/// 
+/// Attach to the entity that draws the sun's disk, and its [`Transform`] is moved every frame to
+/// [`radius`](FiniteSun::radius) units along the sun direction from the
+/// [`origin`](FiniteSun::origin), usually the camera so the sun always looks infinitely far away.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{FiniteSun, Sun};
+/// fn spawn_sun(mut commands: Commands, camera: Single<Entity, With<Camera>>) {
+///     let light = commands.spawn((DirectionalLight::default(), Sun)).id();
+///     // A sun disk that sits behind everything else, lined up with the light's shadows
+///     commands.spawn((
+///         FiniteSun::default()
+///             .with_radius(5000.0)
+///             .with_origin(*camera)
+///             .with_light(light),
+///         Transform::from_scale(Vec3::splat(100.0)),
+///     ));
+/// }
+/// ```
+/// 
+/// Follows the direction of the [`light`](FiniteSun::light) if it has one, including smoothing and
+/// offsets, or the [`SunState`] if it doesn't. It's placed in [`PostUpdate`] after transforms
+/// are propagated, using the origin's position for this frame, so it doesn't trail behind a
+/// moving camera. The entity's [`GlobalTransform`] is written too, so it shouldn't have a parent.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct FiniteSun
+{
+    /// Distance from the origin to place the sun at, in world units
+    pub radius: f32,
+
+    /// Entity to place the sun around, like the camera, or `None` for the world origin
+    pub origin: Option<Entity>,
+
+    /// [`Sun`] light to line the sun up with, or `None` to follow the [`SunState`]
+    pub light: Option<Entity>,
+
+    /// Whether to turn the entity so its `+Z` side faces the origin, which is the side Bevy's
+    /// `Rectangle` mesh faces. Defaults to `true`
+    pub billboard: bool,
+}
+
+impl Default for FiniteSun {
+    fn default() -> Self {
+        Self {
+            radius: 1000.0,
+            origin: None,
+            light: None,
+            billboard: true,
+        }
+    }
+}
+
+impl FiniteSun
+{
+    /// Sets the distance from the origin
+    pub const fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the entity to place the sun around
+    pub const fn with_origin(mut self, origin: Entity) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Sets the sun light to line up with
+    pub const fn with_light(mut self, light: Entity) -> Self {
+        self.light = Some(light);
+        self
+    }
+
+    /// Sets whether the entity turns to face the origin
+    pub const fn with_billboard(mut self, billboard: bool) -> Self {
+        self.billboard = billboard;
+        self
+    }
+}
+
+/// Moves every [`FiniteSun`] into place around its origin
+pub(crate) fn place_finite_suns(
+    mut suns: Query<(&FiniteSun, &mut Transform, &mut GlobalTransform)>,
+    origins: Query<&GlobalTransform, Without<FiniteSun>>,
+    lights: Query<&GlobalTransform, (With<Sun>, Without<FiniteSun>)>,
+    state: Res<SunState>,
+){
+    for (finite_sun, mut transform, mut global_transform) in &mut suns {
+        let direction = match finite_sun.light.and_then(|light| lights.get(light).ok()) {
+            Some(light) => *light.back(),
+            None => state.direction,
+        };
+        let origin = finite_sun.origin
+            .and_then(|origin| origins.get(origin).ok())
+            .map_or(Vec3::ZERO, GlobalTransform::translation);
+        let mut placed = transform.with_translation(origin + direction * finite_sun.radius);
+        if finite_sun.billboard {
+            let up = if direction.y.abs() > 0.999 { Vec3::Z } else { Vec3::Y };
+            placed.look_to(direction, up);
+        }
+        if *transform != placed {
+            *transform = placed;
+            *global_transform = GlobalTransform::from(placed);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::transform::TransformPlugin;
+    use crate::{Environment, RealisticSunDirectionPlugin, SunTarget};
+
+    #[test]
+    fn placed_around_moving_origin() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_latitude_deg(30.0).with_hours_since_noon(2.0);
+        app.insert_resource(environment);
+        let camera = app.world_mut().spawn(Transform::from_xyz(10.0, 2.0, 0.0)).id();
+        let light = app.world_mut().spawn(Sun).id();
+        let finite_sun = app.world_mut()
+            .spawn(FiniteSun::default().with_radius(100.0).with_origin(camera).with_light(light))
+            .id();
+        for step in 0..3 {
+            app.world_mut().get_mut::<Transform>(camera).unwrap().translation.x += step as f32;
+            app.update();
+            let camera_position = app.world().get::<GlobalTransform>(camera).unwrap().translation();
+            let sun = app.world().get::<GlobalTransform>(finite_sun).unwrap();
+            let expected = camera_position + environment.sun_direction() * 100.0;
+            assert!(sun.translation().abs_diff_eq(expected, 1e-3), "{step}: {}", sun.translation());
+            assert!(sun.back().abs_diff_eq(-environment.sun_direction(), 1e-5));
+        }
+    }
+
+    #[test]
+    fn follows_parented_light() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_latitude_deg(30.0).with_hours_since_noon(-2.0);
+        app.insert_resource(environment);
+        let pivot = app.world_mut().spawn(Transform::from_rotation(Quat::from_rotation_y(1.2))).id();
+        let light = app.world_mut().spawn((Sun, SunTarget::World, ChildOf(pivot))).id();
+        let finite_sun = app.world_mut().spawn(FiniteSun::default().with_radius(10.0).with_light(light)).id();
+        app.update();
+        let sun = app.world().get::<GlobalTransform>(finite_sun).unwrap();
+        assert!(sun.translation().abs_diff_eq(environment.sun_direction() * 10.0, 1e-3), "{}", sun.translation());
+    }
+}
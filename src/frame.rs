@@ -0,0 +1,115 @@
+//! Contains the [`SunReferenceFrame`] resource for projects that aren't Y-up
+use bevy::prelude::*;
+
+
+/// Which world axes are up, north and east, for projects that don't use Bevy's Y-up convention
+/// 
+/// The [`Environment`](crate::Environment) always works out directions with `+Y` up, north
+/// along `-Z` and east along `+X`. This resource maps those directions into your world before they
+/// are written to lights and other outputs, so a Z-up project or one with north along `+Z` can
+/// use the sun without rotating a parent entity.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{RealisticSunDirectionPlugin, SunReferenceFrame};
+/// # let mut app = App::new();
+/// // Z-up, with north along +Y like most terrain and GIS tools
+/// app.add_plugins(RealisticSunDirectionPlugin::default()
+///     .with_reference_frame(SunReferenceFrame::Z_UP));
+/// ```
+/// 
+/// Used for the rotations of [`Sun`](crate::Sun), [`Moon`](crate::Moon) and
+/// [`Tracks`](crate::Tracks) lights, [`FacesSun`](crate::FacesSun) props, the
+/// [`SunState`](crate::SunState) direction, [`SunlightProbe`](crate::SunlightProbe) rays and the
+/// lighting features that read a sun's elevation from its transform. Methods on the
+/// [`Environment`](crate::Environment), [`SunAlignment`](crate::SunAlignment) directions, the
+/// [`bake`](crate::bake) module and the debug gizmos stay in the Y-up frame, use
+/// [`to_world`](SunReferenceFrame::to_world) to convert their directions.
+/// 
+/// The axes can describe a mirrored (left-handed) frame, like north along `+Z` with east along
+/// `+X`, in which case the sun crosses the sky the other way around when seen from Bevy's
+/// right-handed coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunReferenceFrame
+{
+    /// World direction pointing straight up
+    pub up: Vec3,
+    /// World direction pointing north along the horizon
+    pub north: Vec3,
+    /// World direction pointing east along the horizon
+    pub east: Vec3,
+}
+
+impl Default for SunReferenceFrame {
+    fn default() -> Self {
+        Self::Y_UP
+    }
+}
+
+impl SunReferenceFrame
+{
+    /// Bevy's convention and the default: `+Y` up, north along `-Z` and east along `+X`
+    pub const Y_UP: Self = Self::new(Vec3::Y, Vec3::NEG_Z, Vec3::X);
+
+    /// `+Z` up, north along `+Y` and east along `+X`, used by Blender and many terrain tools
+    pub const Z_UP: Self = Self::new(Vec3::Z, Vec3::Y, Vec3::X);
+
+    /// `+Y` up, north along `+Z` and east along `+X`, the left-handed convention used by engines
+    /// like Unity
+    pub const Y_UP_LEFT_HANDED: Self = Self::new(Vec3::Y, Vec3::Z, Vec3::X);
+
+    /// Creates a new `SunReferenceFrame` from world directions. They should be unit vectors at
+    /// right angles to each other
+    pub const fn new(up: Vec3, north: Vec3, east: Vec3) -> Self {
+        Self { up, north, east }
+    }
+
+    /// Converts a direction from the Y-up frame the [`Environment`](crate::Environment) uses
+    /// into a world direction
+    pub fn to_world(&self, direction: Vec3) -> Vec3 {
+        self.east * direction.x + self.up * direction.y - self.north * direction.z
+    }
+
+    /// Converts a world direction into the Y-up frame the [`Environment`](crate::Environment)
+    /// uses, the opposite of [`to_world`](SunReferenceFrame::to_world)
+    pub fn from_world(&self, direction: Vec3) -> Vec3 {
+        Vec3::new(direction.dot(self.east), direction.dot(self.up), -direction.dot(self.north))
+    }
+
+    /// Rotation of a light shining along `light_direction` in the Y-up frame, with its local up
+    /// kept towards the world's up
+    pub fn light_rotation(&self, light_direction: Vec3) -> Quat {
+        Transform::IDENTITY.looking_to(self.to_world(light_direction), self.up).rotation
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun, SunState};
+
+    #[test]
+    fn z_up_suns() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RealisticSunDirectionPlugin::default().with_reference_frame(SunReferenceFrame::Z_UP),
+        ));
+        // morning on the equinox, so the sun is in the east
+        let environment = Environment::default().with_hours_since_noon(-3.0);
+        app.insert_resource(environment);
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        let to_sun = -*app.world().get::<Transform>(sun).unwrap().forward();
+        assert!(to_sun.z > 0.5 && to_sun.x > 0.5 && to_sun.y.abs() < 1e-5, "{to_sun}");
+        let state = app.world().resource::<SunState>();
+        assert!(state.direction.abs_diff_eq(to_sun, 1e-5));
+        let frame = SunReferenceFrame::Y_UP_LEFT_HANDED;
+        let direction = environment.sun_direction();
+        assert!(frame.from_world(frame.to_world(direction)).abs_diff_eq(direction, 1e-6));
+    }
+}
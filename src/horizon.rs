@@ -0,0 +1,85 @@
+//! Contains the [`HorizonClamp`] resource that stops suns from shining up through the floor
+use bevy::prelude::*;
+use crate::conversion::*;
+
+
+/// Keeps every [`Sun`](crate::Sun) from going further below the horizon than a minimum elevation
+/// 
+/// While this resource exists, suns below [`min_elevation`](HorizonClamp::min_elevation) are
+/// held at that elevation while still moving around the horizon with the real sun. Useful when
+/// the directional light should keep casting usable shadows at night, or stand in for moonlight,
+/// instead of shining up from below the floor.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::HorizonClamp;
+/// # let mut app = App::new();
+/// // Never let the sun light go lower than 5 degrees below the horizon
+/// app.insert_resource(HorizonClamp::default().with_min_elevation_deg(-5.0));
+/// ```
+/// 
+/// Only the light direction is clamped. Queries on the [`Environment`](crate::Environment) and
+/// solar events still use the real sun.
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct HorizonClamp
+{
+    /// Lowest elevation of the sun in radians, negative for below the horizon
+    pub min_elevation: f32,
+}
+
+impl Default for HorizonClamp {
+    /// 5 degrees below the horizon
+    fn default() -> Self {
+        Self { min_elevation: -5.0 * DEG_TO_RAD }
+    }
+}
+
+impl HorizonClamp
+{
+    /// Sets the lowest elevation of the sun in radians
+    pub const fn with_min_elevation(mut self, min_elevation: f32) -> Self {
+        self.min_elevation = min_elevation;
+        self
+    }
+
+    /// Sets the lowest elevation of the sun in degrees
+    pub const fn with_min_elevation_deg(self, min_elevation: f32) -> Self {
+        self.with_min_elevation(min_elevation * DEG_TO_RAD)
+    }
+
+    /// Raises a direction the sun light travels in to at least the minimum elevation
+    pub(crate) fn clamp_light_direction(&self, light_direction: Vec3) -> Vec3 {
+        let (azimuth, elevation) = direction_to_azimuth_elevation(-light_direction);
+        if elevation >= self.min_elevation {
+            return light_direction;
+        }
+        -azimuth_elevation_to_direction(azimuth, self.min_elevation)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn keeps_azimuth() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(HorizonClamp::default());
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(45.0)
+            .with_hours_since_noon(8.0);
+        app.insert_resource(environment);
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        let forward = app.world().get::<Transform>(sun).unwrap().forward();
+        let (azimuth, elevation) = direction_to_azimuth_elevation(-*forward);
+        assert!((elevation + 5.0 * DEG_TO_RAD).abs() < 1e-4, "{}", elevation * RAD_TO_DEG);
+        assert!((azimuth - environment.solar_azimuth()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,130 @@
+//! Contains the [`IlluminanceCurve`] component that sets how bright the sun is based on how high
+//! it is
+use bevy::light::light_consts::lux;
+use bevy::prelude::*;
+use crate::SunReferenceFrame;
+use crate::appearance::{sample_curve, to_sorted_radians};
+use crate::conversion::*;
+use crate::irradiance::air_mass;
+
+
+/// Sets a sun light's illuminance from keyframes of elevation to lux
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{IlluminanceCurve, Sun};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     // Fades through the atmosphere like the real sun
+///     IlluminanceCurve::default(),
+/// ));
+/// // Or a custom curve, with keys in degrees
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     IlluminanceCurve::new(vec![(-5.0, 0.0), (0.0, 500.0), (15.0, 40_000.0), (45.0, 80_000.0)]),
+/// ));
+/// ```
+/// 
+/// The default is [`IlluminanceCurve::physical`]. Elevation is read from the light's own
+/// [`Transform`] every frame, like [`SunAppearance`](crate::SunAppearance).
+/// 
+/// **Note:** this overwrites the light's illuminance, including the illuminance set by a
+/// [`SunAppearance`](crate::SunAppearance) on the same light, whose color curve is still used
+#[derive(Clone, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct IlluminanceCurve
+{
+    /// Illuminance in lux as an [elevation curve](crate::SunAppearance#elevation-curves)
+    pub keys: Vec<(f32, f32)>,
+}
+
+impl Default for IlluminanceCurve {
+    fn default() -> Self {
+        Self::physical()
+    }
+}
+
+impl IlluminanceCurve
+{
+    /// Creates a curve from `(elevation in degrees, lux)` keys
+    pub fn new(keys: Vec<(f32, f32)>) -> Self {
+        Self { keys: to_sorted_radians(keys) }
+    }
+
+    /// Sunlight passing through an Earth-like atmosphere, reaching around
+    /// [`DIRECT_SUNLIGHT`](lux::DIRECT_SUNLIGHT) overhead
+    /// 
+    /// Light is lost to atmospheric extinction along the path through the air, which gets much
+    /// longer near the horizon, so the sun fades to under a hundred lux as it sets and off below
+    /// the horizon.
+    pub fn physical() -> Self {
+        const ELEVATIONS: [f32; 14] = [-1.0, 0.0, 1.0, 2.0, 3.0, 5.0, 7.5, 10.0, 15.0, 20.0, 30.0, 45.0, 60.0, 90.0];
+        let keys = ELEVATIONS.iter()
+            .map(|&elevation| (elevation * DEG_TO_RAD, physical_illuminance(elevation)))
+            .collect();
+        Self { keys }
+    }
+
+    /// Multiplies every key by `scale`, for brighter or dimmer stars
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        for key in &mut self.keys {
+            key.1 *= scale;
+        }
+        self
+    }
+
+    /// Illuminance at `elevation` in radians, or `None` if the curve is empty
+    pub fn illuminance_at(&self, elevation: f32) -> Option<f32> {
+        sample_curve(&self.keys, elevation, |from, to, t| from.lerp(*to, t))
+    }
+}
+
+/// Illuminance in lux of sunlight at `elevation` degrees, after extinction in the atmosphere
+fn physical_illuminance(elevation: f32) -> f32 {
+    /// Optical depth of the atmosphere straight up, for light in the visible range
+    const EXTINCTION: f32 = 0.21;
+    if elevation < 0.0 {
+        return 0.0;
+    }
+    lux::RAW_SUNLIGHT * (-EXTINCTION * air_mass(elevation * DEG_TO_RAD)).exp()
+}
+
+/// Applies each [`IlluminanceCurve`] to its light, based on the direction the light is facing
+pub(crate) fn apply_illuminance_curves(
+    mut lights: Query<(&IlluminanceCurve, &Transform, &mut DirectionalLight)>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    for (curve, transform, mut light) in &mut lights {
+        let (_, elevation) = direction_to_azimuth_elevation(reference_frame.from_world(-*transform.forward()));
+        if let Some(illuminance) = curve.illuminance_at(elevation) && light.illuminance != illuminance {
+            light.illuminance = illuminance;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_curve() {
+        let curve = IlluminanceCurve::physical();
+        let overhead = curve.illuminance_at(90.0 * DEG_TO_RAD).unwrap();
+        assert!((overhead - lux::DIRECT_SUNLIGHT).abs() < 10_000.0, "{}", overhead);
+        let horizon = curve.illuminance_at(0.0).unwrap();
+        assert!(horizon > 0.0 && horizon < 100.0, "{}", horizon);
+        assert_eq!(curve.illuminance_at(-0.1), Some(0.0));
+        // always brighter the higher the sun is
+        assert!(curve.keys.windows(2).all(|keys| keys[0].1 <= keys[1].1));
+    }
+}
@@ -0,0 +1,115 @@
+//! Contains the [`Irradiance`] model for how much solar power reaches a surface
+use bevy::prelude::*;
+use crate::conversion::*;
+
+
+/// Simple model of how much power from the sun reaches the ground, in watts per square meter
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{Environment, Irradiance};
+/// // A solar panel tilted 30 degrees towards the south, on a hazy planet
+/// fn solar_panel_watts(environment: Res<Environment>) -> f32 {
+///     let normal = Quat::from_rotation_x(30f32.to_radians()) * Vec3::Y;
+///     let irradiance = Irradiance::EARTH.with_transmittance(0.6);
+///     irradiance.on_surface(environment.sun_direction(), normal) * 1.6 * 0.2
+/// }
+/// ```
+/// 
+/// Sunlight is dimmed on its way through the atmosphere by the air mass it passes through, which
+/// gets much thicker near the horizon, and spread out over a surface by the cosine of the angle it
+/// hits it at. Only direct sunlight is counted, so shade and cloud cover are up to you. Use
+/// [`Environment::irradiance_on`](crate::Environment::irradiance_on) for Earth's values.
+#[derive(Clone, Copy, Debug)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct Irradiance
+{
+    /// Power of sunlight above the atmosphere, in watts per square meter
+    pub solar_constant: f32,
+
+    /// Fraction of sunlight that makes it straight down through the atmosphere, from `0.0` to
+    /// `1.0`. Lower for hazier or thicker atmospheres
+    pub transmittance: f32,
+}
+
+impl Default for Irradiance {
+    fn default() -> Self {
+        Self::EARTH
+    }
+}
+
+impl Irradiance
+{
+    /// Sunlight on Earth on a clear day, reaching a little under 1000 W/m² with the sun overhead
+    pub const EARTH: Self = Self {
+        solar_constant: 1361.0,
+        transmittance: 0.7,
+    };
+
+    /// Sets the power of sunlight above the atmosphere in watts per square meter
+    pub const fn with_solar_constant(mut self, solar_constant: f32) -> Self {
+        self.solar_constant = solar_constant;
+        self
+    }
+
+    /// Sets the fraction of sunlight that makes it straight down through the atmosphere
+    pub const fn with_transmittance(mut self, transmittance: f32) -> Self {
+        self.transmittance = transmittance;
+        self
+    }
+
+    /// Power in watts per square meter on a surface facing the sun, with the sun `elevation`
+    /// radians above the horizon. `0.0` when the sun is below the horizon
+    pub fn direct_normal(&self, elevation: f32) -> f32 {
+        if elevation <= 0.0 {
+            return 0.0;
+        }
+        // Meinel's model, fitted to clear sky measurements
+        self.solar_constant * self.transmittance.powf(air_mass(elevation).powf(0.678))
+    }
+
+    /// Power in watts per square meter on a surface facing along `normal`, with the sun in
+    /// `sun_direction` (pointing *towards* the sun). `0.0` for surfaces facing away from the sun
+    pub fn on_surface(&self, sun_direction: Vec3, normal: Vec3) -> f32 {
+        let (Some(sun_direction), Some(normal)) = (sun_direction.try_normalize(), normal.try_normalize()) else {
+            return 0.0;
+        };
+        let facing = normal.dot(sun_direction).max(0.0);
+        if facing == 0.0 {
+            return 0.0;
+        }
+        let (_, elevation) = direction_to_azimuth_elevation(sun_direction);
+        self.direct_normal(elevation) * facing
+    }
+}
+
+/// Relative amount of air sunlight passes through at `elevation` radians, `1.0` straight up. Uses
+/// the Kasten-Young formula, which stays finite at the horizon
+pub(crate) fn air_mass(elevation: f32) -> f32 {
+    let elevation_deg = elevation * RAD_TO_DEG;
+    1.0 / (elevation.sin().max(0.0) + 0.50572 * (elevation_deg + 6.07995).powf(-1.6364))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Environment;
+
+    #[test]
+    fn panels_facing_the_sun() {
+        let overhead = Environment::default();
+        let flat = overhead.irradiance_on(Vec3::Y);
+        assert!((flat - 950.0).abs() < 20.0, "{}", flat);
+        assert_eq!(overhead.irradiance_on(Vec3::NEG_Y), 0.0);
+        // tilting the panel away from the sun loses power with the cosine
+        let tilted = overhead.irradiance_on(Vec3::new(1.0, 1.0, 0.0));
+        assert!((tilted - flat * std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-2);
+        // low sun loses most of its power to the atmosphere
+        let low = Irradiance::EARTH.direct_normal(5.0 * DEG_TO_RAD);
+        assert!(low < flat / 2.0, "{}", low);
+        assert_eq!(overhead.with_hours_since_noon(12.0).irradiance_on(Vec3::Y), 0.0);
+    }
+}
@@ -0,0 +1,172 @@
+//! Contains the [`LatitudeSource`] resource, which moves the [`Environment`]'s latitude with the
+//! player
+use std::f32::consts::FRAC_PI_2;
+use bevy::prelude::*;
+use crate::{Environment, SunReferenceFrame};
+use crate::conversion::*;
+
+
+/// Works out the [`Environment`]'s latitude, and optionally longitude, from where an entity
+/// marked with [`TracksLatitude`] is in the world
+///
+/// The world is treated as a map of a planet with a radius of
+/// [`planet_radius`](LatitudeSource::planet_radius) world units, with
+/// [`origin`](LatitudeSource::origin) at [`origin_latitude`](LatitudeSource::origin_latitude) and
+/// [`origin_longitude`](LatitudeSource::origin_longitude). Walking north along the
+/// [`SunReferenceFrame`] raises the latitude, so the sun arcs lower as the player travels towards
+/// the pole.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{LatitudeSource, TracksLatitude};
+/// # let mut app = App::new();
+/// // A map starting at 40 degrees north, on a planet where one degree is 1000 world units
+/// app.insert_resource(LatitudeSource::default()
+///     .with_origin_deg(40.0, 0.0)
+///     .with_planet_radius(1000.0 * 360.0 / std::f32::consts::TAU));
+/// fn spawn_player(mut commands: Commands) {
+///     commands.spawn((Transform::default(), TracksLatitude));
+/// }
+/// ```
+///
+/// Only used while exactly one entity has [`TracksLatitude`]. The latitude is clamped to the
+/// poles, and the longitude wraps around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct LatitudeSource
+{
+    /// World position at the origin latitude and longitude
+    pub origin: Vec3,
+
+    /// Latitude in radians at the [`origin`](LatitudeSource::origin)
+    pub origin_latitude: f32,
+
+    /// Longitude in radians at the [`origin`](LatitudeSource::origin)
+    pub origin_longitude: f32,
+
+    /// Radius of the planet in world units, so moving `planet_radius` units north changes the
+    /// latitude by one radian
+    pub planet_radius: f32,
+
+    /// Whether to set the [`longitude`](Environment::longitude) from moving east and west as well
+    pub longitude: bool,
+}
+
+impl Default for LatitudeSource {
+    /// Starts at the equator and prime meridian on an Earth sized planet, one world unit per
+    /// meter, with the longitude left alone
+    fn default() -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            origin_latitude: 0.0,
+            origin_longitude: 0.0,
+            planet_radius: Self::EARTH_RADIUS,
+            longitude: false,
+        }
+    }
+}
+
+impl LatitudeSource
+{
+    /// Mean radius of the Earth in meters
+    pub const EARTH_RADIUS: f32 = 6_371_000.0;
+
+    /// Sets the world position at the origin
+    pub const fn with_origin(mut self, origin: Vec3) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets the latitude and longitude at the origin in degrees
+    pub const fn with_origin_deg(mut self, latitude: f32, longitude: f32) -> Self {
+        self.origin_latitude = latitude * DEG_TO_RAD;
+        self.origin_longitude = longitude * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the radius of the planet in world units
+    pub const fn with_planet_radius(mut self, planet_radius: f32) -> Self {
+        self.planet_radius = planet_radius;
+        self
+    }
+
+    /// Sets whether the longitude follows the tracked entity too
+    pub const fn with_longitude(mut self, longitude: bool) -> Self {
+        self.longitude = longitude;
+        self
+    }
+
+    /// Latitude and longitude in radians at a world `position`, as `(latitude, longitude)`
+    ///
+    /// Distances are measured along the `north` and `east` of the `frame`, and east-west
+    /// distances cover more longitude closer to the poles
+    pub fn latitude_longitude(&self, position: Vec3, frame: &SunReferenceFrame) -> (f32, f32) {
+        let offset = position - self.origin;
+        let radius = self.planet_radius.max(f32::EPSILON);
+        let latitude = (self.origin_latitude + offset.dot(frame.north) / radius).clamp(-FRAC_PI_2, FRAC_PI_2);
+        let parallel_radius = (radius * latitude.cos()).max(f32::EPSILON);
+        let longitude = wrap_angle(self.origin_longitude + offset.dot(frame.east) / parallel_radius);
+        (latitude, longitude)
+    }
+}
+
+/// Marks the entity whose world position sets the latitude for a [`LatitudeSource`], usually the
+/// player or camera
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct TracksLatitude;
+
+/// Sets the [`Environment`]'s latitude and longitude from the entity marked with
+/// [`TracksLatitude`]
+pub(crate) fn apply_latitude_source(
+    source: Res<LatitudeSource>,
+    tracked: Query<Ref<GlobalTransform>, With<TracksLatitude>>,
+    reference_frame: Res<SunReferenceFrame>,
+    mut environment: ResMut<Environment>,
+){
+    let Ok(transform) = tracked.single() else {
+        return;
+    };
+    if !(transform.is_changed() || source.is_changed() || reference_frame.is_changed()) {
+        return;
+    }
+    let (latitude, longitude) = source.latitude_longitude(transform.translation(), &reference_frame);
+    if environment.latitude != latitude {
+        environment.latitude = latitude;
+    }
+    if source.longitude && environment.longitude != longitude {
+        environment.longitude = longitude;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn walking_north_raises_latitude() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(LatitudeSource::default()
+            .with_origin_deg(40.0, 0.0)
+            .with_planet_radius(100.0)
+            .with_longitude(true));
+        let player = app.world_mut().spawn((Transform::default(), TracksLatitude)).id();
+        app.update();
+        app.update();
+        assert!((app.world().resource::<Environment>().latitude * RAD_TO_DEG - 40.0).abs() < 1e-4);
+        // north is -Z, and 10 units is a tenth of a radian
+        app.world_mut().get_mut::<Transform>(player).unwrap().translation = Vec3::new(10.0, 0.0, -10.0);
+        app.update();
+        app.update();
+        let environment = *app.world().resource::<Environment>();
+        assert!((environment.latitude - (40.0 * DEG_TO_RAD + 0.1)).abs() < 1e-5, "{}", environment.latitude);
+        assert!((environment.longitude - 0.1 / environment.latitude.cos()).abs() < 1e-5, "{}", environment.longitude);
+    }
+}
@@ -0,0 +1,243 @@
+//! Contains the [`EnvironmentOverride`] component that lets levels carry their own environment
+use bevy::prelude::*;
+use crate::Environment;
+use crate::conversion::*;
+
+
+/// Overrides values in the [`Environment`] resource while the entity it's attached to exists
+/// 
+/// Put one in a level's scene and the level's latitude, time or axial tilt are applied as soon as
+/// the scene is spawned, so streamed levels carry their lighting context with them. Only the values
+/// that are set are overridden, the rest are left alone.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::EnvironmentOverride;
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // This level is in the far north, at dawn
+/// commands.spawn(
+///     EnvironmentOverride::default()
+///         .with_latitude_deg(70.0)
+///         .with_hours_since_noon(-6.0)
+/// );
+/// ```
+/// 
+/// When several overrides exist at once, the one added most recently wins. When an override is
+/// removed or despawned, the values it overrode are put back to what they were before it was added
+/// (unless [`restore_on_remove`](EnvironmentOverride::restore_on_remove) is `false`). If a newer
+/// override is still active, it will restore to those older values instead when it's removed.
+/// 
+/// Values are in radians, the same as [`Environment`]
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct EnvironmentOverride
+{
+    /// Overrides [`Environment::axial_tilt`]
+    pub axial_tilt: Option<f32>,
+    /// Overrides [`Environment::latitude`]
+    pub latitude: Option<f32>,
+    /// Overrides [`Environment::time_of_day`]
+    pub time_of_day: Option<f32>,
+    /// Overrides [`Environment::time_of_year`]
+    pub time_of_year: Option<f32>,
+    /// Whether to put the overridden values back when this override is removed. Defaults to `true`
+    pub restore_on_remove: bool,
+}
+
+impl Default for EnvironmentOverride {
+    fn default() -> Self {
+        Self {
+            axial_tilt: None,
+            latitude: None,
+            time_of_day: None,
+            time_of_year: None,
+            restore_on_remove: true,
+        }
+    }
+}
+
+impl EnvironmentOverride
+{
+    /// Overrides the axial tilt in radians
+    pub const fn with_axial_tilt(mut self, axial_tilt: f32) -> Self {
+        self.axial_tilt = Some(axial_tilt);
+        self
+    }
+
+    /// Overrides the latitude in radians
+    pub const fn with_latitude(mut self, latitude: f32) -> Self {
+        self.latitude = Some(latitude);
+        self
+    }
+
+    /// Overrides the latitude in degrees
+    pub const fn with_latitude_deg(self, latitude: f32) -> Self {
+        self.with_latitude(latitude * DEG_TO_RAD)
+    }
+
+    /// Overrides the time of day in radians
+    pub const fn with_time_of_day(mut self, time_of_day: f32) -> Self {
+        self.time_of_day = Some(time_of_day);
+        self
+    }
+
+    /// Overrides the time of day in hours since local solar noon
+    pub const fn with_hours_since_noon(self, time_of_day: f32) -> Self {
+        self.with_time_of_day(time_of_day * HOURS_TO_RAD)
+    }
+
+    /// Overrides the time of year in radians
+    pub const fn with_date(mut self, date: f32) -> Self {
+        self.time_of_year = Some(date);
+        self
+    }
+
+    /// Sets whether the overridden values are put back when this override is removed
+    pub const fn with_restore_on_remove(mut self, restore_on_remove: bool) -> Self {
+        self.restore_on_remove = restore_on_remove;
+        self
+    }
+
+    /// Writes the overridden values into `environment`
+    fn apply(&self, environment: &mut Environment) {
+        if let Some(axial_tilt) = self.axial_tilt { environment.axial_tilt = axial_tilt; }
+        if let Some(latitude) = self.latitude { environment.latitude = latitude; }
+        if let Some(time_of_day) = self.time_of_day { environment.time_of_day = time_of_day; }
+        if let Some(time_of_year) = self.time_of_year { environment.time_of_year = time_of_year; }
+    }
+
+    /// Whether this overrides each of the values in the order [`copy_value`] numbers them
+    fn overridden(&self) -> [bool; 4] {
+        [self.axial_tilt.is_some(), self.latitude.is_some(), self.time_of_day.is_some(), self.time_of_year.is_some()]
+    }
+}
+
+/// Copies one of the values an [`EnvironmentOverride`] can override from `from` into `to`, by its
+/// position in [`EnvironmentOverride::overridden`]
+fn copy_value(value: usize, to: &mut Environment, from: &Environment) {
+    match value {
+        0 => to.axial_tilt = from.axial_tilt,
+        1 => to.latitude = from.latitude,
+        2 => to.time_of_day = from.time_of_day,
+        _ => to.time_of_year = from.time_of_year,
+    }
+}
+
+/// Active [`EnvironmentOverride`]s in the order they were added, with the [`Environment`] from
+/// just before each was applied
+#[derive(Default)]
+#[derive(Resource)]
+pub(crate) struct EnvironmentOverrideStack(Vec<(Entity, Environment)>);
+
+/// Applies an [`EnvironmentOverride`] as soon as it's added, if there is an [`Environment`] to
+/// apply it to
+pub(crate) fn on_override_added(
+    add: On<Add, EnvironmentOverride>,
+    overrides: Query<&EnvironmentOverride>,
+    mut stack: ResMut<EnvironmentOverrideStack>,
+    environment: Option<ResMut<Environment>>,
+){
+    let (Ok(environment_override), Some(mut environment)) = (overrides.get(add.entity), environment) else {
+        return;
+    };
+    stack.0.push((add.entity, *environment));
+    environment_override.apply(&mut environment);
+}
+
+/// Restores the values an [`EnvironmentOverride`] overrode when it's removed
+pub(crate) fn on_override_removed(
+    remove: On<Remove, EnvironmentOverride>,
+    overrides: Query<&EnvironmentOverride>,
+    mut stack: ResMut<EnvironmentOverrideStack>,
+    mut environment: Option<ResMut<Environment>>,
+){
+    let Some(index) = stack.0.iter().position(|(entity, _)| *entity == remove.entity) else {
+        return;
+    };
+    let (_, saved) = stack.0.remove(index);
+    let Ok(environment_override) = overrides.get(remove.entity) else {
+        return;
+    };
+    if !environment_override.restore_on_remove {
+        return;
+    }
+    for (value, overridden) in environment_override.overridden().into_iter().enumerate() {
+        if !overridden {
+            continue;
+        }
+        // a newer override of the same value is still active, so it should restore to what this
+        // one saved instead
+        let newer = stack.0[index..].iter_mut()
+            .find(|(entity, _)| overrides.get(*entity).is_ok_and(|newer| newer.overridden()[value]));
+        match newer {
+            Some((_, newer_saved)) => copy_value(value, newer_saved, &saved),
+            None => if let Some(environment) = environment.as_mut() {
+                copy_value(value, environment, &saved);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn stacked_overrides_restore() {
+        let mut app = App::new();
+        app.add_plugins(RealisticSunDirectionPlugin::default());
+        app.insert_resource(Environment::default().with_latitude(0.1).with_time_of_day(1.0));
+        let first = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.2)).id();
+        let second = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.3)).id();
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.3);
+        // removing the older override keeps the newer one active
+        app.world_mut().despawn(first);
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.3);
+        // removing the newer one goes all the way back to before either was added
+        app.world_mut().despawn(second);
+        let environment = app.world().resource::<Environment>();
+        assert_eq!(environment.latitude, 0.1);
+        assert_eq!(environment.time_of_day, 1.0);
+    }
+
+    #[test]
+    fn overrides_without_environment() {
+        let mut app = App::new();
+        app.add_plugins(RealisticSunDirectionPlugin::default().without_default_environment());
+        // nothing to apply to yet, so the override is skipped instead of failing
+        let early = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.2)).id();
+        app.world_mut().despawn(early);
+        app.insert_resource(Environment::default().with_latitude(0.1));
+        let late = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.3)).id();
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.3);
+        app.world_mut().despawn(late);
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.1);
+    }
+
+    #[test]
+    fn removing_middle_override_restores_uncovered_values() {
+        let mut app = App::new();
+        app.add_plugins(RealisticSunDirectionPlugin::default());
+        app.insert_resource(Environment::default().with_latitude(0.1).with_time_of_day(1.0));
+        let first = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.2)).id();
+        let middle = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.25).with_time_of_day(2.0)).id();
+        let last = app.world_mut().spawn(EnvironmentOverride::default().with_latitude(0.3)).id();
+        // the time of day isn't overridden by anything newer, so it comes back straight away
+        app.world_mut().despawn(middle);
+        let environment = app.world().resource::<Environment>();
+        assert_eq!(environment.latitude, 0.3);
+        assert_eq!(environment.time_of_day, 1.0);
+        // the newest override now restores to the first one's latitude
+        app.world_mut().despawn(last);
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.2);
+        app.world_mut().despawn(first);
+        assert_eq!(app.world().resource::<Environment>().latitude, 0.1);
+    }
+}
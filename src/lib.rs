@@ -24,7 +24,7 @@
 //!    # use bevy::app::App;
 //!    # use kj_bevy_realistic_sun::RealisticSunDirectionPlugin;
 //!    # let mut app = App::new();
-//!    app.add_plugins(RealisticSunDirectionPlugin);
+//!    app.add_plugins(RealisticSunDirectionPlugin::default());
 //!    ```
 //! 
 //! 2. add an [`Environment`] resource to the world
@@ -59,11 +59,153 @@
 //! 
 //! Now whenever you update the variables in [`Environment`] from any schedule, the light with the
 //! [`Sun`] component attached will orient itself accordingly on the next frame.
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use bevy::transform::TransformSystems;
 
+mod alignment;
+mod ambient;
+mod appearance;
+#[cfg(feature = "asset")]
+mod asset;
+mod atmosphere;
+pub mod bake;
+mod binary;
+mod blend;
+mod body;
+mod calendar;
+mod celestial;
+mod changes;
+mod cinematic;
+mod clock;
+mod command;
+mod compass;
+mod conditions;
 pub mod conversion;
+#[cfg(feature = "debug_controls")]
+mod debug_controls;
+#[cfg(feature = "debug_gizmos")]
+mod debug_gizmos;
+mod eclipse;
 mod environment;
-pub use environment::Environment;
+mod environments;
+mod epoch;
+mod faces_sun;
+mod finite_sun;
+mod frame;
+mod horizon;
+mod illuminance;
+mod irradiance;
+mod latitude_source;
+mod level;
+mod light_quality;
+pub mod locations;
+mod moon;
+mod motion;
+mod offset;
+mod night_sky;
+mod orbit;
+mod photo_mode;
+mod preset;
+mod real_time;
+mod regions;
+mod scene;
+mod screen;
+#[cfg(feature = "scrubber")]
+mod scrubber;
+mod season;
+#[cfg(feature = "shader_globals")]
+mod shader_globals;
+mod shadows;
+mod sky;
+mod smoothing;
+mod solar_events;
+mod spawn;
+mod state;
+mod sun_path;
+mod sunlight;
+mod target;
+pub mod testing;
+mod tilt;
+#[cfg(feature = "ui")]
+pub mod ui;
+mod update_rate;
+mod weather;
+pub use alignment::{SunAligned, SunAlignment, SunAlignmentLost, SunAlignmentTime};
+pub use ambient::SunAmbientLight;
+pub use appearance::SunAppearance;
+#[cfg(feature = "asset")]
+pub use asset::{
+    ActiveEnvironmentAsset, ActiveSunPathAsset, EnvironmentAsset, EnvironmentAssetLoader,
+    EnvironmentAssetLoaderError, EnvironmentAssetPlugin, SunPathAsset, SunPathAssetLoader,
+    SunPathAssetLoaderError, TrackKey,
+};
+pub use atmosphere::{AtmosphereSync, AtmosphereSyncPlugin};
+pub use binary::{SecondarySun, StarOffset};
+pub use blend::BlendedEnvironment;
+pub use body::{BodyId, CelestialBody, CelestialRegistry, Tracks};
+pub use calendar::{Calendar, CalendarDate, CalendarMonth};
+pub use celestial::CelestialSphere;
+pub use changes::{EnvironmentChangeThreshold, EnvironmentChanged};
+pub use cinematic::{CinematicOverride, CinematicOverrideId, CinematicOverrides};
+pub use clock::{
+    sun_time_paused, sun_time_rewinding, SolarClock, SunTimeIntegrator, SunTimeScale, TimeFlow,
+    WallClockSync,
+};
+pub use command::EnvironmentCommand;
+pub use compass::{CardinalDirection, SunCompass};
+pub use conditions::{sun_above_deg, sun_is_daytime, sun_is_night};
+#[cfg(feature = "debug_controls")]
+pub use debug_controls::{DebugTimeControlPlugin, DebugTimeControls};
+#[cfg(feature = "debug_gizmos")]
+pub use debug_gizmos::{draw_sky_grid, SunDebugGizmos, SunDebugGizmosPlugin};
+pub use eclipse::{SolarEclipse, SolarEclipseEvent};
+pub use environment::{calculate_sun_direction, Environment, SunEnvironment};
+pub use environments::{EnvironmentId, EnvironmentKey, Environments};
+pub use epoch::EpochTime;
+pub use faces_sun::FacesSun;
+pub use finite_sun::FiniteSun;
+pub use frame::SunReferenceFrame;
+pub use horizon::HorizonClamp;
+pub use illuminance::IlluminanceCurve;
+pub use irradiance::Irradiance;
+pub use latitude_source::{LatitudeSource, TracksLatitude};
+pub use level::EnvironmentOverride;
+pub use light_quality::LightQuality;
+pub use locations::Location;
+pub use moon::{LunarOrbit, Moon, MoonPhase};
+pub use motion::SunMotionConstraint;
+pub use night_sky::NightSky;
+pub use offset::SunOffset;
+pub use orbit::{AxialPrecession, EccentricityModel, RotationDirection};
+pub use photo_mode::PhotoModeSunOffset;
+pub use preset::PlanetPreset;
+pub use regions::{Region, SunRegions};
+pub use scene::SceneEnvironment;
+#[cfg(feature = "scene")]
+pub use scene::sun_scene;
+pub use screen::SunScreenPosition;
+#[cfg(feature = "scrubber")]
+pub use scrubber::{sun_scrubber_panel, ScrubberParameter, SunScrubberPlugin, SunScrubberSlider};
+pub use season::{Hemisphere, Season};
+#[cfg(feature = "shader_globals")]
+pub use shader_globals::{SunShaderGlobals, SunShaderGlobalsBuffer, SunShaderGlobalsPlugin};
+pub use shadows::SunShadowPolicy;
+pub use sky::{SkyClearColor, SkyColor};
+pub use smoothing::SunSmoothing;
+pub use solar_events::{
+    PolarDayEndEvent, PolarDayStartEvent, PolarNightEndEvent, PolarNightStartEvent, SolarMidnightEvent,
+    SolarNoonEvent, SunriseEvent, SunsetEvent,
+};
+pub use spawn::{SpawnSunExt, SunConfig};
+pub use state::SunState;
+pub use sun_path::{StylizedSunPath, SunPath, SunPathKey};
+pub use sunlight::{SunlightOcclusion, SunlightProbe, SunlightRay};
+pub use target::{SunDirection, SunTarget};
+pub use tilt::{TiltAnimation, TiltCurve};
+pub use update_rate::SunUpdateRate;
+pub use weather::SkyModifier;
 
 
 /// Adds the systems and resources needed for [`Sun`] components to update their
@@ -74,20 +216,391 @@ pub use environment::Environment;
 /// # use kj_bevy_realistic_sun::RealisticSunDirectionPlugin;
 /// fn main() {
 ///     let app = App::new()
-///         .add_plugins(RealisticSunDirectionPlugin);
+///         .add_plugins(RealisticSunDirectionPlugin::default());
 /// }
 /// ```
 /// 
-/// Adds an [`Environment`] resource with default values, but those values can be overridden by
-/// just adding your own [`Environment`]
-pub struct RealisticSunDirectionPlugin;
+/// Adds an [`Environment`] resource with default values if the app doesn't already have one, so
+/// your own [`Environment`] can be inserted before or after adding the plugin. If a
+/// [`SolarClock`] or [`TimeFlow`] resource is added, it will advance the time in the
+/// [`Environment`] each frame before the sun lights are updated. A [`SunTimeIntegrator`] is
+/// written to the [`Environment`] at the same point whenever you advance it
+/// 
+/// Systems run in the [`Update`] schedule. To run them somewhere else, like [`FixedUpdate`] for
+/// games that drive time there, use [`in_schedule`](RealisticSunDirectionPlugin::in_schedule)
+/// instead. Either way, other systems can order themselves against the sun with [`SunUpdateSet`].
+/// For servers and simulations without any lights, use [`headless`](RealisticSunDirectionPlugin::headless).
+/// Every option is a public field, so the plugin can also be configured all at once:
+/// 
+/// ```no_run
+/// # use bevy::ecs::schedule::ScheduleLabel;
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{RealisticSunDirectionPlugin, SunUpdateRate};
+/// fn main() {
+///     let app = App::new()
+///         .add_plugins(RealisticSunDirectionPlugin {
+///             schedule: FixedUpdate.intern(),
+///             update_rate: Some(SunUpdateRate::EveryNFrames(4)),
+///             ..default()
+///         });
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RealisticSunDirectionPlugin
+{
+    /// Schedule the sun systems run in, [`Update`] by default
+    pub schedule: InternedScheduleLabel,
+
+    /// Whether to add a default [`Environment`] if the app doesn't have one yet, `true` by
+    /// default. When `false` the sun systems wait until your own [`Environment`] is inserted
+    pub auto_insert_environment: bool,
+
+    /// Whether to normalize the [`Environment`] every frame after time advances, see
+    /// [`with_normalized_environment`](RealisticSunDirectionPlugin::with_normalized_environment)
+    pub normalize_environment: bool,
+
+    /// Whether to leave lights alone and only publish the [`SunState`], see
+    /// [`with_headless`](RealisticSunDirectionPlugin::with_headless)
+    pub headless: bool,
+
+    /// [`SunUpdateRate`] to start with if the app doesn't have one yet, `None` to update the suns
+    /// every frame until one is inserted
+    pub update_rate: Option<SunUpdateRate>,
+
+    /// [`SunReferenceFrame`] to start with if the app doesn't have one yet, Y-up by default
+    pub reference_frame: SunReferenceFrame,
+}
+
+impl Default for RealisticSunDirectionPlugin {
+    fn default() -> Self {
+        Self {
+            schedule: Update.intern(),
+            auto_insert_environment: true,
+            normalize_environment: false,
+            headless: false,
+            update_rate: None,
+            reference_frame: SunReferenceFrame::Y_UP,
+        }
+    }
+}
+
+impl RealisticSunDirectionPlugin
+{
+    /// Creates a version of the plugin that runs its systems in `schedule` instead of [`Update`]
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::RealisticSunDirectionPlugin;
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .add_plugins(RealisticSunDirectionPlugin::in_schedule(FixedUpdate));
+    /// }
+    /// ```
+    pub fn in_schedule(schedule: impl ScheduleLabel) -> Self {
+        Self::default().with_schedule(schedule)
+    }
+
+    /// Creates a version of the plugin that only publishes the [`SunState`] resource, without
+    /// touching any lights
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::RealisticSunDirectionPlugin;
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::headless()));
+    /// }
+    /// ```
+    /// 
+    /// Same as `RealisticSunDirectionPlugin::default().with_headless()`, see
+    /// [`with_headless`](RealisticSunDirectionPlugin::with_headless)
+    pub fn headless() -> Self {
+        Self::default().with_headless()
+    }
+
+    /// Runs the systems in `schedule` instead of [`Update`]
+    pub fn with_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
+
+    /// Doesn't add a default [`Environment`], for apps that always insert their own
+    /// 
+    /// The sun systems don't run until an [`Environment`] has been inserted
+    pub const fn without_default_environment(mut self) -> Self {
+        self.auto_insert_environment = false;
+        self
+    }
+
+    /// Starts with `update_rate` if the app doesn't already have a [`SunUpdateRate`]
+    pub const fn with_update_rate(mut self, update_rate: SunUpdateRate) -> Self {
+        self.update_rate = Some(update_rate);
+        self
+    }
+
+    /// Starts with `reference_frame` if the app doesn't already have a [`SunReferenceFrame`], for
+    /// projects that aren't Y-up
+    pub const fn with_reference_frame(mut self, reference_frame: SunReferenceFrame) -> Self {
+        self.reference_frame = reference_frame;
+        self
+    }
+
+    /// Normalizes the [`Environment`] every frame after time advances, wrapping the times into
+    /// the `-PI` to `PI` range and clamping the latitude and axial tilt
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::RealisticSunDirectionPlugin;
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .add_plugins(RealisticSunDirectionPlugin::default()
+    ///             .with_normalized_environment());
+    /// }
+    /// ```
+    /// 
+    /// Lets your own systems add to the time values without wrapping them by hand. See
+    /// [`Environment::normalized`]
+    pub const fn with_normalized_environment(mut self) -> Self {
+        self.normalize_environment = true;
+        self
+    }
+
+    /// Only computes the sun and publishes it in the [`SunState`] resource, without pointing
+    /// [`Sun`], [`Moon`] or any other lights
+    /// 
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use kj_bevy_realistic_sun::RealisticSunDirectionPlugin;
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::in_schedule(FixedUpdate)
+    ///             .with_headless()));
+    /// }
+    /// ```
+    /// 
+    /// For dedicated servers and headless simulations, which can run the same day/night logic as
+    /// the client without spawning any lights. Time still advances, and [`SunriseEvent`]s,
+    /// [`SunAlignment`]s and [`SolarEclipse`] detection keep working, but everything that changes
+    /// a [`Transform`] or light is left out.
+    pub const fn with_headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
+}
+
 impl Plugin for RealisticSunDirectionPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Environment::default());
-        app.add_systems(Update, update_sun_lights);
+        app.register_type::<Environment>()
+            .register_type::<Calendar>()
+            .register_type::<EpochTime>()
+            .register_type::<SunEnvironment>()
+            .register_type::<SceneEnvironment>()
+            .register_type::<Environments>()
+            .register_type::<EnvironmentKey>()
+            .register_type::<Sun>()
+            .register_type::<SunDisabled>()
+            .register_type::<SunOffset>()
+            .register_type::<SunTarget>()
+            .register_type::<SunDirection>()
+            .register_type::<SecondarySun>()
+            .register_type::<Moon>()
+            .register_type::<Tracks>()
+            .register_type::<BodyId>()
+            .register_type::<CelestialSphere>()
+            .register_type::<FacesSun>()
+            .register_type::<FiniteSun>()
+            .register_type::<SunScreenPosition>()
+            .register_type::<NightSky>()
+            .register_type::<MoonPhase>()
+            .register_type::<Season>()
+            .register_type::<Hemisphere>()
+            .register_type::<SolarClock>()
+            .register_type::<TimeFlow>()
+            .register_type::<LatitudeSource>()
+            .register_type::<SunRegions>()
+            .register_type::<Region>()
+            .register_type::<TracksLatitude>()
+            .register_type::<TiltAnimation>()
+            .register_type::<SunTimeIntegrator>()
+            .register_type::<SunTimeScale>()
+            .register_type::<EnvironmentOverride>()
+            .register_type::<CinematicOverrides>()
+            .register_type::<BlendedEnvironment>()
+            .register_type::<PhotoModeSunOffset>()
+            .register_type::<SunAlignment>()
+            .register_type::<SunAppearance>()
+            .register_type::<IlluminanceCurve>()
+            .register_type::<SunShadowPolicy>()
+            .register_type::<SunAmbientLight>()
+            .register_type::<SkyColor>()
+            .register_type::<SolarEclipse>()
+            .register_type::<SkyModifier>()
+            .register_type::<SunSmoothing>()
+            .register_type::<SunMotionConstraint>()
+            .register_type::<SunUpdateRate>()
+            .register_type::<HorizonClamp>()
+            .register_type::<StylizedSunPath>()
+            .register_type::<SunState>()
+            .register_type::<LightQuality>()
+            .register_type::<SunCompass>()
+            .register_type::<CardinalDirection>()
+            .register_type::<SunReferenceFrame>()
+            .register_type::<EnvironmentChangeThreshold>()
+            .register_type::<SunlightProbe>();
+        if self.auto_insert_environment {
+            app.init_resource::<Environment>();
+        }
+        if let Some(update_rate) = self.update_rate && !app.world().contains_resource::<SunUpdateRate>() {
+            app.insert_resource(update_rate);
+        }
+        if !app.world().contains_resource::<SunReferenceFrame>() {
+            app.insert_resource(self.reference_frame);
+        }
+        app.init_resource::<SunState>();
+        app.init_resource::<SunCompass>();
+        app.init_resource::<update_rate::SunUpdateTimer>();
+        app.init_resource::<EnvironmentChangeThreshold>();
+        app.init_resource::<changes::EnvironmentChangeTracker>();
+        app.init_resource::<level::EnvironmentOverrideStack>();
+        app.init_resource::<CinematicOverrides>();
+        app.init_resource::<CelestialRegistry>();
+        app.init_resource::<solar_events::SolarEventTracker>();
+        app.add_observer(level::on_override_added);
+        app.add_observer(level::on_override_removed);
+        app.add_observer(environments::on_environment_key_removed);
+        app.add_observer(regions::on_region_removed);
+        app.add_observer(scene::on_scene_environment_inserted);
+        app.add_observer(command::apply_environment_command);
+        app.configure_sets(self.schedule, (SunUpdateSet::AdvanceTime, SunUpdateSet::UpdateLights)
+            .chain()
+            .run_if(resource_exists::<Environment>));
+        app.add_systems(self.schedule, (
+            clock::advance_solar_clock.run_if(resource_exists::<SolarClock>),
+            clock::advance_time_flow
+                .run_if(resource_exists::<TimeFlow>.and(not(resource_exists::<SolarClock>)))
+                .after(clock::advance_solar_clock),
+            clock::apply_sun_time_integrator.run_if(resource_exists::<SunTimeIntegrator>),
+            tilt::animate_axial_tilt
+                .run_if(resource_exists::<TiltAnimation>)
+                .after(clock::advance_solar_clock)
+                .after(clock::advance_time_flow)
+                .after(clock::apply_sun_time_integrator),
+            blend::drive_blended_environment
+                .run_if(resource_exists::<BlendedEnvironment>)
+                .before(cinematic::advance_cinematic_overrides),
+            cinematic::advance_cinematic_overrides.run_if(resource_exists::<Time>),
+            update_rate::tick_sun_update_rate,
+            environments::apply_environment_keys.run_if(resource_exists::<Environments>),
+            latitude_source::apply_latitude_source.run_if(resource_exists::<LatitudeSource>),
+            regions::apply_sun_regions
+                .run_if(resource_exists::<SunRegions>)
+                .after(clock::advance_solar_clock)
+                .after(clock::advance_time_flow)
+                .after(clock::apply_sun_time_integrator)
+                .after(tilt::animate_axial_tilt),
+        ).in_set(SunUpdateSet::AdvanceTime));
+        if self.normalize_environment {
+            app.add_systems(self.schedule, environment::normalize_environment
+                .after(clock::advance_solar_clock)
+                .after(clock::advance_time_flow)
+                .after(clock::apply_sun_time_integrator)
+                .after(tilt::animate_axial_tilt)
+                .after(latitude_source::apply_latitude_source)
+                .in_set(SunUpdateSet::AdvanceTime));
+        }
+        app.add_systems(self.schedule, (
+            state::update_sun_state,
+            compass::update_sun_compass,
+            eclipse::update_solar_eclipse
+                .run_if(resource_exists::<SolarEclipse>)
+                .after(photo_mode::apply_photo_mode_illuminance),
+            alignment::check_sun_alignments,
+            solar_events::trigger_solar_events,
+            changes::trigger_environment_changed,
+            scene::sync_scene_environments,
+            sunlight::update_sunlight_probes.run_if(resource_exists::<SunlightOcclusion>),
+        ).in_set(SunUpdateSet::UpdateLights));
+        if self.headless {
+            return;
+        }
+        app.add_systems(self.schedule, (
+            update_sun_lights.run_if(update_rate::sun_update_due),
+            update_rate::interpolate_sun_updates
+                .run_if(update_rate::sun_update_interpolating)
+                .after(update_sun_lights),
+            moon::update_moon_lights,
+            body::update_tracking_lights,
+            faces_sun::update_sun_facing,
+            screen::update_sun_screen_positions.after(state::update_sun_state),
+            celestial::update_celestial_spheres,
+            night_sky::update_night_skies,
+            smoothing::smooth_sun_transitions
+                .run_if(resource_exists::<SunSmoothing>)
+                .after(update_sun_lights)
+                .after(update_rate::interpolate_sun_updates),
+            motion::limit_sun_angular_speed
+                .run_if(resource_exists::<Time>)
+                .after(update_sun_lights)
+                .after(update_rate::interpolate_sun_updates)
+                .after(smoothing::smooth_sun_transitions),
+            appearance::apply_sun_appearance
+                .after(update_sun_lights)
+                .after(motion::limit_sun_angular_speed),
+            illuminance::apply_illuminance_curves.after(appearance::apply_sun_appearance),
+            photo_mode::apply_photo_mode_illuminance
+                .after(appearance::apply_sun_appearance)
+                .after(illuminance::apply_illuminance_curves),
+            shadows::apply_sun_shadow_policies
+                .after(update_sun_lights)
+                .after(motion::limit_sun_angular_speed),
+            weather::apply_sky_modifier
+                .after(photo_mode::apply_photo_mode_illuminance)
+                .after(eclipse::update_solar_eclipse),
+            ambient::apply_sun_ambient_light.run_if(resource_exists::<SunAmbientLight>),
+            sky::update_sky_color.run_if(resource_exists::<SkyColor>),
+        ).in_set(SunUpdateSet::UpdateLights));
+        app.add_systems(self.schedule, weather::restore_sky_modified.in_set(SunUpdateSet::AdvanceTime));
+        app.add_systems(PostUpdate, target::compensate_parent_rotation
+            .before(TransformSystems::Propagate)
+            .after(SunUpdateSet::UpdateLights));
+        app.add_systems(PostUpdate, finite_sun::place_finite_suns
+            .after(TransformSystems::Propagate)
+            .after(SunUpdateSet::UpdateLights));
+        #[cfg(feature = "pbr")]
+        app.add_systems(self.schedule, night_sky::apply_night_sky_materials
+            .run_if(resource_exists::<Assets<StandardMaterial>>)
+            .after(night_sky::update_night_skies)
+            .in_set(SunUpdateSet::UpdateLights));
+        #[cfg(feature = "ui")]
+        app.register_type::<ui::SunInfoText>()
+            .add_systems(self.schedule, ui::update_sun_info_text.in_set(SunUpdateSet::UpdateLights));
     }
 }
 
+/// System sets the [`RealisticSunDirectionPlugin`] runs its systems in, for ordering your own
+/// systems against the sun
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunUpdateSet;
+/// # let mut app = App::new();
+/// # fn read_sun_transform() {}
+/// // Runs after the suns have been pointed in the right direction this frame
+/// app.add_systems(Update, read_sun_transform.after(SunUpdateSet::UpdateLights));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(SystemSet)]
+pub enum SunUpdateSet
+{
+    /// Advances the time in the [`Environment`] with a [`SolarClock`], [`TimeFlow`] or
+    /// [`SunTimeIntegrator`], and fades
+    /// [`CinematicOverrides`] in and out
+    AdvanceTime,
+    /// Points every [`Sun`] and [`Moon`] and triggers events based on where the sun is. Runs
+    /// after [`AdvanceTime`](SunUpdateSet::AdvanceTime)
+    UpdateLights,
+}
+
 /// Attach to a
 /// [`DirectionalLight`](https://docs.rs/bevy/0.17.3/bevy/light/struct.DirectionalLight.html)
 /// representing your sun
@@ -110,26 +623,348 @@ impl Plugin for RealisticSunDirectionPlugin {
 ///     Sun,
 /// ));
 /// ```
-#[derive(Clone, Copy, Debug)]
-#[derive(Component)]
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
 #[require(Transform)]
 pub struct Sun;
 
+/// Stops a [`Sun`] from being updated without removing it
+/// 
+/// Suns with this marker keep whatever direction they were last pointing in. Suns that are hidden
+/// with [`Visibility::Hidden`] (or by a hidden parent) are skipped the same way, so suns belonging to
+/// unloaded or hidden parts of a large streamed world don't cost anything or trigger
+/// [`Transform`] change detection every frame. Entities disabled with Bevy's
+/// [`Disabled`](bevy::ecs::entity_disabling::Disabled) component are always skipped.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{Sun, SunDisabled};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // This sun won't move until `SunDisabled` is removed
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     SunDisabled,
+/// ));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct SunDisabled;
+
+/// Suns that [`update_sun_lights`] is allowed to touch
+pub(crate) type UpdatedSunFilter = (With<Sun>, Without<SunDisabled>);
+type SunLightQueryData<'a> = (
+    Entity,
+    Ref<'a, Sun>,
+    &'a mut Transform,
+    Option<Ref<'a, SunEnvironment>>,
+    Option<Ref<'a, SecondarySun>>,
+    Option<Ref<'a, SunOffset>>,
+    Option<(&'a SunTarget, &'a mut SunDirection, &'a mut target::TargetParentRotation)>,
+    Option<&'a ChildOf>,
+    Option<Ref<'a, SunMotionConstraint>>,
+);
+
 /// Runs once per frame, updating every entity with a [`Sun`] component to face in
 /// a calculated direction
 /// 
-/// Direction is calculated based on the values in the [`Environment` resource](Environment)
-fn update_sun_lights(
-    mut lights: Query<&mut Transform, With<Sun>>,
+/// Direction is calculated based on the values in the [`Environment` resource](Environment), or the
+/// sun's own [`SunEnvironment`] if it has one, following the secondary star for suns with a
+/// [`SecondarySun`], nudging suns with a [`SunOffset`] and stepping suns with a
+/// [`SunMotionConstraint`]. Hidden suns and suns marked with [`SunDisabled`] are skipped, and
+/// transforms are only written when the direction actually changes.
+/// Suns with a [`SunTarget`] are written the way it asks, and their [`SunDirection`] is kept up to
+/// date.
+/// 
+/// While none of the resources that affect the sun direction have changed, only new suns and
+/// suns whose own [`Transform`] or [`SunEnvironment`] changed are updated, so a paused clock
+/// costs next to nothing. Showing a sun by changing its parent's [`Visibility`] or removing its
+/// [`SunEnvironment`], [`SecondarySun`] or [`SunOffset`] takes effect the next time the [`Environment`]
+/// changes.
+/// 
+/// The shared rotation is worked out once per frame and written to the suns in parallel, so
+/// scenes with thousands of [`Sun`] props only pay for the writes.
+pub(crate) fn update_sun_lights(
+    mut lights: Query<SunLightQueryData, UpdatedSunFilter>,
+    visibilities: Query<(Option<&Visibility>, Option<&ChildOf>)>,
+    parents: Query<Ref<GlobalTransform>>,
     environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+    reference_frame: Res<SunReferenceFrame>,
+    mut modifiers: SunDirectionModifiers,
 ){
-    let earth_tilt_angle = -environment.time_of_year.cos() / 2.0 * environment.axial_tilt;
-    let earth_tilt_rotation = Quat::from_rotation_x(earth_tilt_angle);
-    let time_of_day_rotation = Quat::from_rotation_z(environment.time_of_day);
-    let latitude_rotation = Quat::from_rotation_x(environment.latitude);
-    let total_rotation = latitude_rotation * time_of_day_rotation * earth_tilt_rotation;
-    let light_direction = total_rotation * Vec3::NEG_Y;
-    for mut transform in &mut lights {
-        transform.look_to(light_direction, Vec3::Y);
+    let globals_changed = environment.is_changed()
+        || cinematic_overrides.is_changed()
+        || reference_frame.is_changed()
+        || modifiers.changed();
+    let modifiers = &modifiers;
+    let light_direction = |environment: &Environment, secondary: bool| {
+        let rotation = if secondary { environment.secondary_sun_rotation() } else { environment.light_rotation() };
+        modifiers.apply(rotation * Vec3::NEG_Y, environment.solar_time_of_day())
+    };
+    let global_environment = cinematic_overrides.resolve(&environment);
+    let global_directions = [false, true].map(|secondary| light_direction(&global_environment, secondary));
+    let looking_to = |light_direction| reference_frame.light_rotation(light_direction);
+    let global_rotations = global_directions.map(looking_to);
+    lights.par_iter_mut().for_each(|query_item| {
+        let (entity, sun, mut transform, sun_environment, secondary_sun, sun_offset, target, parent, motion_constraint) = query_item;
+        let parent_transform = target.as_ref()
+            .filter(|(target, ..)| **target == SunTarget::World)
+            .and(parent)
+            .and_then(|parent| parents.get(parent.parent()).ok());
+        let sun_changed = sun.is_added()
+            || transform.is_changed()
+            || sun_environment.as_ref().is_some_and(|sun_environment| sun_environment.is_changed())
+            || secondary_sun.as_ref().is_some_and(|secondary_sun| secondary_sun.is_added())
+            || sun_offset.as_ref().is_some_and(|sun_offset| sun_offset.is_changed())
+            || motion_constraint.as_ref().is_some_and(|motion_constraint| motion_constraint.is_changed())
+            || parent_transform.as_ref().is_some_and(|parent_transform| parent_transform.is_changed());
+        if !(globals_changed || sun_changed) || is_hidden(entity, &visibilities) {
+            return;
+        }
+        let secondary = secondary_sun.is_some();
+        let constrained_environment = motion_constraint.and_then(|motion_constraint| {
+            let environment = sun_environment.as_ref().map_or(&global_environment, |sun_environment| &sun_environment.0);
+            motion_constraint.constrain_environment(environment)
+        });
+        let rotation = match (sun_environment, sun_offset, constrained_environment) {
+            (None, None, None) => global_rotations[secondary as usize],
+            (sun_environment, sun_offset, constrained_environment) => {
+                let mut light_direction = match (constrained_environment, sun_environment) {
+                    (Some(environment), _) => light_direction(&environment, secondary),
+                    (None, Some(sun_environment)) => light_direction(&sun_environment.0, secondary),
+                    (None, None) => global_directions[secondary as usize],
+                };
+                if let Some(sun_offset) = sun_offset {
+                    light_direction = sun_offset.offset_light_direction(light_direction);
+                }
+                looking_to(light_direction)
+            },
+        };
+        let Some((target, mut direction, mut used_parent_rotation)) = target else {
+            if transform.rotation != rotation {
+                transform.rotation = rotation;
+            }
+            return;
+        };
+        direction.set_if_neq(SunDirection(rotation * Vec3::Z));
+        let parent_rotation = parent_transform.map(|parent_transform| parent_transform.rotation());
+        used_parent_rotation.set_if_neq(target::TargetParentRotation(parent_rotation.unwrap_or(Quat::IDENTITY)));
+        if let Some(rotation) = target.local_rotation(rotation, parent_rotation) && transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    });
+}
+
+/// Optional resources that change the direction of every [`Sun`] on top of the [`Environment`]
+#[derive(SystemParam)]
+pub(crate) struct SunDirectionModifiers<'w, 's>
+{
+    stylized_path: Option<Res<'w, StylizedSunPath>>,
+    photo_mode: Option<Res<'w, PhotoModeSunOffset>>,
+    horizon_clamp: Option<Res<'w, HorizonClamp>>,
+    previously_present: Local<'s, [bool; 3]>,
+}
+
+impl SunDirectionModifiers<'_, '_>
+{
+    /// Whether any of the modifiers were changed, added or removed since last frame
+    fn changed(&mut self) -> bool {
+        let present = [self.stylized_path.is_some(), self.photo_mode.is_some(), self.horizon_clamp.is_some()];
+        let added_or_removed = present != *self.previously_present;
+        *self.previously_present = present;
+        added_or_removed
+            || self.stylized_path.as_ref().is_some_and(|stylized_path| stylized_path.is_changed())
+            || self.photo_mode.as_ref().is_some_and(|photo_mode| photo_mode.is_changed())
+            || self.horizon_clamp.as_ref().is_some_and(|horizon_clamp| horizon_clamp.is_changed())
+    }
+
+    /// Applies every modifier to a direction the sun light travels in
+    fn apply(&self, mut light_direction: Vec3, solar_time_of_day: f32) -> Vec3 {
+        if let Some(stylized_path) = &self.stylized_path {
+            light_direction = stylized_path.blend_light_direction(light_direction, solar_time_of_day);
+        }
+        if let Some(offset) = &self.photo_mode {
+            light_direction = offset.offset_light_direction(light_direction);
+        }
+        if let Some(horizon_clamp) = &self.horizon_clamp {
+            light_direction = horizon_clamp.clamp_light_direction(light_direction);
+        }
+        light_direction
+    }
+}
+
+/// Walks up the hierarchy from `entity` the same way Bevy's visibility propagation does, so hidden
+/// suns are skipped even before [`InheritedVisibility`] has been computed for them
+pub(crate) fn is_hidden(entity: Entity, visibilities: &Query<(Option<&Visibility>, Option<&ChildOf>)>) -> bool {
+    let mut current = entity;
+    while let Ok((visibility, parent)) = visibilities.get(current) {
+        match visibility {
+            Some(Visibility::Hidden) => return true,
+            Some(Visibility::Visible) => return false,
+            _ => {},
+        }
+        let Some(parent) = parent else {
+            return false;
+        };
+        current = parent.parent();
+    }
+    false
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_hidden_and_disabled_suns() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        let visible = app.world_mut().spawn(Sun).id();
+        let hidden = app.world_mut().spawn((Sun, Visibility::Hidden)).id();
+        let disabled = app.world_mut().spawn((Sun, SunDisabled)).id();
+        let hidden_parent = app.world_mut().spawn(Visibility::Hidden).id();
+        let hidden_child = app.world_mut().spawn((Sun, ChildOf(hidden_parent))).id();
+        app.update();
+        assert_ne!(app.world().get::<Transform>(visible).unwrap().rotation, Quat::IDENTITY);
+        assert_eq!(app.world().get::<Transform>(hidden).unwrap().rotation, Quat::IDENTITY);
+        assert_eq!(app.world().get::<Transform>(disabled).unwrap().rotation, Quat::IDENTITY);
+        assert_eq!(app.world().get::<Transform>(hidden_child).unwrap().rotation, Quat::IDENTITY);
+        // an unchanged direction doesn't trigger change detection
+        app.world_mut().clear_trackers();
+        app.update();
+        let mut changed = app.world_mut().query_filtered::<Entity, Changed<Transform>>();
+        assert_eq!(changed.iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn updates_many_suns() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_latitude_deg(20.0).with_hours_since_noon(2.0);
+        app.insert_resource(environment);
+        app.world_mut().spawn_batch((0..1000).map(|_| Sun));
+        app.update();
+        let expected = SunReferenceFrame::Y_UP.light_rotation(-environment.sun_direction());
+        let mut suns = app.world_mut().query_filtered::<&Transform, With<Sun>>();
+        assert!(suns.iter(app.world()).all(|transform| transform.rotation.abs_diff_eq(expected, 1e-6)));
+    }
+
+    #[test]
+    fn sun_environment_overrides_resource() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let global = app.world_mut().spawn(Sun).id();
+        let midnight = Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT);
+        let local = app.world_mut().spawn((Sun, SunEnvironment(midnight))).id();
+        app.update();
+        // straight down at noon, and straight up at midnight
+        assert!(app.world().get::<Transform>(global).unwrap().forward().abs_diff_eq(Vec3::NEG_Y, 1e-5));
+        assert!(app.world().get::<Transform>(local).unwrap().forward().abs_diff_eq(Vec3::Y, 1e-5));
+    }
+
+    #[test]
+    fn runs_in_custom_schedule() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::in_schedule(PostUpdate)));
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        assert_ne!(app.world().get::<Transform>(sun).unwrap().rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn keeps_environment_inserted_first() {
+        let environment = Environment::default().with_hours_since_noon(-3.0);
+        let mut app = App::new();
+        app.insert_resource(environment);
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        assert_eq!(app.world().resource::<Environment>().time_of_day, environment.time_of_day);
+        // without a default environment the suns wait for one
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default().without_default_environment()));
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        assert_eq!(app.world().get::<Transform>(sun).unwrap().rotation, Quat::IDENTITY);
+        app.insert_resource(environment);
+        app.update();
+        assert_ne!(app.world().get::<Transform>(sun).unwrap().rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn paused_clock_leaves_suns_alone() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        app.insert_resource(SolarClock::default());
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        let rotation = app.world().get::<Transform>(sun).unwrap().rotation;
+        app.world_mut().clear_trackers();
+        app.update();
+        assert!(!app.world().is_resource_changed::<Environment>());
+        // new suns and suns that were moved are still pointed at the sun
+        let new_sun = app.world_mut().spawn(Sun).id();
+        app.world_mut().get_mut::<Transform>(sun).unwrap().rotation = Quat::IDENTITY;
+        app.update();
+        assert_eq!(app.world().get::<Transform>(new_sun).unwrap().rotation, rotation);
+        assert_eq!(app.world().get::<Transform>(sun).unwrap().rotation, rotation);
+    }
+
+    #[test]
+    fn normalized_environment_option() {
+        use std::f32::consts::{PI, TAU};
+        use approx::abs_diff_eq;
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::in_schedule(Update).with_normalized_environment()));
+        app.insert_resource(Environment::default()
+            .with_time_of_day(TAU + 1.0)
+            .with_date(-3.0 * PI)
+            .with_latitude(4.0));
+        app.update();
+        let environment = app.world().resource::<Environment>();
+        assert!(abs_diff_eq!(environment.time_of_day, 1.0, epsilon = 1e-5));
+        assert!(abs_diff_eq!(environment.time_of_year.abs(), PI, epsilon = 1e-5));
+        assert_eq!(environment.latitude, PI / 2.0);
+        app.world_mut().clear_trackers();
+        app.update();
+        assert!(!app.world().is_resource_changed::<Environment>());
+    }
+
+    #[test]
+    fn secondary_sun_follows_offset() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default()
+            .with_hours_since_noon(-3.0)
+            .with_secondary_sun(StarOffset::default().with_elongation_deg(20.0).with_inclination_deg(5.0));
+        app.insert_resource(environment);
+        let primary = app.world_mut().spawn(Sun).id();
+        let secondary = app.world_mut().spawn((Sun, SecondarySun)).id();
+        app.update();
+        let primary = app.world().get::<Transform>(primary).unwrap();
+        let secondary = app.world().get::<Transform>(secondary).unwrap();
+        assert!((-*primary.forward()).abs_diff_eq(environment.sun_direction(), 1e-5));
+        assert!((-*secondary.forward()).abs_diff_eq(environment.secondary_sun_direction(), 1e-5));
+        assert!(primary.forward().angle_between(*secondary.forward()) > 0.1);
+    }
+
+    #[test]
+    fn types_registered() {
+        let mut app = App::new();
+        app.add_plugins(RealisticSunDirectionPlugin::default());
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        assert!(registry.get(std::any::TypeId::of::<Environment>()).is_some());
+        assert!(registry.get(std::any::TypeId::of::<Sun>()).is_some());
+        assert!(registry.get_type_data::<ReflectResource>(std::any::TypeId::of::<Environment>()).is_some());
     }
 }
@@ -0,0 +1,94 @@
+//! Contains the [`LightQuality`] enum for naming the kind of light the sun gives at its height
+use bevy::prelude::*;
+
+
+/// Kind of light the sun gives at its current height, using the bands photographers work with
+/// 
+/// ```no_run
+/// # use bevy::ecs::system::Res;
+/// # use kj_bevy_realistic_sun::{Environment, LightQuality};
+/// fn award_photo_bonus(environment: Res<Environment>) {
+///     if environment.light_quality() == LightQuality::GoldenHour {
+///         println!("Great light for a photo!");
+///     }
+/// }
+/// ```
+/// 
+/// The bands are measured against the flat horizon, with the edges in the `_DEG` constants. They
+/// are ordered from darkest to brightest, so they can be compared like
+/// `quality >= LightQuality::BlueHour`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LightQuality
+{
+    /// The sun is more than `18` degrees below the horizon and the sky is fully dark
+    Night,
+    /// Astronomical and nautical twilight, with a faint glow on the horizon
+    Twilight,
+    /// Just after sunset or before sunrise, when the sky is a deep even blue
+    BlueHour,
+    /// The sun is low in the sky, with warm soft light and long shadows
+    GoldenHour,
+    /// Ordinary daylight
+    Daylight,
+    /// The sun is high overhead, with hard light and short shadows
+    HarshMidday,
+}
+
+impl LightQuality
+{
+    /// Elevation in degrees where [`Night`](LightQuality::Night) ends and
+    /// [`Twilight`](LightQuality::Twilight) starts
+    pub const TWILIGHT_START_DEG: f32 = -18.0;
+    /// Elevation in degrees where [`BlueHour`](LightQuality::BlueHour) starts
+    pub const BLUE_HOUR_START_DEG: f32 = -6.0;
+    /// Elevation in degrees where [`GoldenHour`](LightQuality::GoldenHour) starts
+    pub const GOLDEN_HOUR_START_DEG: f32 = -4.0;
+    /// Elevation in degrees where [`Daylight`](LightQuality::Daylight) starts
+    pub const DAYLIGHT_START_DEG: f32 = 6.0;
+    /// Elevation in degrees where [`HarshMidday`](LightQuality::HarshMidday) starts
+    pub const HARSH_MIDDAY_START_DEG: f32 = 50.0;
+
+    /// Light quality with the sun `elevation` degrees above the horizon
+    pub fn from_elevation_deg(elevation: f32) -> Self {
+        if elevation >= Self::HARSH_MIDDAY_START_DEG { Self::HarshMidday }
+        else if elevation >= Self::DAYLIGHT_START_DEG { Self::Daylight }
+        else if elevation >= Self::GOLDEN_HOUR_START_DEG { Self::GoldenHour }
+        else if elevation >= Self::BLUE_HOUR_START_DEG { Self::BlueHour }
+        else if elevation >= Self::TWILIGHT_START_DEG { Self::Twilight }
+        else { Self::Night }
+    }
+
+    /// Name of the light quality, like `"Golden Hour"`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Night => "Night",
+            Self::Twilight => "Twilight",
+            Self::BlueHour => "Blue Hour",
+            Self::GoldenHour => "Golden Hour",
+            Self::Daylight => "Daylight",
+            Self::HarshMidday => "Harsh Midday",
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Environment;
+
+    #[test]
+    fn bands_from_elevation() {
+        assert_eq!(LightQuality::from_elevation_deg(-30.0), LightQuality::Night);
+        assert_eq!(LightQuality::from_elevation_deg(-10.0), LightQuality::Twilight);
+        assert_eq!(LightQuality::from_elevation_deg(-5.0), LightQuality::BlueHour);
+        assert_eq!(LightQuality::from_elevation_deg(2.0), LightQuality::GoldenHour);
+        assert_eq!(LightQuality::from_elevation_deg(30.0), LightQuality::Daylight);
+        assert_eq!(LightQuality::from_elevation_deg(70.0), LightQuality::HarshMidday);
+        let noon = Environment::default().with_latitude_deg(10.0);
+        assert_eq!(noon.light_quality(), LightQuality::HarshMidday);
+        assert!(noon.with_hours_since_noon(-5.8).light_quality() <= LightQuality::GoldenHour);
+    }
+}
@@ -0,0 +1,187 @@
+//! Real-world places to set the [`Environment`](crate::Environment) to, with the [`Location`]
+//! type and a table of common cities and landmarks
+//! 
+//! ```no_run
+//! # use kj_bevy_realistic_sun::Environment;
+//! use kj_bevy_realistic_sun::locations::{self, Location};
+//! // A built in location
+//! let environment = Environment::default()
+//!     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+//!     .with_location(Location::TOKYO);
+//! // Picked by name from a settings menu
+//! let reykjavik = locations::find("reykjavik").unwrap();
+//! // Or a location of your own
+//! const HOMETOWN: Location = Location::new("Hometown", 45.5, -73.6, -5.0);
+//! ```
+use crate::conversion::*;
+
+
+/// A place on Earth, with its latitude, longitude and time zone
+/// 
+/// Values are stored in radians like the [`Environment`](crate::Environment), but created from
+/// degrees and hours with [`Location::new`]. Use one with
+/// [`Environment::with_location`](crate::Environment::with_location).
+/// 
+/// **Note:** the UTC offset is the place's standard time, daylight saving time isn't taken into
+/// account
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location
+{
+    /// Name of the place, like `"Tokyo"`
+    pub name: &'static str,
+    /// Latitude in radians, positive to the north
+    pub latitude: f32,
+    /// Longitude in radians, positive to the east of the prime meridian
+    pub longitude: f32,
+    /// Offset of the standard time zone from UTC, in radians of time (`PI/12` per hour)
+    pub utc_offset: f32,
+}
+
+impl Location
+{
+    /// Creates a new `Location` from a latitude and longitude in degrees and a UTC offset in hours
+    pub const fn new(name: &'static str, latitude: f32, longitude: f32, utc_offset: f32) -> Self {
+        Self {
+            name,
+            latitude: latitude * DEG_TO_RAD,
+            longitude: longitude * DEG_TO_RAD,
+            utc_offset: utc_offset * HOURS_TO_RAD,
+        }
+    }
+
+    /// Latitude in degrees, positive to the north
+    pub fn latitude_deg(&self) -> f32 {
+        self.latitude * RAD_TO_DEG
+    }
+
+    /// Longitude in degrees, positive to the east of the prime meridian
+    pub fn longitude_deg(&self) -> f32 {
+        self.longitude * RAD_TO_DEG
+    }
+
+    /// Offset of the standard time zone from UTC in hours
+    pub fn utc_offset_hours(&self) -> f32 {
+        self.utc_offset * RAD_TO_HOURS
+    }
+
+    /// A location in New Jersey, the crate's original test location
+    pub const NEW_JERSEY: Self = Self::new("New Jersey", 40.82706, -74.1, -5.0);
+    /// New York City, United States
+    pub const NEW_YORK: Self = Self::new("New York", 40.7128, -74.006, -5.0);
+    /// Los Angeles, United States
+    pub const LOS_ANGELES: Self = Self::new("Los Angeles", 34.0522, -118.2437, -8.0);
+    /// Honolulu, United States
+    pub const HONOLULU: Self = Self::new("Honolulu", 21.3069, -157.8583, -10.0);
+    /// Mexico City, Mexico
+    pub const MEXICO_CITY: Self = Self::new("Mexico City", 19.4326, -99.1332, -6.0);
+    /// Quito, Ecuador, almost on the equator
+    pub const QUITO: Self = Self::new("Quito", -0.1807, -78.4678, -5.0);
+    /// São Paulo, Brazil
+    pub const SAO_PAULO: Self = Self::new("São Paulo", -23.5505, -46.6333, -3.0);
+    /// Buenos Aires, Argentina
+    pub const BUENOS_AIRES: Self = Self::new("Buenos Aires", -34.6037, -58.3816, -3.0);
+    /// Reykjavík, Iceland
+    pub const REYKJAVIK: Self = Self::new("Reykjavík", 64.1466, -21.9426, 0.0);
+    /// London, United Kingdom
+    pub const LONDON: Self = Self::new("London", 51.5074, -0.1278, 0.0);
+    /// The Royal Observatory in Greenwich, on the prime meridian
+    pub const GREENWICH: Self = Self::new("Greenwich", 51.4769, -0.0005, 0.0);
+    /// Stonehenge, United Kingdom, lined up with the solstice sunrise and sunset
+    pub const STONEHENGE: Self = Self::new("Stonehenge", 51.1789, -1.8262, 0.0);
+    /// Paris, France
+    pub const PARIS: Self = Self::new("Paris", 48.8566, 2.3522, 1.0);
+    /// Berlin, Germany
+    pub const BERLIN: Self = Self::new("Berlin", 52.52, 13.405, 1.0);
+    /// Tromsø, Norway, north of the arctic circle
+    pub const TROMSO: Self = Self::new("Tromsø", 69.6492, 18.9553, 1.0);
+    /// Cape Town, South Africa
+    pub const CAPE_TOWN: Self = Self::new("Cape Town", -33.9249, 18.4241, 2.0);
+    /// Cairo, Egypt
+    pub const CAIRO: Self = Self::new("Cairo", 30.0444, 31.2357, 2.0);
+    /// The pyramids of Giza, Egypt
+    pub const GIZA_PYRAMIDS: Self = Self::new("Giza Pyramids", 29.9792, 31.1342, 2.0);
+    /// Moscow, Russia
+    pub const MOSCOW: Self = Self::new("Moscow", 55.7558, 37.6173, 3.0);
+    /// Nairobi, Kenya
+    pub const NAIROBI: Self = Self::new("Nairobi", -1.2921, 36.8219, 3.0);
+    /// Dubai, United Arab Emirates
+    pub const DUBAI: Self = Self::new("Dubai", 25.2048, 55.2708, 4.0);
+    /// Mumbai, India
+    pub const MUMBAI: Self = Self::new("Mumbai", 19.076, 72.8777, 5.5);
+    /// Singapore
+    pub const SINGAPORE: Self = Self::new("Singapore", 1.3521, 103.8198, 8.0);
+    /// Beijing, China
+    pub const BEIJING: Self = Self::new("Beijing", 39.9042, 116.4074, 8.0);
+    /// Tokyo, Japan
+    pub const TOKYO: Self = Self::new("Tokyo", 35.6762, 139.6503, 9.0);
+    /// Sydney, Australia
+    pub const SYDNEY: Self = Self::new("Sydney", -33.8688, 151.2093, 10.0);
+    /// Auckland, New Zealand
+    pub const AUCKLAND: Self = Self::new("Auckland", -36.8485, 174.7633, 12.0);
+    /// Machu Picchu, Peru
+    pub const MACHU_PICCHU: Self = Self::new("Machu Picchu", -13.1631, -72.545, -5.0);
+    /// McMurdo Station, Antarctica
+    pub const MCMURDO_STATION: Self = Self::new("McMurdo Station", -77.8419, 166.6863, 12.0);
+    /// The geographic north pole
+    pub const NORTH_POLE: Self = Self::new("North Pole", 90.0, 0.0, 0.0);
+    /// The geographic south pole
+    pub const SOUTH_POLE: Self = Self::new("South Pole", -90.0, 0.0, 0.0);
+}
+
+/// Every built in [`Location`], for listing them in menus
+pub const ALL: &[Location] = &[
+    Location::NEW_JERSEY,
+    Location::NEW_YORK,
+    Location::LOS_ANGELES,
+    Location::HONOLULU,
+    Location::MEXICO_CITY,
+    Location::QUITO,
+    Location::SAO_PAULO,
+    Location::BUENOS_AIRES,
+    Location::REYKJAVIK,
+    Location::LONDON,
+    Location::GREENWICH,
+    Location::STONEHENGE,
+    Location::PARIS,
+    Location::BERLIN,
+    Location::TROMSO,
+    Location::CAPE_TOWN,
+    Location::CAIRO,
+    Location::GIZA_PYRAMIDS,
+    Location::MOSCOW,
+    Location::NAIROBI,
+    Location::DUBAI,
+    Location::MUMBAI,
+    Location::SINGAPORE,
+    Location::BEIJING,
+    Location::TOKYO,
+    Location::SYDNEY,
+    Location::AUCKLAND,
+    Location::MACHU_PICCHU,
+    Location::MCMURDO_STATION,
+    Location::NORTH_POLE,
+    Location::SOUTH_POLE,
+];
+
+/// Finds a built in [`Location`] by name, ignoring case, or `None` if there isn't one
+pub fn find(name: &str) -> Option<Location> {
+    ALL.iter().copied().find(|location| location.name.eq_ignore_ascii_case(name))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::abs_diff_eq;
+    use crate::Environment;
+
+    #[test]
+    fn locations() {
+        assert_eq!(find("tokyo"), Some(Location::TOKYO));
+        assert_eq!(find("Atlantis"), None);
+        let environment = Environment::default().with_location(Location::TOKYO);
+        assert!(abs_diff_eq!(environment.latitude * RAD_TO_DEG, 35.6762, epsilon = 1e-4));
+        assert!(abs_diff_eq!(environment.utc_offset * RAD_TO_HOURS, 9.0, epsilon = 1e-5));
+        assert_eq!(Environment::LATITUDE_NEW_JERSEY, Location::NEW_JERSEY.latitude);
+    }
+}
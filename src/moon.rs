@@ -0,0 +1,247 @@
+//! Contains the [`Moon`] component and the [`LunarOrbit`] parameters that control where it is
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, SunEnvironment, SunReferenceFrame};
+use crate::conversion::*;
+
+
+/// Attach to a second
+/// [`DirectionalLight`](https://docs.rs/bevy/0.17.3/bevy/light/struct.DirectionalLight.html)
+/// representing your moon
+/// 
+/// Works the same way as [`Sun`](crate::Sun), but points the way the moon light would be pointing
+/// given the [`lunar_orbit`](Environment::lunar_orbit) and the other values in the
+/// [`Environment`] resource (or its own [`SunEnvironment`]). The moon rises and sets later each
+/// day than the sun as it moves along its orbit.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::Moon;
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// commands.spawn((
+///     DirectionalLight {
+///         illuminance: 0.3,
+///         ..Default::default()
+///     },
+///     Moon,
+/// ));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct Moon;
+
+/// Orbital parameters of the moon, stored in [`Environment::lunar_orbit`]
+/// 
+/// The moon's position is worked out from the time of year, so it moves along its orbit as the
+/// year advances. Defaults to Earth's moon.
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{Environment, LunarOrbit};
+/// // A fast moon with a steeply inclined orbit, full at the start of summer
+/// let environment = Environment::default()
+///     .with_lunar_orbit(LunarOrbit::default()
+///         .with_period_days(10.0, 365.0)
+///         .with_inclination_deg(20.0)
+///         .with_phase_offset_deg(180.0));
+/// ```
+/// 
+/// **Note:** the [`Environment`] only stores the time within the current year, so unless a whole
+/// number of periods fits in a year the moon will jump when the time of year wraps around
+#[derive(Clone, Copy, Debug)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct LunarOrbit
+{
+    /// Time between two new moons, as a fraction of a year
+    pub period: f32,
+
+    /// Inclination of the moon's orbit compared to the planet's orbit around the sun, in radians
+    pub inclination: f32,
+
+    /// Angle between the moon and the sun as seen from the planet at the summer solstice (time of
+    /// year `0.0`), in radians. `0.0` is a new moon and `PI` is a full moon
+    pub phase_offset: f32,
+}
+
+impl Default for LunarOrbit {
+    fn default() -> Self {
+        Self::EARTH
+    }
+}
+
+impl LunarOrbit
+{
+    /// Earth's moon, with a period of 29.53 days and an inclination of 5.14 degrees
+    pub const EARTH: Self = Self {
+        period: 29.530589 / 365.2422,
+        inclination: 5.145 * DEG_TO_RAD,
+        phase_offset: 0.0,
+    };
+
+    /// Sets the period as a fraction of a year
+    pub const fn with_period(mut self, period: f32) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Sets the period in days, given the number of days in a year
+    pub const fn with_period_days(self, days: f32, days_per_year: f32) -> Self {
+        self.with_period(days / days_per_year)
+    }
+
+    /// Sets the inclination in radians
+    pub const fn with_inclination(mut self, inclination: f32) -> Self {
+        self.inclination = inclination;
+        self
+    }
+
+    /// Sets the inclination in degrees
+    pub const fn with_inclination_deg(self, inclination: f32) -> Self {
+        self.with_inclination(inclination * DEG_TO_RAD)
+    }
+
+    /// Sets the phase offset in radians
+    pub const fn with_phase_offset(mut self, phase_offset: f32) -> Self {
+        self.phase_offset = phase_offset;
+        self
+    }
+
+    /// Sets the phase offset in degrees
+    pub const fn with_phase_offset_deg(self, phase_offset: f32) -> Self {
+        self.with_phase_offset(phase_offset * DEG_TO_RAD)
+    }
+
+    /// Angle in radians the moon is ahead of the sun along the planet's orbit at `time_of_year`
+    pub(crate) fn elongation(&self, time_of_year: f32) -> f32 {
+        if self.period <= 0.0 {
+            return self.phase_offset;
+        }
+        (self.phase_offset + time_of_year / self.period).rem_euclid(TAU)
+    }
+}
+
+/// Named phases of the moon, returned by [`Environment::moon_phase_name`]
+/// 
+/// ```no_run
+/// # use bevy::ecs::system::Res;
+/// # use kj_bevy_realistic_sun::{Environment, MoonPhase};
+/// fn werewolves_out(environment: Res<Environment>) -> bool {
+///     environment.moon_phase_name() == MoonPhase::FullMoon
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum MoonPhase
+{
+    /// The moon is next to the sun and can't be seen
+    NewMoon,
+    /// Less than half of the moon is lit, and growing
+    WaxingCrescent,
+    /// Half of the moon is lit, and growing
+    FirstQuarter,
+    /// More than half of the moon is lit, and growing
+    WaxingGibbous,
+    /// The moon is opposite the sun and fully lit
+    FullMoon,
+    /// More than half of the moon is lit, and shrinking
+    WaningGibbous,
+    /// Half of the moon is lit, and shrinking
+    LastQuarter,
+    /// Less than half of the moon is lit, and shrinking
+    WaningCrescent,
+}
+
+impl MoonPhase
+{
+    /// Named phase for a normalized `phase` from `0.0` to `1.0`, in the same format as
+    /// [`Environment::moon_phase`]. Each name covers an eighth of the cycle, centered on its exact
+    /// phase
+    pub fn from_phase(phase: f32) -> Self {
+        const PHASES: [MoonPhase; 8] = [
+            MoonPhase::NewMoon,
+            MoonPhase::WaxingCrescent,
+            MoonPhase::FirstQuarter,
+            MoonPhase::WaxingGibbous,
+            MoonPhase::FullMoon,
+            MoonPhase::WaningGibbous,
+            MoonPhase::LastQuarter,
+            MoonPhase::WaningCrescent,
+        ];
+        let index = (phase.rem_euclid(1.0) * 8.0 + 0.5) as usize % 8;
+        PHASES[index]
+    }
+}
+
+/// Runs once per frame, updating every entity with a [`Moon`] component to face in the
+/// calculated direction
+pub(crate) fn update_moon_lights(
+    mut lights: Query<(Entity, &mut Transform, Option<&SunEnvironment>), With<Moon>>,
+    visibilities: Query<(Option<&Visibility>, Option<&ChildOf>)>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    let environment = cinematic_overrides.resolve(&environment);
+    let global_direction = -environment.moon_direction();
+    for (entity, mut transform, moon_environment) in &mut lights {
+        if crate::is_hidden(entity, &visibilities) {
+            continue;
+        }
+        let light_direction = match moon_environment {
+            Some(moon_environment) => -moon_environment.0.moon_direction(),
+            None => global_direction,
+        };
+        let rotation = reference_frame.light_rotation(light_direction);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn full_moon_opposite_sun() {
+        let environment = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(30.0)
+            .with_lunar_orbit(LunarOrbit::EARTH.with_inclination(0.0).with_phase_offset(PI));
+        let sun = environment.sun_direction();
+        let moon = environment.moon_direction();
+        assert!(moon.abs_diff_eq(-sun, 1e-5));
+        // a new moon sits right next to the sun
+        let environment = environment.with_lunar_orbit(LunarOrbit::EARTH.with_inclination(0.0));
+        assert!(environment.moon_direction().abs_diff_eq(sun, 1e-5));
+    }
+
+    #[test]
+    fn phases() {
+        let environment = Environment::default().with_lunar_orbit(LunarOrbit::EARTH.with_phase_offset(PI));
+        assert!(abs_diff_eq!(environment.moon_phase(), 0.5, epsilon = 1e-5));
+        assert!(abs_diff_eq!(environment.moon_illumination(), 1.0, epsilon = 1e-5));
+        assert_eq!(environment.moon_phase_name(), MoonPhase::FullMoon);
+        assert_eq!(MoonPhase::from_phase(0.98), MoonPhase::NewMoon);
+        assert_eq!(MoonPhase::from_phase(0.25), MoonPhase::FirstQuarter);
+        assert_eq!(MoonPhase::from_phase(0.8), MoonPhase::LastQuarter);
+    }
+
+    #[test]
+    fn moves_along_orbit() {
+        let orbit = LunarOrbit::default().with_period(0.25);
+        assert!(abs_diff_eq!(orbit.elongation(TAU / 8.0), PI, epsilon = 1e-5));
+    }
+}
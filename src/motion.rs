@@ -0,0 +1,199 @@
+//! Contains the [`SunMotionConstraint`] component for stylized sun movement
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+use crate::{Environment, Sun};
+use crate::conversion::*;
+
+
+/// Holds a [`Sun`] back from following the realistic direction smoothly, for stylized games
+///
+/// The realistic direction is worked out first and then constrained, so the sun still follows the
+/// [`Environment`] (or its own [`SunEnvironment`](crate::SunEnvironment)) and any
+/// [`SunOffset`](crate::SunOffset), just in steps or at a limited speed.
+///
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{Sun, SunMotionConstraint};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // Lighting changes in 8 steps through the day
+/// commands.spawn((
+///     Sun,
+///     SunMotionConstraint::Snap(8),
+/// ));
+/// // Never turns faster than 10 degrees per second, even when the time jumps
+/// commands.spawn((
+///     Sun,
+///     SunMotionConstraint::max_angular_speed_deg(10.0),
+/// ));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SunMotionConstraint
+{
+    /// Jumps between this many evenly spaced positions through the day, centered on solar noon so
+    /// noon is always one of them. The sun moves to the nearest position
+    Snap(u32),
+
+    /// Rounds the solar time of day down to a multiple of this many hours since midnight, so the
+    /// sun ticks forward like the hour hand of a clock
+    QuantizedHours(f32),
+
+    /// Turns towards the realistic direction at most this many radians per second, so jumps in
+    /// time are caught up with gradually. Only the [`Transform`] is held back, not the
+    /// [`SunDirection`](crate::SunDirection)
+    MaxAngularSpeed(f32),
+}
+
+impl SunMotionConstraint
+{
+    /// Creates a [`QuantizedHours`](SunMotionConstraint::QuantizedHours) constraint that steps
+    /// every `hours`
+    pub const fn quantized_hours(hours: f32) -> Self {
+        Self::QuantizedHours(hours)
+    }
+
+    /// Creates a [`MaxAngularSpeed`](SunMotionConstraint::MaxAngularSpeed) constraint in degrees
+    /// per second
+    pub const fn max_angular_speed_deg(speed: f32) -> Self {
+        Self::MaxAngularSpeed(speed * DEG_TO_RAD)
+    }
+
+    /// Constrains a [`solar_time_of_day`](Environment::solar_time_of_day) in radians, or returns
+    /// it unchanged for constraints that don't work on the time
+    pub fn constrain_time_of_day(&self, time_of_day: f32) -> f32 {
+        match *self {
+            Self::Snap(positions) if positions > 0 => {
+                let step = TAU / positions as f32;
+                wrap_angle((time_of_day / step).round() * step)
+            },
+            Self::QuantizedHours(hours) if hours > 0.0 => {
+                let step = hours * HOURS_TO_RAD;
+                wrap_angle(((time_of_day + PI) / step).floor() * step - PI)
+            },
+            _ => time_of_day,
+        }
+    }
+
+    /// Copy of `environment` with the time constrained, or [`None`] for constraints that don't
+    /// work on the time
+    /// 
+    /// The solar time is constrained, so solar noon stays a snap point whatever the longitude,
+    /// time zone and equation of time.
+    pub(crate) fn constrain_environment(&self, environment: &Environment) -> Option<Environment> {
+        match self {
+            Self::Snap(_) | Self::QuantizedHours(_) => {
+                let solar_time_of_day = self.constrain_time_of_day(environment.solar_time_of_day());
+                Some(environment.with_time_of_day(environment.solar_to_clock_time(solar_time_of_day)))
+            },
+            Self::MaxAngularSpeed(_) => None,
+        }
+    }
+}
+
+/// Rotation a [`SunMotionConstraint::MaxAngularSpeed`] sun was last shown at, and the rotation it
+/// is turning towards
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub(crate) struct SunMotionState
+{
+    shown: Quat,
+    target: Quat,
+}
+
+/// Turns suns with a [`SunMotionConstraint::MaxAngularSpeed`] towards the rotation written by
+/// [`update_sun_lights`](crate::update_sun_lights), no faster than their speed
+pub(crate) fn limit_sun_angular_speed(
+    mut commands: Commands,
+    mut suns: Query<(Entity, &mut Transform, &SunMotionConstraint, Option<&mut SunMotionState>), With<Sun>>,
+    time: Res<Time>,
+){
+    for (entity, mut transform, constraint, state) in &mut suns {
+        let SunMotionConstraint::MaxAngularSpeed(max_speed) = *constraint else {
+            if state.is_some() {
+                commands.entity(entity).remove::<SunMotionState>();
+            }
+            continue;
+        };
+        let Some(mut state) = state else {
+            commands.entity(entity).insert(SunMotionState { shown: transform.rotation, target: transform.rotation });
+            continue;
+        };
+        // anything other than the rotation shown last frame is a new target
+        if transform.rotation != state.shown {
+            state.target = transform.rotation;
+        }
+        if state.shown == state.target {
+            continue;
+        }
+        let step = max_speed.max(0.0) * time.delta_secs();
+        let rotation = if state.shown.angle_between(state.target) <= step {
+            state.target
+        } else {
+            state.shown.rotate_towards(state.target, step)
+        };
+        state.shown = rotation;
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use bevy::time::TimeUpdateStrategy;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn constrains_time() {
+        let snap = SunMotionConstraint::Snap(4);
+        assert_eq!(snap.constrain_time_of_day(1.0 * HOURS_TO_RAD), 0.0);
+        assert!((snap.constrain_time_of_day(5.0 * HOURS_TO_RAD) - 6.0 * HOURS_TO_RAD).abs() < 1e-5);
+        let hourly = SunMotionConstraint::quantized_hours(1.0);
+        assert!((hourly.constrain_time_of_day(2.75 * HOURS_TO_RAD) - 2.0 * HOURS_TO_RAD).abs() < 1e-5);
+        assert!((hourly.constrain_time_of_day(-0.25 * HOURS_TO_RAD) + HOURS_TO_RAD).abs() < 1e-5);
+    }
+
+    #[test]
+    fn snaps_solar_time() {
+        let environment = Environment::default()
+            .with_longitude_deg(-74.0)
+            .with_utc_offset_hours(-5.0)
+            .with_hours_since_noon(1.0);
+        let snapped = SunMotionConstraint::Snap(4).constrain_environment(&environment).unwrap();
+        // 1 PM on the clock is 1:04 PM solar time, so the sun snaps to solar noon, not clock noon
+        assert!(snapped.solar_time_of_day().abs() < 1e-5, "{}", snapped.solar_time_of_day());
+        let hourly = SunMotionConstraint::quantized_hours(1.0).constrain_environment(&environment).unwrap();
+        assert!((hourly.solar_time_of_day() - HOURS_TO_RAD).abs() < 1e-5, "{}", hourly.solar_time_of_day());
+    }
+
+    #[test]
+    fn limits_turning_speed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(0.5)));
+        // virtual time clamps each step to 0.25 seconds by default
+        app.world_mut().resource_mut::<Time<Virtual>>().set_max_delta(Duration::from_secs(1));
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        let sun = app.world_mut().spawn((Sun, SunMotionConstraint::max_angular_speed_deg(10.0))).id();
+        app.update();
+        app.update();
+        let morning = app.world().get::<Transform>(sun).unwrap().rotation;
+        app.insert_resource(Environment::default().with_hours_since_noon(3.0));
+        app.update();
+        let turned = morning.angle_between(app.world().get::<Transform>(sun).unwrap().rotation);
+        assert!((turned - 5.0 * DEG_TO_RAD).abs() < 1e-3, "{}", turned * RAD_TO_DEG);
+        // keeps turning towards the afternoon without the environment changing
+        app.update();
+        let turned = morning.angle_between(app.world().get::<Transform>(sun).unwrap().rotation);
+        assert!((turned - 10.0 * DEG_TO_RAD).abs() < 1e-3, "{}", turned * RAD_TO_DEG);
+    }
+}
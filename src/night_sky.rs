@@ -0,0 +1,149 @@
+//! Contains the [`NightSky`] component that turns a star skybox with the sky and fades it in at
+//! night
+use bevy::prelude::*;
+use crate::{CelestialSphere, CinematicOverrides, Environment, SunEnvironment};
+use crate::conversion::*;
+
+
+/// Attach to a star skybox to have it turn with the sky and fade in as the sun goes down
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::NightSky;
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // Stars start showing at sunset and are at full brightness by the end of nautical twilight
+/// commands.spawn(NightSky::default());
+/// // Or only once it is properly dark
+/// commands.spawn(NightSky::default().with_fade_deg(-6.0, -18.0));
+/// ```
+/// 
+/// Requires a [`CelestialSphere`], so the entity is rotated with the fixed stars. Every frame
+/// [`brightness`](NightSky::brightness) is set from `0.0` while the sun is above
+/// [`hidden_above`](NightSky::hidden_above) to [`max_brightness`](NightSky::max_brightness) once
+/// it is below [`visible_below`](NightSky::visible_below), using the entity's own
+/// [`SunEnvironment`] if it has one. Read it from your own material code, or with the `pbr`
+/// feature the entity's `StandardMaterial` has its alpha and emissive color set from it. The
+/// material needs a blending alpha mode for the alpha to have any effect.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(CelestialSphere)]
+pub struct NightSky
+{
+    /// Solar elevation in radians the stars start fading in below
+    pub hidden_above: f32,
+
+    /// Solar elevation in radians the stars are at full brightness below
+    pub visible_below: f32,
+
+    /// Brightness of the stars at night, where `1.0` is the full emissive color
+    pub max_brightness: f32,
+
+    /// Emissive color of the material at full brightness, used with the `pbr` feature
+    pub emissive: LinearRgba,
+
+    /// Current brightness of the stars, updated every frame
+    pub brightness: f32,
+}
+
+impl Default for NightSky {
+    /// Fades in from sunset until the sun is 12 degrees below the horizon
+    fn default() -> Self {
+        Self {
+            hidden_above: 0.0,
+            visible_below: -12.0 * DEG_TO_RAD,
+            max_brightness: 1.0,
+            emissive: LinearRgba::WHITE,
+            brightness: 0.0,
+        }
+    }
+}
+
+impl NightSky
+{
+    /// Sets the solar elevations in degrees the stars start fading in below and are at full
+    /// brightness below
+    pub const fn with_fade_deg(mut self, hidden_above: f32, visible_below: f32) -> Self {
+        self.hidden_above = hidden_above * DEG_TO_RAD;
+        self.visible_below = visible_below * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the brightness of the stars at night
+    pub const fn with_max_brightness(mut self, max_brightness: f32) -> Self {
+        self.max_brightness = max_brightness;
+        self
+    }
+
+    /// Sets the emissive color of the material at full brightness
+    pub const fn with_emissive(mut self, emissive: LinearRgba) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
+    /// Brightness of the stars with the sun `elevation` radians above the horizon
+    pub fn brightness_at(&self, elevation: f32) -> f32 {
+        let range = self.hidden_above - self.visible_below;
+        if range <= 0.0 {
+            return if elevation < self.hidden_above { self.max_brightness } else { 0.0 };
+        }
+        ((self.hidden_above - elevation) / range).clamp(0.0, 1.0) * self.max_brightness
+    }
+}
+
+/// Runs once per frame, setting the brightness of every [`NightSky`] from the solar elevation
+pub(crate) fn update_night_skies(
+    mut skies: Query<(&mut NightSky, Option<&SunEnvironment>)>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let global_elevation = cinematic_overrides.resolve(&environment).solar_elevation();
+    for (mut sky, sky_environment) in &mut skies {
+        let elevation = sky_environment.map_or(global_elevation, |sky_environment| sky_environment.0.solar_elevation());
+        let brightness = sky.brightness_at(elevation);
+        if sky.brightness != brightness {
+            sky.brightness = brightness;
+        }
+    }
+}
+
+/// Copies the brightness of every changed [`NightSky`] into its material
+#[cfg(feature = "pbr")]
+pub(crate) fn apply_night_sky_materials(
+    skies: Query<(&NightSky, &MeshMaterial3d<StandardMaterial>), Changed<NightSky>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+){
+    for (sky, material) in &skies {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = material.base_color.with_alpha(sky.brightness);
+            material.emissive = sky.emissive * sky.brightness;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn fades_in_at_night() {
+        let sky = NightSky::default();
+        assert_eq!(sky.brightness_at(10.0 * DEG_TO_RAD), 0.0);
+        assert!((sky.brightness_at(-6.0 * DEG_TO_RAD) - 0.5).abs() < 1e-5);
+        assert_eq!(sky.brightness_at(-30.0 * DEG_TO_RAD), 1.0);
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default().with_latitude_deg(40.0).with_hours_since_noon(12.0));
+        let entity = app.world_mut().spawn(NightSky::default()).id();
+        app.update();
+        assert_eq!(app.world().get::<NightSky>(entity).unwrap().brightness, 1.0);
+        let rotation = app.world().get::<Transform>(entity).unwrap().rotation;
+        assert_eq!(rotation, app.world().resource::<Environment>().celestial_sphere_rotation());
+    }
+}
@@ -0,0 +1,106 @@
+//! Contains the [`SunOffset`] component for nudging a single sun away from its realistic position
+use bevy::prelude::*;
+use crate::conversion::*;
+use crate::photo_mode::offset_light_direction;
+
+
+/// Nudges one [`Sun`](crate::Sun) away from where the [`Environment`](crate::Environment) puts it,
+/// while it keeps moving realistically
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::light::DirectionalLight;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{Sun, SunOffset};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // Swing the sun a little to the west so the shadow falls across the door at 3 PM
+/// commands.spawn((
+///     DirectionalLight::default(),
+///     Sun,
+///     SunOffset::default().with_azimuth_deg(12.0).with_elevation_deg(-3.0),
+/// ));
+/// ```
+/// 
+/// Applied after everything else that moves the sun, like
+/// [`StylizedSunPath`](crate::StylizedSunPath) and [`HorizonClamp`](crate::HorizonClamp). The
+/// azimuth and elevation offsets are applied first, then [`rotation`](SunOffset::rotation). Unlike
+/// [`PhotoModeSunOffset`](crate::PhotoModeSunOffset) this only affects the sun it is attached to,
+/// and is meant to stay on for the whole scene.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunOffset
+{
+    /// Radians to turn the sun clockwise around the horizon (from north towards east)
+    pub azimuth: f32,
+    /// Radians to raise the sun above where it really is. The result is clamped so the sun can't
+    /// go past straight up or straight down
+    pub elevation: f32,
+    /// Extra world space rotation applied to the direction the sun light travels in
+    pub rotation: Quat,
+}
+
+impl Default for SunOffset {
+    fn default() -> Self {
+        Self {
+            azimuth: 0.0,
+            elevation: 0.0,
+            rotation: Quat::IDENTITY,
+        }
+    }
+}
+
+impl SunOffset
+{
+    /// Sets the azimuth offset in degrees
+    pub const fn with_azimuth_deg(mut self, azimuth: f32) -> Self {
+        self.azimuth = azimuth * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the elevation offset in degrees
+    pub const fn with_elevation_deg(mut self, elevation: f32) -> Self {
+        self.elevation = elevation * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the extra rotation applied to the sun light's direction
+    pub const fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Applies the offset to a direction the sun light travels in
+    pub fn offset_light_direction(&self, light_direction: Vec3) -> Vec3 {
+        self.rotation * offset_light_direction(light_direction, self.azimuth, self.elevation)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn offsets_one_sun() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default()
+            .with_latitude_deg(40.0)
+            .with_hours_since_noon(3.0);
+        app.insert_resource(environment);
+        let plain = app.world_mut().spawn(Sun).id();
+        let offset = app.world_mut().spawn((Sun, SunOffset::default().with_elevation_deg(-10.0))).id();
+        app.update();
+        let plain = app.world().get::<Transform>(plain).unwrap().forward();
+        let offset = app.world().get::<Transform>(offset).unwrap().forward();
+        assert!((-*plain).abs_diff_eq(environment.sun_direction(), 1e-5));
+        let elevation_difference = (-plain.y).asin() - (-offset.y).asin();
+        assert!((elevation_difference - 10.0 * DEG_TO_RAD).abs() < 1e-4, "{}", elevation_difference);
+    }
+}
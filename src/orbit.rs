@@ -0,0 +1,208 @@
+//! Contains the [`EccentricityModel`] for planets with elliptical orbits, the [`AxialPrecession`]
+//! for planets whose seasons drift over the centuries and the [`RotationDirection`] a planet spins in
+use std::f32::consts::{FRAC_PI_2, TAU};
+use bevy::prelude::*;
+use crate::conversion::wrap_angle;
+
+
+/// Shape of the planet's orbit around the sun, stored in [`Environment::eccentricity`](crate::Environment::eccentricity)
+/// 
+/// When set, the equation of time is applied to the sun, so solar noon drifts earlier and later
+/// against the clock over the year the same way it does on a real planet, by up to about 16 minutes
+/// on Earth. It is made up of the speed changes from an elliptical orbit, plus the effect of the
+/// [`axial_tilt`](crate::Environment::axial_tilt) on how fast the sun moves across the sky.
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{EccentricityModel, Environment};
+/// let environment = Environment::default()
+///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///     .with_eccentricity(EccentricityModel::EARTH);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct EccentricityModel
+{
+    /// Eccentricity of the orbit, `0.0` for a circle up to just under `1.0`
+    pub eccentricity: f32,
+
+    /// Time of year the planet is closest to the sun, in radians in the same format as
+    /// [`time_of_year`](crate::Environment::time_of_year)
+    pub perihelion: f32,
+}
+
+impl Default for EccentricityModel {
+    fn default() -> Self {
+        Self::EARTH
+    }
+}
+
+impl EccentricityModel
+{
+    /// Earth's orbit, with an eccentricity of 0.0167 and perihelion in early January
+    pub const EARTH: Self = Self {
+        eccentricity: 0.0167,
+        // January 3rd, 196 days after the June solstice
+        perihelion: 196.0 / 365.2422 * TAU,
+    };
+
+    /// Sets the eccentricity of the orbit
+    pub const fn with_eccentricity(mut self, eccentricity: f32) -> Self {
+        self.eccentricity = eccentricity;
+        self
+    }
+
+    /// Sets the time of year of perihelion in radians
+    pub const fn with_perihelion(mut self, perihelion: f32) -> Self {
+        self.perihelion = perihelion;
+        self
+    }
+
+    /// Sets the time of year of perihelion in days after the summer solstice, given the number of
+    /// days in a year
+    pub const fn with_perihelion_days(self, days: f32, days_per_year: f32) -> Self {
+        self.with_perihelion(days / days_per_year * TAU)
+    }
+
+    /// Apparent solar time minus mean solar time in radians of time at `time_of_year`, for a
+    /// planet with `axial_tilt`
+    pub(crate) fn equation_of_time(&self, time_of_year: f32, axial_tilt: f32) -> f32 {
+        let mean_anomaly = time_of_year - self.perihelion;
+        // ecliptic longitude measured from the spring equinox, a quarter year before the solstice
+        let longitude = time_of_year + FRAC_PI_2;
+        let obliquity_factor = (axial_tilt / 2.0).tan().powi(2);
+        -2.0 * self.eccentricity * mean_anomaly.sin() + obliquity_factor * (2.0 * longitude).sin()
+    }
+}
+
+/// Slow wobble of the planet's axis, stored in [`Environment::precession`](crate::Environment::precession),
+/// that makes the seasons drift through the calendar over thousands of years
+/// 
+/// The solstices and equinoxes are moved along the orbit by [`angle`](AxialPrecession::angle), so
+/// the summer solstice falls on a [`time_of_year`](crate::Environment::time_of_year) of `angle`
+/// instead of `0.0`. The calendar keeps following the time of year, while the sun's height and the
+/// [`Season`](crate::Season)s follow the axis.
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{AxialPrecession, Environment};
+/// // A planet whose seasons go all the way round the calendar every 500 years,
+/// // currently with the solstice a month late
+/// let environment = Environment::default()
+///     .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+///     .with_precession(AxialPrecession::new(500.0).with_angle_deg(30.0));
+/// ```
+/// 
+/// With a [`period`](AxialPrecession::period) set, the [`SolarClock`](crate::SolarClock) and
+/// [`TimeFlow`](crate::TimeFlow) move the angle along with the time of year, for games spanning
+/// long enough for the drift to show.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct AxialPrecession
+{
+    /// Radians along the orbit the solstices have moved, so the summer solstice is at this
+    /// [`time_of_year`](crate::Environment::time_of_year)
+    pub angle: f32,
+
+    /// Years for the axis to wobble all the way around, or `0.0` to keep the angle where it is.
+    /// Negative periods move the solstices earlier in the year instead of later
+    pub period: f32,
+}
+
+impl AxialPrecession
+{
+    /// Years for Earth's axis to precess all the way around
+    pub const EARTH_PERIOD: f32 = 25_772.0;
+
+    /// Earth's precession, with the solstices where they are today
+    pub const EARTH: Self = Self::new(Self::EARTH_PERIOD);
+
+    /// Creates a new `AxialPrecession` that goes all the way around every `period` years, starting
+    /// at an angle of `0.0`
+    pub const fn new(period: f32) -> Self {
+        Self { angle: 0.0, period }
+    }
+
+    /// Sets how far along the orbit the solstices have moved in radians
+    pub const fn with_angle(mut self, angle: f32) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    /// Sets how far along the orbit the solstices have moved in degrees
+    pub const fn with_angle_deg(self, angle: f32) -> Self {
+        self.with_angle(angle * TAU / 360.0)
+    }
+
+    /// Moves the angle along for `year_delta` radians of time of year passing
+    pub(crate) fn advance(&mut self, year_delta: f32) {
+        if self.period != 0.0 {
+            self.angle = wrap_angle(self.angle + year_delta / self.period);
+        }
+    }
+}
+
+/// Which way the planet spins compared to the way it orbits, stored in
+/// [`Environment::rotation_direction`](crate::Environment::rotation_direction)
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::{Environment, RotationDirection};
+/// // Venus, where the sun rises in the west
+/// let environment = Environment::default()
+///     .with_axial_tilt_deg(2.64)
+///     .with_rotation_direction(RotationDirection::Retrograde);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum RotationDirection
+{
+    /// Spins the same way it orbits, like Earth, so the sun rises in the east
+    #[default]
+    Prograde,
+    /// Spins against its orbit, like Venus, so the sun rises in the west
+    Retrograde,
+}
+
+impl RotationDirection
+{
+    /// `1.0` for prograde and `-1.0` for retrograde, the direction the sun's hour angle moves in as
+    /// the time of day goes forwards
+    pub const fn sign(self) -> f32 {
+        match self {
+            Self::Prograde => 1.0,
+            Self::Retrograde => -1.0,
+        }
+    }
+
+    /// Number of turns the planet makes against the stars in a year `solar_days_per_year` solar
+    /// days long
+    /// 
+    /// A prograde planet turns once more against the stars than it has solar days, since its orbit
+    /// carries the sun along the same way it spins, while a retrograde planet turns once less.
+    pub fn sidereal_days_per_year(self, solar_days_per_year: f32) -> f32 {
+        solar_days_per_year + self.sign()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::*;
+    use crate::Environment;
+
+    #[test]
+    fn earth_equation_of_time() {
+        let equation_of_time = |days_after_solstice: f32| {
+            let time_of_year = days_after_solstice / 365.2422 * TAU;
+            EccentricityModel::EARTH.equation_of_time(time_of_year, Environment::AXIAL_TILT_EARTH) * RAD_TO_HOURS * 60.0
+        };
+        // sundials are about 16 minutes fast in early November and 14 minutes slow in mid February
+        assert!((equation_of_time(135.0) - 16.4).abs() < 1.0, "{}", equation_of_time(135.0));
+        assert!((equation_of_time(235.0) + 14.2).abs() < 1.0, "{}", equation_of_time(235.0));
+    }
+}
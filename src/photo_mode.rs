@@ -0,0 +1,150 @@
+//! Contains the [`PhotoModeSunOffset`] resource for nudging the sun in photo modes
+use std::f32::consts::FRAC_PI_2;
+use bevy::prelude::*;
+use crate::Sun;
+use crate::conversion::*;
+
+
+/// Layers a temporary offset on top of the realistic sun, for photo modes
+/// 
+/// While this resource exists, every [`Sun`] is nudged by the azimuth and elevation offsets and
+/// its [`DirectionalLight::illuminance`] is scaled. The [`Environment`](crate::Environment) and
+/// [`SolarClock`](crate::SolarClock) aren't touched, so removing the resource puts everything
+/// back exactly how it was.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::PhotoModeSunOffset;
+/// fn enter_photo_mode(mut commands: Commands) {
+///     commands.insert_resource(PhotoModeSunOffset::default());
+/// }
+/// 
+/// fn nudge_sun(mut offset: ResMut<PhotoModeSunOffset>, input: Res<ButtonInput<KeyCode>>) {
+///     if input.pressed(KeyCode::ArrowLeft) { offset.azimuth -= 0.01; }
+///     if input.pressed(KeyCode::ArrowRight) { offset.azimuth += 0.01; }
+/// }
+/// 
+/// fn exit_photo_mode(mut commands: Commands) {
+///     commands.remove_resource::<PhotoModeSunOffset>();
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct PhotoModeSunOffset
+{
+    /// Radians to turn the sun clockwise around the horizon (from north towards east)
+    pub azimuth: f32,
+    /// Radians to raise the sun above where it really is. The result is clamped so the sun can't
+    /// go past straight up or straight down
+    pub elevation: f32,
+    /// Multiplier for the sun light's illuminance
+    pub illuminance_scale: f32,
+}
+
+impl Default for PhotoModeSunOffset {
+    fn default() -> Self {
+        Self {
+            azimuth: 0.0,
+            elevation: 0.0,
+            illuminance_scale: 1.0,
+        }
+    }
+}
+
+impl PhotoModeSunOffset
+{
+    /// Sets the azimuth offset in degrees
+    pub const fn with_azimuth_deg(mut self, azimuth: f32) -> Self {
+        self.azimuth = azimuth * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the elevation offset in degrees
+    pub const fn with_elevation_deg(mut self, elevation: f32) -> Self {
+        self.elevation = elevation * DEG_TO_RAD;
+        self
+    }
+
+    /// Sets the illuminance multiplier
+    pub const fn with_illuminance_scale(mut self, illuminance_scale: f32) -> Self {
+        self.illuminance_scale = illuminance_scale;
+        self
+    }
+
+    /// Applies the azimuth and elevation offset to a direction the sun light travels in
+    pub(crate) fn offset_light_direction(&self, light_direction: Vec3) -> Vec3 {
+        offset_light_direction(light_direction, self.azimuth, self.elevation)
+    }
+}
+
+/// Turns a direction the sun light travels in by `azimuth` radians around the horizon and raises
+/// it by `elevation` radians, clamped so it can't go past straight up or straight down
+pub(crate) fn offset_light_direction(light_direction: Vec3, azimuth: f32, elevation: f32) -> Vec3 {
+    let (sun_azimuth, sun_elevation) = direction_to_azimuth_elevation(-light_direction);
+    let sun_elevation = (sun_elevation + elevation).clamp(-FRAC_PI_2, FRAC_PI_2);
+    -azimuth_elevation_to_direction(sun_azimuth + azimuth, sun_elevation)
+}
+
+/// Illuminance a [`Sun`] had before photo mode scaled it, so it can be put back
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub(crate) struct PhotoModeBaseIlluminance(f32);
+
+/// Scales the illuminance of every [`Sun`] while photo mode is active, and puts it back when
+/// photo mode ends
+pub(crate) fn apply_photo_mode_illuminance(
+    mut commands: Commands,
+    mut suns: Query<(Entity, &mut DirectionalLight, Option<&PhotoModeBaseIlluminance>), With<Sun>>,
+    photo_mode: Option<Res<PhotoModeSunOffset>>,
+){
+    for (entity, mut light, base) in &mut suns {
+        match (&photo_mode, base) {
+            (Some(offset), Some(base)) => {
+                light.illuminance = base.0 * offset.illuminance_scale;
+            },
+            (Some(offset), None) => {
+                commands.entity(entity).insert(PhotoModeBaseIlluminance(light.illuminance));
+                light.illuminance *= offset.illuminance_scale;
+            },
+            (None, Some(base)) => {
+                light.illuminance = base.0;
+                commands.entity(entity).remove::<PhotoModeBaseIlluminance>();
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn reverts_when_removed() {
+        let mut app = App::new();
+        app.add_plugins(RealisticSunDirectionPlugin::default());
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        let sun = app.world_mut().spawn((DirectionalLight { illuminance: 100.0, ..default() }, Sun)).id();
+        app.update();
+        let original = *app.world().get::<Transform>(sun).unwrap();
+        app.insert_resource(PhotoModeSunOffset::default()
+            .with_azimuth_deg(20.0)
+            .with_illuminance_scale(2.0));
+        app.update();
+        app.update();
+        assert_eq!(app.world().get::<DirectionalLight>(sun).unwrap().illuminance, 200.0);
+        let offset = *app.world().get::<Transform>(sun).unwrap();
+        let (original_azimuth, original_elevation) = direction_to_azimuth_elevation(-*original.forward());
+        let (offset_azimuth, offset_elevation) = direction_to_azimuth_elevation(-*offset.forward());
+        assert!((offset_azimuth - original_azimuth - 20.0 * DEG_TO_RAD).abs() < 1e-4);
+        assert!((offset_elevation - original_elevation).abs() < 1e-4);
+        app.world_mut().remove_resource::<PhotoModeSunOffset>();
+        app.update();
+        assert_eq!(app.world().get::<DirectionalLight>(sun).unwrap().illuminance, 100.0);
+        let reverted = app.world().get::<Transform>(sun).unwrap();
+        assert!(reverted.forward().angle_between(*original.forward()) < 1e-4);
+    }
+}
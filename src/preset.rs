@@ -0,0 +1,119 @@
+//! Contains [`PlanetPreset`] with the parameters of real and made up planets
+use bevy::prelude::*;
+use crate::RotationDirection;
+use crate::conversion::*;
+
+
+/// Axial tilt, day length and year length for some real planets and a few made up ones
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{Environment, PlanetPreset, TimeFlow};
+/// # let mut app = App::new();
+/// app.insert_resource(Environment::from_preset(PlanetPreset::Mars).with_latitude_deg(-4.5));
+/// app.insert_resource(TimeFlow::default().with_preset(PlanetPreset::Mars));
+/// ```
+/// 
+/// Day lengths are solar days, from one noon to the next, and years are counted in those days.
+/// 
+/// Venus spins backwards, so [`Environment::from_preset`](crate::Environment::from_preset) sets a
+/// [`Retrograde`](RotationDirection::Retrograde) rotation and its sun rises in the west. Its axial
+/// tilt is given as the equivalent tilt for a planet spinning backwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum PlanetPreset
+{
+    /// Earth, with 24 hour days and a 23.4 degree axial tilt
+    #[default]
+    Earth,
+    /// Mercury, with almost no axial tilt and a day that lasts two of its years
+    Mercury,
+    /// Venus, with a 117 Earth day long day and barely any seasons
+    Venus,
+    /// Mars, with a day a little longer than Earth's and a year almost twice as long
+    Mars,
+    /// Earth's moon, with a month long day and barely any seasons
+    Moon,
+    /// A made up planet with mild seasons and a 360 day year
+    Temperate,
+    /// A made up planet with no axial tilt, so every day of the year is the same
+    Seasonless,
+    /// A made up planet tipped far over, with long days, a short year and extreme seasons
+    Extreme,
+}
+
+impl PlanetPreset
+{
+    /// Axial tilt in radians, for [`Environment::axial_tilt`](crate::Environment::axial_tilt)
+    pub const fn axial_tilt(self) -> f32 {
+        let degrees = match self {
+            Self::Earth => 23.439281,
+            Self::Mercury => 0.034,
+            Self::Venus => 2.64,
+            Self::Mars => 25.19,
+            Self::Moon => 1.54,
+            Self::Temperate => 18.0,
+            Self::Seasonless => 0.0,
+            Self::Extreme => 60.0,
+        };
+        degrees * DEG_TO_RAD
+    }
+
+    /// Which way the planet spins, for [`Environment::rotation_direction`](crate::Environment::rotation_direction)
+    pub const fn rotation_direction(self) -> RotationDirection {
+        match self {
+            Self::Venus => RotationDirection::Retrograde,
+            _ => RotationDirection::Prograde,
+        }
+    }
+
+    /// Length of a solar day in Earth hours
+    pub const fn day_length_hours(self) -> f32 {
+        match self {
+            Self::Earth => 24.0,
+            Self::Mercury => 4222.6,
+            Self::Venus => 2802.0,
+            Self::Mars => 24.6597,
+            Self::Moon => 708.7,
+            Self::Temperate => 24.0,
+            Self::Seasonless => 26.0,
+            Self::Extreme => 30.0,
+        }
+    }
+
+    /// Number of solar days in one year, for [`TimeFlow::days_per_year`](crate::TimeFlow::days_per_year)
+    pub const fn days_per_year(self) -> f32 {
+        match self {
+            Self::Earth => 365.2422,
+            Self::Mercury => 0.5,
+            Self::Venus => 1.925,
+            Self::Mars => 668.59,
+            Self::Moon => 12.37,
+            Self::Temperate => 360.0,
+            Self::Seasonless => 300.0,
+            Self::Extreme => 120.0,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn year_lengths_in_earth_days() {
+        let tests = [
+            (PlanetPreset::Earth, 365.24),
+            (PlanetPreset::Mercury, 87.97),
+            (PlanetPreset::Venus, 224.7),
+            (PlanetPreset::Mars, 686.98),
+        ];
+        for (preset, earth_days) in tests {
+            let year = preset.days_per_year() * preset.day_length_hours() / 24.0;
+            assert!((year - earth_days).abs() < 0.5, "{:?} year is {} Earth days", preset, year);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Converts real world time into [`Environment`](crate::Environment) values for Earth
+use std::f64::consts::{PI, TAU};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::conversion::wrap_angle_f64;
+
+
+/// Length of a solar day in seconds
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+/// Length of Earth's tropical year in days
+const DAYS_PER_YEAR: f64 = 365.24219;
+/// Unix timestamp of the June solstice in 2000 (2000-06-21 01:48 UTC), used as the reference
+/// point for [`time_of_year`](crate::Environment::time_of_year)
+const SUMMER_SOLSTICE_2000: f64 = 961_552_080.0;
+
+/// Current system time as seconds since the unix epoch
+/// 
+/// A system clock set before 1970 is treated as exactly the epoch
+pub(crate) fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Seconds since the unix epoch for a date and time in any time zone
+#[cfg(feature = "real_time")]
+pub(crate) fn unix_seconds<Tz: chrono::TimeZone>(datetime: &chrono::DateTime<Tz>) -> f64 {
+    datetime.timestamp() as f64 + datetime.timestamp_subsec_nanos() as f64 / 1e9
+}
+
+/// Local mean solar time of day in radians at `longitude` radians east of Greenwich, with `0.0`
+/// at local solar noon
+pub(crate) fn time_of_day(unix_seconds: f64, longitude: f32) -> f32 {
+    let day_fraction = unix_seconds.rem_euclid(SECONDS_PER_DAY) / SECONDS_PER_DAY;
+    wrap_angle_f64(day_fraction * TAU - PI + longitude as f64) as f32
+}
+
+/// Time of year in radians, with `0.0` at the June solstice
+pub(crate) fn time_of_year(unix_seconds: f64) -> f32 {
+    let years = (unix_seconds - SUMMER_SOLSTICE_2000) / (SECONDS_PER_DAY * DAYS_PER_YEAR);
+    wrap_angle_f64(years.fract() * TAU) as f32
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::abs_diff_eq;
+
+    #[test]
+    fn noon_at_greenwich() {
+        // 2024-03-20 12:00 UTC
+        let noon = 1_710_936_000.0;
+        assert!(abs_diff_eq!(time_of_day(noon, 0.0), 0.0, epsilon = 1e-5));
+        // 90 degrees east it's already 6 hours past noon
+        let east = time_of_day(noon, std::f32::consts::FRAC_PI_2);
+        assert!(abs_diff_eq!(east, std::f32::consts::FRAC_PI_2, epsilon = 1e-5));
+    }
+
+    #[test]
+    fn solstices() {
+        // 2023-06-21 14:57 UTC
+        let summer = time_of_year(1_687_359_420.0);
+        assert!(abs_diff_eq!(summer, 0.0, epsilon = 0.03), "{}", summer);
+        // 2023-12-22 03:27 UTC
+        let winter = time_of_year(1_703_215_620.0);
+        assert!(abs_diff_eq!(winter.abs(), std::f32::consts::PI, epsilon = 0.03), "{}", winter);
+    }
+
+    #[cfg(feature = "real_time")]
+    #[test]
+    fn from_utc_matches_timestamp() {
+        use chrono::{FixedOffset, TimeZone, Utc};
+        use crate::Environment;
+        // 2024-03-20 12:00 UTC, the same moment written in New York time
+        let utc = Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap();
+        let new_york = FixedOffset::west_opt(4 * 60 * 60).unwrap().with_ymd_and_hms(2024, 3, 20, 8, 0, 0).unwrap();
+        assert_eq!(unix_seconds(&utc), 1_710_936_000.0);
+        assert_eq!(unix_seconds(&new_york), 1_710_936_000.0);
+        // close to the equinox the sun is overhead at the equator near noon, give or take the
+        // equation of time
+        let environment = Environment::from_utc(utc, 0.0, 0.0);
+        assert!(environment.solar_elevation_deg() > 85.0, "{}", environment.solar_elevation_deg());
+    }
+}
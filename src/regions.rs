@@ -0,0 +1,154 @@
+//! Contains the [`SunRegions`] resource and [`Region`] component, for worlds big enough that the
+//! sun should be in a different place at either end of the map
+use bevy::prelude::*;
+use crate::{Environment, LatitudeSource, SunEnvironment, SunReferenceFrame};
+
+
+/// Splits the map into a grid of regions, each with its own latitude and longitude on a curved
+/// planet, for very large worlds
+///
+/// Every entity with a [`Region`] gets a [`SunEnvironment`] copied from the global
+/// [`Environment`], with the latitude (and longitude, if the [`mapping`](SunRegions::mapping)
+/// asks for it) moved to the middle of its region. So a sun lighting the northern edge of the map
+/// sits lower in the sky than one lighting the south, while they share the same time of day.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{LatitudeSource, Region, Sun, SunRegions};
+/// fn setup(mut commands: Commands) {
+///     // 2 km regions on a small planet, with the map starting at 30 degrees north
+///     commands.insert_resource(SunRegions::new(
+///         LatitudeSource::default().with_origin_deg(30.0, 0.0).with_planet_radius(500_000.0),
+///         2000.0,
+///     ));
+///     commands.spawn((DirectionalLight::default(), Sun, Region(IVec2::new(0, 3))));
+/// }
+/// ```
+///
+/// Use [`region_at`](SunRegions::region_at) to pick the region for a position, like when
+/// streaming in a chunk with its own light. The regions follow the [`SunReferenceFrame`].
+///
+/// **Note:** a region's `SunEnvironment` is a copy of the plain [`Environment`], so
+/// [`CinematicOverrides`](crate::CinematicOverrides) don't apply to it, and it shouldn't be used
+/// together with an [`EnvironmentKey`](crate::EnvironmentKey) on the same entity
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunRegions
+{
+    /// How world positions map onto the planet, see [`LatitudeSource`]
+    pub mapping: LatitudeSource,
+
+    /// Width of each square region in world units
+    pub region_size: f32,
+}
+
+impl Default for SunRegions {
+    /// Regions 10 km across on an Earth sized planet, one world unit per meter
+    fn default() -> Self {
+        Self::new(LatitudeSource::default(), 10_000.0)
+    }
+}
+
+impl SunRegions
+{
+    /// Creates a new `SunRegions` grid with `region_size` world unit regions
+    pub const fn new(mapping: LatitudeSource, region_size: f32) -> Self {
+        Self { mapping, region_size }
+    }
+
+    /// Region a world `position` is in
+    pub fn region_at(&self, position: Vec3, frame: &SunReferenceFrame) -> Region {
+        let offset = position - self.mapping.origin;
+        let size = self.region_size.max(f32::EPSILON);
+        Region(IVec2::new(
+            (offset.dot(frame.east) / size).round() as i32,
+            (offset.dot(frame.north) / size).round() as i32,
+        ))
+    }
+
+    /// World position of the middle of `region`
+    pub fn region_center(&self, region: Region, frame: &SunReferenceFrame) -> Vec3 {
+        let cell = region.0.as_vec2() * self.region_size;
+        self.mapping.origin + frame.east * cell.x + frame.north * cell.y
+    }
+
+    /// Copy of `environment` with the latitude and longitude at the middle of `region`
+    pub fn environment_for(&self, region: Region, environment: &Environment, frame: &SunReferenceFrame) -> Environment {
+        let (latitude, longitude) = self.mapping.latitude_longitude(self.region_center(region, frame), frame);
+        let mut regional = environment.with_latitude(latitude);
+        if self.mapping.longitude {
+            regional.longitude = longitude;
+        }
+        regional
+    }
+}
+
+/// Cell of the [`SunRegions`] grid an entity is lit from, counted east and north from the region
+/// at the mapping's origin
+///
+/// Keeps the entity's [`SunEnvironment`] in line with its region while the [`SunRegions`]
+/// resource exists. Removing the component removes the `SunEnvironment` too.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Region(pub IVec2);
+
+/// Copies the [`Environment`] with each region's latitude into entities with a [`Region`]
+pub(crate) fn apply_sun_regions(
+    mut commands: Commands,
+    mut regional: Query<(Entity, Ref<Region>, Option<&mut SunEnvironment>)>,
+    regions: Res<SunRegions>,
+    environment: Res<Environment>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    let globals_changed = regions.is_changed() || environment.is_changed() || reference_frame.is_changed();
+    for (entity, region, sun_environment) in &mut regional {
+        if !(globals_changed || region.is_changed()) {
+            continue;
+        }
+        let regional_environment = regions.environment_for(*region, &environment, &reference_frame);
+        match sun_environment {
+            Some(mut sun_environment) => sun_environment.0 = regional_environment,
+            None => {
+                commands.entity(entity).insert(SunEnvironment(regional_environment));
+            },
+        }
+    }
+}
+
+/// Removes the [`SunEnvironment`] that came from a removed [`Region`]
+pub(crate) fn on_region_removed(remove: On<Remove, Region>, mut commands: Commands) {
+    // the entity may be on its way to being despawned
+    if let Ok(mut entity) = commands.get_entity(remove.entity) {
+        entity.try_remove::<SunEnvironment>();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RealisticSunDirectionPlugin, Sun};
+    use crate::conversion::*;
+
+    #[test]
+    fn northern_region_has_lower_sun() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let regions = SunRegions::new(LatitudeSource::default().with_origin_deg(30.0, 0.0).with_planet_radius(100.0), 10.0);
+        app.insert_resource(regions);
+        assert_eq!(regions.region_at(Vec3::new(4.0, 0.0, -26.0), &SunReferenceFrame::Y_UP), Region(IVec2::new(0, 3)));
+        let south = app.world_mut().spawn((Sun, Region(IVec2::ZERO))).id();
+        let north = app.world_mut().spawn((Sun, Region(IVec2::new(0, 3)))).id();
+        app.update();
+        app.update();
+        let latitude = |sun| app.world().get::<SunEnvironment>(sun).unwrap().0.latitude;
+        assert!((latitude(south) - 30.0 * DEG_TO_RAD).abs() < 1e-5);
+        assert!((latitude(north) - (30.0 * DEG_TO_RAD + 0.3)).abs() < 1e-5);
+        let elevation = |sun| app.world().get::<Transform>(sun).unwrap().back().y;
+        assert!(elevation(north) < elevation(south));
+    }
+}
@@ -0,0 +1,128 @@
+//! Contains the [`SceneEnvironment`] component, for storing the [`Environment`] in scenes along
+//! with the suns
+#[cfg(feature = "scene")]
+use bevy::ecs::query::Or;
+use bevy::prelude::*;
+#[cfg(feature = "scene")]
+use bevy::scene::{DynamicScene, DynamicSceneBuilder};
+use crate::Environment;
+#[cfg(feature = "scene")]
+use crate::Sun;
+
+
+/// Component form of the global [`Environment`] resource, for scenes and editors that only deal
+/// with entities
+///
+/// Inserting it, like when a scene is spawned, copies its settings into the `Environment`. After
+/// that it's kept up to date with the `Environment`, so saving the entity again saves the current
+/// time and place.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{Environment, SceneEnvironment};
+/// fn setup(mut commands: Commands) {
+///     // a scene saved with an entity like this restores the day/night setup when it's loaded
+///     commands.spawn(SceneEnvironment(Environment::default().with_latitude_deg(52.0)));
+/// }
+/// ```
+///
+/// Only one should exist at a time, otherwise the last one inserted wins
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SceneEnvironment(pub Environment);
+
+/// Builds a [`DynamicScene`] with every [`Sun`] and [`SceneEnvironment`] entity in the `world`,
+/// along with the [`Environment`] resource, ready to be saved as a `.scn.ron` file
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::sun_scene;
+/// fn save_suns(world: &World) {
+///     let registry = world.resource::<AppTypeRegistry>().read();
+///     let ron = sun_scene(world).serialize(&registry).unwrap();
+///     std::fs::write("assets/levels/valley.scn.ron", ron).unwrap();
+/// }
+/// ```
+///
+/// Only available with the `scene` feature
+#[cfg(feature = "scene")]
+pub fn sun_scene(world: &World) -> DynamicScene {
+    let entities = world.try_query_filtered::<Entity, Or<(With<Sun>, With<SceneEnvironment>)>>()
+        .map(|mut query| query.iter(world).collect::<Vec<_>>())
+        .unwrap_or_default();
+    DynamicSceneBuilder::from_world(world)
+        .deny_all_resources()
+        .allow_resource::<Environment>()
+        .extract_entities(entities.into_iter())
+        .extract_resources()
+        .build()
+}
+
+/// Copies a newly inserted [`SceneEnvironment`] into the global [`Environment`]
+pub(crate) fn on_scene_environment_inserted(
+    insert: On<Insert, SceneEnvironment>,
+    scene_environments: Query<&SceneEnvironment>,
+    mut commands: Commands,
+){
+    if let Ok(scene_environment) = scene_environments.get(insert.entity) {
+        commands.insert_resource(scene_environment.0);
+    }
+}
+
+/// Keeps every [`SceneEnvironment`] in line with the global [`Environment`]
+pub(crate) fn sync_scene_environments(
+    mut scene_environments: Query<&mut SceneEnvironment>,
+    environment: Res<Environment>,
+){
+    if !environment.is_changed() {
+        return;
+    }
+    for mut scene_environment in &mut scene_environments {
+        scene_environment.0 = *environment;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+    use crate::conversion::*;
+
+    #[test]
+    fn scene_environment_sets_environment() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let entity = app.world_mut().spawn(SceneEnvironment(Environment::default().with_latitude_deg(60.0))).id();
+        app.update();
+        assert!((app.world().resource::<Environment>().latitude * RAD_TO_DEG - 60.0).abs() < 1e-4);
+        app.world_mut().resource_mut::<Environment>().latitude = 0.0;
+        app.update();
+        assert_eq!(app.world().get::<SceneEnvironment>(entity).unwrap().0.latitude, 0.0);
+    }
+
+    #[cfg(feature = "scene")]
+    #[test]
+    fn sun_scene_round_trip() {
+        use bevy::ecs::entity::EntityHashMap;
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default().with_latitude_deg(-30.0));
+        app.world_mut().spawn(Sun);
+        app.update();
+        let scene = sun_scene(app.world());
+        assert_eq!(scene.entities.len(), 1);
+        assert!(scene.serialize(&app.world().resource::<AppTypeRegistry>().read()).is_ok());
+
+        let mut loaded = App::new();
+        loaded.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        scene.write_to_world(loaded.world_mut(), &mut EntityHashMap::default()).unwrap();
+        loaded.update();
+        assert!((loaded.world().resource::<Environment>().latitude * RAD_TO_DEG + 30.0).abs() < 1e-4);
+        let mut suns = loaded.world_mut().query_filtered::<&Transform, With<Sun>>();
+        let sun = *suns.single(loaded.world()).unwrap();
+        assert!(sun.back().is_finite());
+    }
+}
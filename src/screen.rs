@@ -0,0 +1,126 @@
+//! Contains the [`SunScreenPosition`] component that tracks where the sun is on a camera's screen
+use bevy::prelude::*;
+use crate::SunState;
+
+
+/// Tracks where the sun is on a camera's screen, for lens flares, lens dirt and god rays
+/// 
+/// Attach it to a camera with a [`Projection`] and it's updated every frame with the sun's
+/// position in normalized device coordinates and in the viewport, and whether it can be seen.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunScreenPosition;
+/// fn spawn_camera(mut commands: Commands) {
+///     commands.spawn((Camera3d::default(), SunScreenPosition::default()));
+/// }
+/// 
+/// fn lens_flare(cameras: Query<&SunScreenPosition>) {
+///     for sun in &cameras {
+///         if sun.visible {
+///             let strength = sun.facing.powi(8);
+///             println!("Flare at {:?} with strength {strength}", sun.viewport_position);
+///         }
+///     }
+/// }
+/// ```
+/// 
+/// The sun direction comes from the [`SunState`], so it follows the [`Environment`](crate::Environment)
+/// and any [`CinematicOverrides`](crate::CinematicOverrides). The sun is treated as infinitely far
+/// away, so only a perspective projection places it correctly. Doesn't check whether anything in
+/// the scene covers the sun, use a [`SunlightProbe`](crate::SunlightProbe) on the camera for that.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct SunScreenPosition
+{
+    /// Position of the sun in normalized device coordinates, from `-1.0` to `1.0` across the
+    /// screen with `+Y` up. Only meaningful while [`in_front`](SunScreenPosition::in_front)
+    pub ndc: Vec2,
+
+    /// Position of the sun in logical pixels from the top left of the camera's viewport, or
+    /// `None` while it's behind the camera or the viewport size isn't known yet
+    pub viewport_position: Option<Vec2>,
+
+    /// Whether the sun is in front of the camera
+    pub in_front: bool,
+
+    /// Whether the sun is above the horizon
+    pub above_horizon: bool,
+
+    /// Whether the sun is above the horizon, in front of the camera and inside its view
+    pub visible: bool,
+
+    /// How directly the camera is looking at the sun, from `1.0` looking straight at it to
+    /// `-1.0` facing directly away
+    pub facing: f32,
+}
+
+impl SunScreenPosition
+{
+    /// Works out where a sun towards `direction` is for a camera at `camera_transform` with
+    /// `clip_from_view` as its projection matrix
+    pub fn from_direction(direction: Vec3, camera_transform: &GlobalTransform, clip_from_view: Mat4) -> Self {
+        let view_direction = camera_transform.rotation().inverse() * direction;
+        let clip = clip_from_view * view_direction.extend(1.0);
+        let in_front = view_direction.z < 0.0 && clip.w > 0.0;
+        let ndc = if clip.w != 0.0 { clip.truncate().truncate() / clip.w } else { Vec2::ZERO };
+        let above_horizon = direction.y > 0.0;
+        Self {
+            ndc,
+            viewport_position: None,
+            in_front,
+            above_horizon,
+            visible: above_horizon && in_front && ndc.abs().max_element() <= 1.0,
+            facing: camera_transform.forward().dot(direction),
+        }
+    }
+}
+
+/// Updates every [`SunScreenPosition`] from the [`SunState`]
+pub(crate) fn update_sun_screen_positions(
+    mut cameras: Query<(&mut SunScreenPosition, &GlobalTransform, &Projection, Option<&Camera>)>,
+    state: Res<SunState>,
+){
+    for (mut screen_position, camera_transform, projection, camera) in &mut cameras {
+        let mut updated = SunScreenPosition::from_direction(
+            state.direction, camera_transform, projection.get_clip_from_view(),
+        );
+        // the direction is in world space, so the state knows better which way is up
+        updated.above_horizon = state.elevation > 0.0;
+        updated.visible = updated.above_horizon && updated.in_front && updated.ndc.abs().max_element() <= 1.0;
+        if updated.in_front && let Some(size) = camera.and_then(Camera::logical_viewport_size) {
+            updated.viewport_position = Some((updated.ndc * Vec2::new(0.5, -0.5) + 0.5) * size);
+        }
+        screen_position.set_if_neq(updated);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn tracks_sun_on_screen() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_latitude_deg(40.0).with_hours_since_noon(-3.0);
+        app.insert_resource(environment);
+        let to_sun = Transform::default().looking_to(environment.sun_direction(), Vec3::Y);
+        let away = Transform::default().looking_to(-environment.sun_direction(), Vec3::Y);
+        let looking = app.world_mut()
+            .spawn((SunScreenPosition::default(), GlobalTransform::from(to_sun), Projection::default()))
+            .id();
+        let behind = app.world_mut()
+            .spawn((SunScreenPosition::default(), GlobalTransform::from(away), Projection::default()))
+            .id();
+        app.update();
+        let looking = app.world().get::<SunScreenPosition>(looking).unwrap();
+        assert!(looking.visible && looking.ndc.length() < 1e-4, "{looking:?}");
+        assert!((looking.facing - 1.0).abs() < 1e-5);
+        let behind = app.world().get::<SunScreenPosition>(behind).unwrap();
+        assert!(!behind.visible && !behind.in_front);
+    }
+}
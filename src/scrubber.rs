@@ -0,0 +1,256 @@
+//! Contains the [`SunScrubberPlugin`], a drop in panel of sliders for scrubbing through the
+//! environment
+use std::f32::consts::PI;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use crate::Environment;
+use crate::conversion::*;
+use crate::ui;
+
+
+/// Background color of the scrubber panel
+const PANEL_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+/// Color of the empty part of a slider
+const TRACK_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
+/// Color of the filled part of a slider
+const FILL_COLOR: Color = Color::srgb(0.9, 0.7, 0.2);
+/// Size of the font in the scrubber panel
+const FONT_SIZE: f32 = 13.0;
+
+/// Adds a panel of sliders for the time of day, date, latitude and axial tilt, for scrubbing
+/// through the [`Environment`] in editors and dev builds
+///
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{RealisticSunDirectionPlugin, SunScrubberPlugin};
+/// # let mut app = App::new();
+/// app.add_plugins((RealisticSunDirectionPlugin::default(), SunScrubberPlugin));
+/// ```
+///
+/// Click or drag on a slider to set the value, and the sun follows straight away. The panel is
+/// spawned in the bottom left corner at startup, or spawn your own with [`sun_scrubber_panel`].
+/// A [`SolarClock`](crate::SolarClock) or [`TimeFlow`](crate::TimeFlow) keeps advancing the time
+/// while you scrub, so pause it to hold the sun still.
+///
+/// Only available with the `scrubber` feature, which needs Bevy's UI and a window to click in
+pub struct SunScrubberPlugin;
+impl Plugin for SunScrubberPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SunScrubberSlider>()
+            .register_type::<ScrubberParameter>()
+            .add_systems(Startup, spawn_sun_scrubber)
+            .add_systems(Update, (
+                drag_scrubber_sliders.before(crate::SunUpdateSet::AdvanceTime),
+                update_scrubber_sliders.after(crate::SunUpdateSet::AdvanceTime),
+            ).run_if(resource_exists::<Environment>));
+    }
+}
+
+/// [`Environment`] value a [`SunScrubberSlider`] controls
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub enum ScrubberParameter
+{
+    /// [`time_of_day`](Environment::time_of_day), through a whole day from midnight to midnight
+    TimeOfDay,
+    /// [`time_of_year`](Environment::time_of_year), through a whole year from the winter solstice
+    TimeOfYear,
+    /// [`latitude`](Environment::latitude), from the south pole to the north pole
+    Latitude,
+    /// [`axial_tilt`](Environment::axial_tilt), from none to `90` degrees
+    AxialTilt,
+}
+
+impl ScrubberParameter
+{
+    /// Every parameter, in the order they are shown in the panel
+    pub const ALL: [Self; 4] = [Self::TimeOfDay, Self::TimeOfYear, Self::Latitude, Self::AxialTilt];
+
+    /// Name shown next to the slider
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TimeOfDay => "Time of Day",
+            Self::TimeOfYear => "Date",
+            Self::Latitude => "Latitude",
+            Self::AxialTilt => "Axial Tilt",
+        }
+    }
+
+    /// Lowest and highest values of the slider in radians
+    pub const fn range(self) -> (f32, f32) {
+        match self {
+            Self::TimeOfDay | Self::TimeOfYear => (-PI, PI),
+            Self::Latitude => (-PI / 2.0, PI / 2.0),
+            Self::AxialTilt => (0.0, PI / 2.0),
+        }
+    }
+
+    /// Current value of the parameter in `environment`
+    pub fn get(self, environment: &Environment) -> f32 {
+        match self {
+            Self::TimeOfDay => environment.time_of_day,
+            Self::TimeOfYear => environment.time_of_year,
+            Self::Latitude => environment.latitude,
+            Self::AxialTilt => environment.axial_tilt,
+        }
+    }
+
+    /// Sets the parameter in `environment`
+    pub fn set(self, environment: &mut Environment, value: f32) {
+        match self {
+            Self::TimeOfDay => environment.time_of_day = value,
+            Self::TimeOfYear => environment.time_of_year = value,
+            Self::Latitude => environment.latitude = value,
+            Self::AxialTilt => environment.axial_tilt = value,
+        }
+    }
+
+    /// How far along the slider `value` is, from `0.0` to `1.0`
+    pub fn fraction(self, value: f32) -> f32 {
+        let (min, max) = self.range();
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    /// Value at `fraction` of the way along the slider
+    pub fn value_at(self, fraction: f32) -> f32 {
+        let (min, max) = self.range();
+        min + (max - min) * fraction.clamp(0.0, 1.0)
+    }
+
+    /// Text shown next to the slider for `environment`
+    fn label(self, environment: &Environment) -> String {
+        match self {
+            Self::TimeOfDay => ui::clock(environment),
+            Self::TimeOfYear => ui::season_name(environment.season()).to_string(),
+            Self::Latitude | Self::AxialTilt => format!("{:.1}°", self.get(environment) * RAD_TO_DEG),
+        }
+    }
+}
+
+/// A slider that sets one [`ScrubberParameter`] of the [`Environment`] when clicked or dragged
+///
+/// Spawned as part of a [`sun_scrubber_panel`]. The first child is resized to show the value.
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[require(Node, Interaction, RelativeCursorPosition)]
+pub struct SunScrubberSlider(pub ScrubberParameter);
+
+/// Marks the [`Text`] showing the value of a [`ScrubberParameter`]
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+struct ScrubberValueText(ScrubberParameter);
+
+/// A panel in the bottom left corner of the screen with a slider for every [`ScrubberParameter`]
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::sun_scrubber_panel;
+/// fn spawn_scrubber(mut commands: Commands) {
+///     commands.spawn(sun_scrubber_panel());
+/// }
+/// ```
+pub fn sun_scrubber_panel() -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.0),
+            bottom: Val::Px(0.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(5.0)),
+            ..default()
+        },
+        BackgroundColor(PANEL_COLOR),
+        Children::spawn(SpawnIter(ScrubberParameter::ALL.into_iter().map(scrubber_row))),
+    )
+}
+
+/// A row with the name, slider and value of `parameter`
+fn scrubber_row(parameter: ScrubberParameter) -> impl Bundle {
+    let font = TextFont { font_size: FONT_SIZE, ..default() };
+    (
+        Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        },
+        children![
+            (Text::new(parameter.name()), font.clone(), Node { width: Val::Px(80.0), ..default() }),
+            (
+                SunScrubberSlider(parameter),
+                Node { width: Val::Px(160.0), height: Val::Px(10.0), ..default() },
+                BackgroundColor(TRACK_COLOR),
+                children![(Node { height: Val::Percent(100.0), ..default() }, BackgroundColor(FILL_COLOR))],
+            ),
+            (Text::default(), font, ScrubberValueText(parameter)),
+        ],
+    )
+}
+
+/// Spawns the [`sun_scrubber_panel`] for the [`SunScrubberPlugin`]
+fn spawn_sun_scrubber(mut commands: Commands) {
+    commands.spawn(sun_scrubber_panel());
+}
+
+/// Sets the [`Environment`] from any [`SunScrubberSlider`] being dragged
+fn drag_scrubber_sliders(
+    sliders: Query<(&SunScrubberSlider, &Interaction, &RelativeCursorPosition)>,
+    mut environment: ResMut<Environment>,
+){
+    for (slider, interaction, cursor) in &sliders {
+        let (Interaction::Pressed, Some(normalized)) = (interaction, cursor.normalized) else {
+            continue;
+        };
+        // the cursor position is relative to the center of the slider
+        let value = slider.0.value_at(normalized.x + 0.5);
+        if slider.0.get(&environment) != value {
+            slider.0.set(&mut environment, value);
+        }
+    }
+}
+
+/// Keeps the fill and value text of every slider in line with the [`Environment`]
+fn update_scrubber_sliders(
+    sliders: Query<(Ref<SunScrubberSlider>, &Children)>,
+    mut fills: Query<&mut Node>,
+    mut texts: Query<(&mut Text, Ref<ScrubberValueText>)>,
+    environment: Res<Environment>,
+){
+    for (slider, children) in &sliders {
+        if !(environment.is_changed() || slider.is_added()) {
+            continue;
+        }
+        let width = Val::Percent(slider.0.fraction(slider.0.get(&environment)) * 100.0);
+        if let Some(mut fill) = children.first().and_then(|&fill| fills.get_mut(fill).ok())
+            && fill.width != width {
+            fill.width = width;
+        }
+    }
+    for (mut text, value_text) in &mut texts {
+        if !(environment.is_changed() || value_text.is_added()) {
+            continue;
+        }
+        let label = value_text.0.label(&environment);
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slider_fractions() {
+        let parameter = ScrubberParameter::Latitude;
+        assert_eq!(parameter.fraction(0.0), 0.5);
+        assert_eq!(parameter.value_at(1.0), PI / 2.0);
+        // out of range values pin to the ends
+        assert_eq!(parameter.fraction(PI), 1.0);
+        assert_eq!(ScrubberParameter::TimeOfDay.value_at(0.0), -PI);
+    }
+}
@@ -0,0 +1,116 @@
+//! Contains the [`Season`] and [`Hemisphere`] enums for working with the time of year as seasons
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+use crate::Environment;
+
+
+/// Half of the planet on either side of the equator
+/// 
+/// Seasons are opposite in the two hemispheres, so the same
+/// [`time_of_year`](Environment::time_of_year) is summer in one and winter in the other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Hemisphere
+{
+    /// North of the equator, where [`Environment::DATE_SUMMER`] is the summer solstice
+    #[default]
+    Northern,
+    /// South of the equator, where [`Environment::DATE_SUMMER`] is the winter solstice
+    Southern,
+}
+
+impl Hemisphere
+{
+    /// Hemisphere a latitude in radians is in. The equator counts as the northern hemisphere
+    pub fn from_latitude(latitude: f32) -> Self {
+        if latitude < 0.0 { Self::Southern } else { Self::Northern }
+    }
+}
+
+/// Astronomical season, starting at a solstice or equinox
+/// 
+/// ```no_run
+/// # use bevy::ecs::system::Res;
+/// # use kj_bevy_realistic_sun::{Environment, Season};
+/// fn snow_on_the_ground(environment: Res<Environment>) -> bool {
+///     environment.season() == Season::Winter
+/// }
+/// ```
+/// 
+/// Seasons follow the [`Hemisphere`], so they are right for the southern hemisphere too, where
+/// [`Environment::DATE_SUMMER`] is the start of winter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Season
+{
+    /// From the spring equinox to the summer solstice
+    Spring,
+    /// From the summer solstice to the autumn equinox
+    Summer,
+    /// From the autumn equinox to the winter solstice
+    Autumn,
+    /// From the winter solstice to the spring equinox
+    Winter,
+}
+
+impl Season
+{
+    /// Season at a [`time_of_year`](Environment::time_of_year) in radians in `hemisphere`
+    pub fn from_time_of_year(time_of_year: f32, hemisphere: Hemisphere) -> Self {
+        const SEASONS: [Season; 4] = [Season::Winter, Season::Spring, Season::Summer, Season::Autumn];
+        let index = ((time_of_year + PI).rem_euclid(TAU) / (PI / 2.0)) as usize % 4;
+        match hemisphere {
+            Hemisphere::Northern => SEASONS[index],
+            Hemisphere::Southern => SEASONS[index].opposite(),
+        }
+    }
+
+    /// [`time_of_year`](Environment::time_of_year) in radians at the solstice or equinox the
+    /// season starts on in `hemisphere`
+    pub fn start_date(self, hemisphere: Hemisphere) -> f32 {
+        let northern = match hemisphere {
+            Hemisphere::Northern => self,
+            Hemisphere::Southern => self.opposite(),
+        };
+        match northern {
+            Self::Spring => Environment::DATE_SPRING,
+            Self::Summer => Environment::DATE_SUMMER,
+            Self::Autumn => Environment::DATE_AUTUMN,
+            Self::Winter => Environment::DATE_WINTER,
+        }
+    }
+
+    /// Season on the other side of the equator at the same time of year
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Spring => Self::Autumn,
+            Self::Summer => Self::Winter,
+            Self::Autumn => Self::Spring,
+            Self::Winter => Self::Summer,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seasons_flip_across_equator() {
+        let environment = Environment::default()
+            .with_latitude_deg(-33.9)
+            .with_season(Season::Summer, Hemisphere::Southern);
+        assert_eq!(environment.time_of_year, Environment::DATE_WINTER);
+        assert_eq!(environment.season(), Season::Summer);
+        assert_eq!(environment.season_at_latitude_deg(51.5), Season::Winter);
+        for season in [Season::Spring, Season::Summer, Season::Autumn, Season::Winter] {
+            for hemisphere in [Hemisphere::Northern, Hemisphere::Southern] {
+                let date = season.start_date(hemisphere) + 0.1;
+                assert_eq!(Season::from_time_of_year(date, hemisphere), season);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Contains the [`SunShaderGlobalsPlugin`], which uploads the sun to a uniform buffer for custom
+//! shaders
+use bevy::prelude::*;
+use bevy::render::{Render, RenderApp, RenderSystems};
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::render_resource::{ShaderType, UniformBuffer};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use crate::{LightQuality, SunState};
+use crate::conversion::*;
+
+
+/// Uploads the [`SunShaderGlobals`] to the GPU every frame, so custom sky, water and foliage
+/// shaders can read the sun without an extract system of their own
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{RealisticSunDirectionPlugin, SunShaderGlobalsPlugin};
+/// # let mut app = App::new();
+/// app.add_plugins((RealisticSunDirectionPlugin::default(), SunShaderGlobalsPlugin));
+/// ```
+///
+/// The values come from the [`SunState`], so add the plugin after the
+/// [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin). In the render world the
+/// buffer is kept in the [`SunShaderGlobalsBuffer`] resource, ready to add to your own bind
+/// groups. Materials can copy the main world [`SunShaderGlobals`] resource into their own
+/// uniforms instead.
+///
+/// Only available with the `shader_globals` feature
+pub struct SunShaderGlobalsPlugin;
+impl Plugin for SunShaderGlobalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SunShaderGlobals>()
+            .init_resource::<SunShaderGlobals>()
+            .add_plugins(ExtractResourcePlugin::<SunShaderGlobals>::default())
+            .add_systems(PostUpdate, update_sun_shader_globals);
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<SunShaderGlobalsBuffer>()
+            .add_systems(Render, prepare_sun_shader_globals.in_set(RenderSystems::PrepareResources));
+    }
+}
+
+/// The sun as it's laid out for shaders, updated from the [`SunState`] by the
+/// [`SunShaderGlobalsPlugin`]
+///
+/// Matches this WGSL struct:
+///
+/// ```wgsl
+/// struct SunGlobals {
+///     direction: vec3<f32>,
+///     elevation: f32,
+///     azimuth: f32,
+///     day_factor: f32,
+///     day_phase: f32,
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Resource, Reflect, ExtractResource, ShaderType)]
+#[reflect(Resource, Default)]
+pub struct SunShaderGlobals
+{
+    /// Unit vector pointing *towards* the sun in world space, see [`SunState::direction`]
+    pub direction: Vec3,
+
+    /// Angle of the sun above the horizon in radians
+    pub elevation: f32,
+
+    /// Compass direction of the sun in radians, clockwise from north
+    pub azimuth: f32,
+
+    /// `0.0` at night and `1.0` during the day, blending smoothly through the blue hour and golden
+    /// hour from [`LightQuality::BLUE_HOUR_START_DEG`] to [`LightQuality::DAYLIGHT_START_DEG`]
+    pub day_factor: f32,
+
+    /// How far through the solar day it is, see [`SunState::day_phase`]
+    pub day_phase: f32,
+}
+
+impl SunShaderGlobals
+{
+    /// Works out the shader values from the `state` of the sun
+    pub fn from_state(state: &SunState) -> Self {
+        let elevation = state.elevation * RAD_TO_DEG;
+        let t = ((elevation - LightQuality::BLUE_HOUR_START_DEG)
+            / (LightQuality::DAYLIGHT_START_DEG - LightQuality::BLUE_HOUR_START_DEG)).clamp(0.0, 1.0);
+        Self {
+            direction: state.direction,
+            elevation: state.elevation,
+            azimuth: state.azimuth,
+            day_factor: t * t * (3.0 - 2.0 * t),
+            day_phase: state.day_phase,
+        }
+    }
+}
+
+/// Uniform buffer holding the [`SunShaderGlobals`] in the render world
+#[derive(Default)]
+#[derive(Resource)]
+pub struct SunShaderGlobalsBuffer
+{
+    /// The buffer, written during [`RenderSystems::PrepareResources`]. Use
+    /// [`binding`](UniformBuffer::binding) to add it to a bind group
+    pub buffer: UniformBuffer<SunShaderGlobals>,
+}
+
+/// Keeps the [`SunShaderGlobals`] resource in line with the [`SunState`]
+fn update_sun_shader_globals(mut globals: ResMut<SunShaderGlobals>, state: Option<Res<SunState>>) {
+    let Some(state) = state.filter(|state| state.is_changed()) else {
+        return;
+    };
+    globals.set_if_neq(SunShaderGlobals::from_state(&state));
+}
+
+/// Writes the extracted [`SunShaderGlobals`] into the [`SunShaderGlobalsBuffer`]
+fn prepare_sun_shader_globals(
+    globals: Res<SunShaderGlobals>,
+    mut buffer: ResMut<SunShaderGlobalsBuffer>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+){
+    if !(globals.is_changed() || buffer.buffer.buffer().is_none()) {
+        return;
+    }
+    buffer.buffer.set(*globals);
+    buffer.buffer.write_buffer(&render_device, &render_queue);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn follows_sun_state() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default(), SunShaderGlobalsPlugin));
+        app.insert_resource(Environment::default().with_latitude_deg(45.0));
+        app.update();
+        let globals = *app.world().resource::<SunShaderGlobals>();
+        assert_eq!(globals.direction, app.world().resource::<SunState>().direction);
+        assert_eq!(globals.day_factor, 1.0);
+        app.insert_resource(Environment::default().with_latitude_deg(45.0).with_hours_since_noon(12.0));
+        app.update();
+        assert_eq!(app.world().resource::<SunShaderGlobals>().day_factor, 0.0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Contains the [`SunShadowPolicy`] component that turns a sun's shadows off at night
+use bevy::prelude::*;
+use crate::SunReferenceFrame;
+use crate::conversion::*;
+
+
+/// Turns off a sun light's [`shadows_enabled`](DirectionalLight::shadows_enabled) while it's
+/// below the horizon, and back on when it rises, to save the GPU time spent rendering shadow maps
+/// for a light that isn't shining
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{Sun, SunShadowPolicy};
+/// fn spawn_sun(mut commands: Commands) {
+///     commands.spawn((
+///         DirectionalLight { shadows_enabled: true, ..default() },
+///         Sun,
+///         // shadows fade out with the light well before the sun is fully down
+///         SunShadowPolicy::default().with_min_elevation_deg(-2.0),
+///     ));
+/// }
+/// ```
+/// 
+/// Elevation is read from the light's own [`Transform`] every frame, like
+/// [`IlluminanceCurve`](crate::IlluminanceCurve). The shadows only come back on once the sun is
+/// [`hysteresis`](SunShadowPolicy::hysteresis) above [`min_elevation`](SunShadowPolicy::min_elevation),
+/// so they don't flicker while it sits on the horizon.
+/// 
+/// **Note:** while this component is on a light, it owns the light's `shadows_enabled`. Remove it
+/// before turning shadows on or off by hand
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunShadowPolicy
+{
+    /// Elevation in radians below which shadows are turned off. Defaults to `0.0`, the horizon
+    pub min_elevation: f32,
+    /// Radians the sun has to climb past [`min_elevation`](SunShadowPolicy::min_elevation) before
+    /// shadows are turned back on. Defaults to half a degree
+    pub hysteresis: f32,
+    /// Whether the light casts shadows while the sun is up. Defaults to `true`, set to `false`
+    /// to keep shadows off all day
+    pub shadows_when_up: bool,
+}
+
+impl Default for SunShadowPolicy {
+    fn default() -> Self {
+        Self {
+            min_elevation: 0.0,
+            hysteresis: Self::DEFAULT_HYSTERESIS,
+            shadows_when_up: true,
+        }
+    }
+}
+
+impl SunShadowPolicy
+{
+    /// Default [`hysteresis`](SunShadowPolicy::hysteresis) of half a degree
+    pub const DEFAULT_HYSTERESIS: f32 = 0.5 * DEG_TO_RAD;
+
+    /// Sets the elevation in radians below which shadows are turned off
+    pub const fn with_min_elevation(mut self, min_elevation: f32) -> Self {
+        self.min_elevation = min_elevation;
+        self
+    }
+
+    /// Sets the elevation in degrees below which shadows are turned off
+    pub const fn with_min_elevation_deg(self, min_elevation: f32) -> Self {
+        self.with_min_elevation(min_elevation * DEG_TO_RAD)
+    }
+
+    /// Sets how many radians the sun has to climb past the minimum elevation before shadows are
+    /// turned back on
+    pub const fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Sets whether the light casts shadows while the sun is up
+    pub const fn with_shadows_when_up(mut self, shadows_when_up: bool) -> Self {
+        self.shadows_when_up = shadows_when_up;
+        self
+    }
+
+    /// Whether shadows should be on with the sun at `elevation` radians, given whether they're on
+    /// right now
+    pub fn shadows_enabled_at(&self, elevation: f32, currently_enabled: bool) -> bool {
+        let threshold = if currently_enabled { self.min_elevation } else { self.min_elevation + self.hysteresis };
+        self.shadows_when_up && elevation >= threshold
+    }
+}
+
+/// Turns shadows on and off for each light with a [`SunShadowPolicy`], based on the direction the
+/// light is facing
+pub(crate) fn apply_sun_shadow_policies(
+    mut lights: Query<(&SunShadowPolicy, &Transform, &mut DirectionalLight)>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    for (policy, transform, mut light) in &mut lights {
+        let (_, elevation) = direction_to_azimuth_elevation(reference_frame.from_world(-*transform.forward()));
+        let shadows_enabled = policy.shadows_enabled_at(elevation, light.shadows_enabled);
+        if light.shadows_enabled != shadows_enabled {
+            light.shadows_enabled = shadows_enabled;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn shadows_follow_the_sun() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT));
+        let sun = app.world_mut().spawn((
+            DirectionalLight { shadows_enabled: true, ..default() },
+            Sun,
+            SunShadowPolicy::default(),
+        )).id();
+        app.update();
+        assert!(!app.world().get::<DirectionalLight>(sun).unwrap().shadows_enabled);
+        // a quarter of a degree up is still inside the hysteresis
+        app.insert_resource(Environment::default().with_time_of_day(-90.0 * DEG_TO_RAD + 0.25 * DEG_TO_RAD));
+        app.update();
+        assert!(!app.world().get::<DirectionalLight>(sun).unwrap().shadows_enabled);
+        app.insert_resource(Environment::default().with_hours_since_noon(-2.0));
+        app.update();
+        assert!(app.world().get::<DirectionalLight>(sun).unwrap().shadows_enabled);
+    }
+}
@@ -0,0 +1,161 @@
+//! Contains the [`SkyColor`] resource for tinting the sky without an atmosphere
+use bevy::prelude::*;
+use crate::appearance::{sample_curve, to_sorted_radians};
+use crate::conversion::*;
+use crate::{CinematicOverrides, Environment};
+
+
+/// Which of the [`SkyColor`] colors is written to the [`ClearColor`] resource
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Reflect)]
+#[reflect(Default)]
+pub enum SkyClearColor
+{
+    /// The clear color is left alone
+    #[default]
+    Off,
+    /// The clear color follows the [`horizon_color`](SkyColor::horizon_color)
+    Horizon,
+    /// The clear color follows the [`zenith_color`](SkyColor::zenith_color)
+    Zenith,
+}
+
+/// Works out a horizon and zenith sky color from how high the sun is in the sky
+/// 
+/// While this resource exists, [`horizon_color`](SkyColor::horizon_color) and
+/// [`zenith_color`](SkyColor::zenith_color) are updated every frame from the solar elevation in
+/// the [`Environment`], for 2D or stylized games that don't use Bevy's atmosphere but still want
+/// day and night tinting. Feed them into a sky gradient shader, or have the clear color follow
+/// one of them.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use bevy::color::LinearRgba;
+/// # use kj_bevy_realistic_sun::{SkyClearColor, SkyColor};
+/// # let mut app = App::new();
+/// // The default gradients, with the camera background following the horizon color
+/// app.insert_resource(SkyColor::default().with_clear_color(SkyClearColor::Horizon));
+/// // Or a custom zenith gradient
+/// app.insert_resource(SkyColor::default()
+///     .with_zenith_curve(vec![(-10.0, LinearRgba::BLACK), (10.0, LinearRgba::BLUE)]));
+/// ```
+#[derive(Clone, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct SkyColor
+{
+    /// Horizon color as an [elevation curve](crate::SunAppearance#elevation-curves)
+    pub horizon: Vec<(f32, LinearRgba)>,
+
+    /// Zenith color as an [elevation curve](crate::SunAppearance#elevation-curves)
+    pub zenith: Vec<(f32, LinearRgba)>,
+
+    /// Which color to write to the [`ClearColor`] resource, if any
+    pub clear_color: SkyClearColor,
+
+    /// Current color of the sky at the horizon, updated every frame
+    pub horizon_color: LinearRgba,
+
+    /// Current color of the sky straight up, updated every frame
+    pub zenith_color: LinearRgba,
+}
+
+impl Default for SkyColor {
+    /// Near black at night, an orange horizon under a deep blue sky around sunrise and sunset, and
+    /// a pale horizon under a blue sky during the day
+    fn default() -> Self {
+        Self {
+            horizon: vec![
+                (-18.0 * DEG_TO_RAD, Color::srgb(0.01, 0.01, 0.03).into()),
+                (-6.0 * DEG_TO_RAD, Color::srgb(0.2, 0.15, 0.3).into()),
+                (0.0, Color::srgb(0.95, 0.5, 0.25).into()),
+                (10.0 * DEG_TO_RAD, Color::srgb(0.75, 0.85, 0.95).into()),
+            ],
+            zenith: vec![
+                (-18.0 * DEG_TO_RAD, Color::srgb(0.0, 0.0, 0.02).into()),
+                (-6.0 * DEG_TO_RAD, Color::srgb(0.05, 0.07, 0.2).into()),
+                (0.0, Color::srgb(0.2, 0.3, 0.55).into()),
+                (10.0 * DEG_TO_RAD, Color::srgb(0.25, 0.5, 0.9).into()),
+            ],
+            clear_color: SkyClearColor::Off,
+            horizon_color: LinearRgba::BLACK,
+            zenith_color: LinearRgba::BLACK,
+        }
+    }
+}
+
+impl SkyColor
+{
+    /// Replaces the horizon gradient with `(elevation in degrees, color)` keys
+    pub fn with_horizon_curve(mut self, keys: Vec<(f32, LinearRgba)>) -> Self {
+        self.horizon = to_sorted_radians(keys);
+        self
+    }
+
+    /// Replaces the zenith gradient with `(elevation in degrees, color)` keys
+    pub fn with_zenith_curve(mut self, keys: Vec<(f32, LinearRgba)>) -> Self {
+        self.zenith = to_sorted_radians(keys);
+        self
+    }
+
+    /// Sets which color to write to the [`ClearColor`] resource
+    pub fn with_clear_color(mut self, clear_color: SkyClearColor) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Horizon and zenith colors at `elevation` in radians. Empty gradients give black
+    pub fn colors_at(&self, elevation: f32) -> (LinearRgba, LinearRgba) {
+        let sample = |curve: &[(f32, LinearRgba)]| {
+            sample_curve(curve, elevation, |from, to, t| from.mix(to, t)).unwrap_or(LinearRgba::BLACK)
+        };
+        (sample(&self.horizon), sample(&self.zenith))
+    }
+}
+
+/// Updates the [`SkyColor`] colors, and the [`ClearColor`] if it is set to follow one of them
+pub(crate) fn update_sky_color(
+    mut sky_color: ResMut<SkyColor>,
+    clear_color: Option<ResMut<ClearColor>>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let elevation = cinematic_overrides.resolve(&environment).solar_elevation();
+    let (horizon_color, zenith_color) = sky_color.colors_at(elevation);
+    if sky_color.horizon_color != horizon_color || sky_color.zenith_color != zenith_color {
+        sky_color.horizon_color = horizon_color;
+        sky_color.zenith_color = zenith_color;
+    }
+    let color = match sky_color.clear_color {
+        SkyClearColor::Off => return,
+        SkyClearColor::Horizon => Color::from(horizon_color),
+        SkyClearColor::Zenith => Color::from(zenith_color),
+    };
+    if let Some(mut clear_color) = clear_color && clear_color.0 != color {
+        clear_color.0 = color;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn follows_elevation() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.init_resource::<ClearColor>();
+        app.insert_resource(SkyColor::default().with_clear_color(SkyClearColor::Zenith));
+        app.update();
+        let sky_color = app.world().resource::<SkyColor>();
+        let day_zenith = sky_color.zenith_color;
+        assert_eq!(day_zenith, Color::srgb(0.25, 0.5, 0.9).into());
+        assert_eq!(app.world().resource::<ClearColor>().0, Color::from(day_zenith));
+        app.insert_resource(Environment::default().with_time_of_day(Environment::TIME_MIDNIGHT));
+        app.update();
+        let sky_color = app.world().resource::<SkyColor>();
+        assert_eq!(sky_color.horizon_color, Color::srgb(0.01, 0.01, 0.03).into());
+    }
+}
@@ -0,0 +1,151 @@
+//! Contains the [`SunSmoothing`] resource that eases suns to their new direction after a jump
+use bevy::prelude::*;
+use crate::Sun;
+use crate::conversion::*;
+
+
+/// Eases every [`Sun`] to its new direction when the [`Environment`](crate::Environment) jumps,
+/// instead of snapping
+/// 
+/// While this resource exists, any frame where a sun's direction changes by more than
+/// [`threshold`](SunSmoothing::threshold) starts a transition that turns the sun from where it was
+/// to where it should be over [`duration`](SunSmoothing::duration) seconds. This hides the
+/// lighting pop from loading a save, fast travel or sleeping until morning, while the normal
+/// frame to frame movement of the sun is left alone.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::SunSmoothing;
+/// # let mut app = App::new();
+/// // Any jump of more than 10 degrees takes 3 seconds to settle
+/// app.insert_resource(SunSmoothing::default()
+///     .with_threshold_deg(10.0)
+///     .with_duration(3.0));
+/// ```
+/// 
+/// **Note:** a sun that moves faster than the threshold every frame, like with a very fast
+/// [`TimeFlow`](crate::TimeFlow), will keep restarting its transition and lag behind
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct SunSmoothing
+{
+    /// Seconds a transition takes
+    pub duration: f32,
+
+    /// Smallest change in direction in a single frame that starts a transition, in radians
+    pub threshold: f32,
+}
+
+impl Default for SunSmoothing {
+    fn default() -> Self {
+        Self {
+            duration: 1.5,
+            threshold: 5.0 * DEG_TO_RAD,
+        }
+    }
+}
+
+impl SunSmoothing
+{
+    /// Sets how long a transition takes in seconds
+    pub const fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets the smallest change in direction that starts a transition in radians
+    pub const fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the smallest change in direction that starts a transition in degrees
+    pub const fn with_threshold_deg(self, threshold: f32) -> Self {
+        self.with_threshold(threshold * DEG_TO_RAD)
+    }
+}
+
+/// Rotation a [`Sun`] was last shown at, and the rotation and progress of a running transition
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub(crate) struct SunSmoothingState
+{
+    shown: Quat,
+    transition: Option<(Quat, f32)>,
+}
+
+/// Eases suns from the rotation they were shown at last frame to the rotation just written by
+/// [`update_sun_lights`](crate::update_sun_lights)
+pub(crate) fn smooth_sun_transitions(
+    mut commands: Commands,
+    mut suns: Query<(Entity, &mut Transform, Option<&mut SunSmoothingState>), With<Sun>>,
+    smoothing: Res<SunSmoothing>,
+    time: Res<Time>,
+){
+    for (entity, mut transform, state) in &mut suns {
+        let target = transform.rotation;
+        let Some(mut state) = state else {
+            commands.entity(entity).insert(SunSmoothingState { shown: target, transition: None });
+            continue;
+        };
+        if state.shown == target && state.transition.is_none() {
+            continue;
+        }
+        if state.transition.is_none() && state.shown.angle_between(target) > smoothing.threshold {
+            state.transition = Some((state.shown, 0.0));
+        }
+        let rotation = match state.transition.as_mut() {
+            Some((from, elapsed)) => {
+                *elapsed += time.delta_secs();
+                let t = (*elapsed / smoothing.duration.max(f32::EPSILON)).min(1.0);
+                if t >= 1.0 {
+                    state.transition = None;
+                    target
+                } else {
+                    from.slerp(target, t * t * (3.0 - 2.0 * t))
+                }
+            },
+            None => target,
+        };
+        state.shown = rotation;
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use bevy::time::TimeUpdateStrategy;
+    use crate::{Environment, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn eases_after_jump() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(0.5)));
+        app.insert_resource(SunSmoothing::default().with_duration(1.0));
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        app.update();
+        let morning = app.world().get::<Transform>(sun).unwrap().rotation;
+        app.insert_resource(Environment::default().with_hours_since_noon(3.0));
+        let afternoon = Transform::default()
+            .looking_to(-Environment::default().with_hours_since_noon(3.0).sun_direction(), Vec3::Y)
+            .rotation;
+        let mut angles = Vec::new();
+        for _ in 0..4 {
+            app.update();
+            angles.push(app.world().get::<Transform>(sun).unwrap().rotation.angle_between(afternoon));
+        }
+        let jump = morning.angle_between(afternoon);
+        assert!(angles[0] > 0.1 * jump && angles[0] < jump, "{:?}", angles);
+        assert!(angles[1] < angles[0], "{:?}", angles);
+        assert!(angles[3] < 1e-4, "{:?}", angles);
+    }
+}
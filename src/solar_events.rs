@@ -0,0 +1,196 @@
+//! Contains the [`SunriseEvent`], [`SunsetEvent`], [`SolarNoonEvent`] and [`SolarMidnightEvent`]
+//! events, the polar day and night events, and the system that triggers them
+use std::f32::consts::PI;
+use bevy::prelude::*;
+use crate::Environment;
+use crate::conversion::wrap_angle;
+
+
+/// Triggered when the sun rises above the horizon
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunriseEvent;
+/// # let mut app = App::new();
+/// app.add_observer(|_: On<SunriseEvent>| {
+///     println!("Time to turn the streetlights off");
+/// });
+/// ```
+/// 
+/// The horizon is the flat plane through the origin, so this fires when the sun's elevation goes
+/// from zero or below to above zero
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct SunriseEvent;
+
+/// Triggered when the sun sets below the horizon
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunsetEvent;
+/// # let mut app = App::new();
+/// app.add_observer(|_: On<SunsetEvent>| {
+///     println!("Time to turn the streetlights on");
+/// });
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct SunsetEvent;
+
+/// Triggered when the time of day passes solar noon, where the sun is at its highest
+/// 
+/// **Note:** this fires even if the sun stays below the horizon all day, like during the polar night
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct SolarNoonEvent;
+
+/// Triggered when the time of day passes solar midnight, where the sun is at its lowest
+/// 
+/// **Note:** this fires even if the sun stays above the horizon all day, like during the midnight
+/// sun
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct SolarMidnightEvent;
+
+/// Triggered when the time of year reaches days where the sun never sets, the midnight sun
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{PolarDayEndEvent, PolarDayStartEvent};
+/// # let mut app = App::new();
+/// app.add_observer(|_: On<PolarDayStartEvent>| {
+///     println!("The midnight sun is here, nobody is getting any sleep");
+/// });
+/// app.add_observer(|_: On<PolarDayEndEvent>| {
+///     println!("The sun sets for the first time in weeks");
+/// });
+/// ```
+/// 
+/// Checked every frame with [`Environment::is_polar_day`] as the time of year changes. Not
+/// triggered for the starting [`Environment`], so check `is_polar_day` when loading in
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct PolarDayStartEvent;
+
+/// Triggered when the midnight sun ends and the sun sets again, see [`PolarDayStartEvent`]
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct PolarDayEndEvent;
+
+/// Triggered when the time of year reaches days where the sun never rises, the polar night
+/// 
+/// See [`PolarDayStartEvent`] for an example, and [`Environment::is_polar_night`]
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct PolarNightStartEvent;
+
+/// Triggered when the polar night ends and the sun rises again, see [`PolarNightStartEvent`]
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct PolarNightEndEvent;
+
+/// Sun elevation and solar time of day, and whether it was polar day and polar night, from the
+/// last time solar events were checked
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Resource)]
+pub(crate) struct SolarEventTracker
+{
+    previous: Option<(f32, f32)>,
+    previous_polar: Option<(bool, bool)>,
+}
+
+/// Whether moving `delta` radians from `from` passes through `target`, in either direction
+fn crosses(from: f32, delta: f32, target: f32) -> bool {
+    let start = wrap_angle(from - target);
+    let end = start + delta;
+    (start < 0.0 && end >= 0.0) || (start >= 0.0 && end < 0.0)
+}
+
+/// Triggers solar events when the sun in the [`Environment`] crosses the horizon, noon or midnight
+pub(crate) fn trigger_solar_events(
+    mut commands: Commands,
+    mut tracker: ResMut<SolarEventTracker>,
+    environment: Res<Environment>,
+){
+    let polar = (environment.is_polar_day(), environment.is_polar_night());
+    if let Some((was_polar_day, was_polar_night)) = tracker.previous_polar.replace(polar) {
+        match (was_polar_day, polar.0) {
+            (false, true) => commands.trigger(PolarDayStartEvent),
+            (true, false) => commands.trigger(PolarDayEndEvent),
+            _ => {},
+        }
+        match (was_polar_night, polar.1) {
+            (false, true) => commands.trigger(PolarNightStartEvent),
+            (true, false) => commands.trigger(PolarNightEndEvent),
+            _ => {},
+        }
+    }
+    let elevation = environment.solar_elevation();
+    let time_of_day = environment.solar_time_of_day();
+    let Some((previous_elevation, previous_time_of_day)) = tracker.previous.replace((elevation, time_of_day)) else {
+        return;
+    };
+    if previous_elevation <= 0.0 && elevation > 0.0 {
+        commands.trigger(SunriseEvent);
+    } else if previous_elevation > 0.0 && elevation <= 0.0 {
+        commands.trigger(SunsetEvent);
+    }
+    // shortest way around, so wrapping time values don't count as a whole day passing
+    let delta = wrap_angle(time_of_day - previous_time_of_day);
+    if delta == 0.0 {
+        return;
+    }
+    if crosses(previous_time_of_day, delta, 0.0) {
+        commands.trigger(SolarNoonEvent);
+    }
+    if crosses(previous_time_of_day, delta, PI) {
+        commands.trigger(SolarMidnightEvent);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[derive(Default, Resource)]
+    struct Triggered(Vec<&'static str>);
+
+    #[test]
+    fn events_over_a_day() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.init_resource::<Triggered>();
+        app.add_observer(|_: On<SunriseEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("rise"));
+        app.add_observer(|_: On<SunsetEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("set"));
+        app.add_observer(|_: On<SolarNoonEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("noon"));
+        app.add_observer(|_: On<SolarMidnightEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("midnight"));
+        // equator with no tilt, so the sun rises at -PI/2 and sets at PI/2
+        for time_of_day in [-FRAC_PI_2 - 0.1, -FRAC_PI_2 + 0.1, 0.1, FRAC_PI_2 + 0.1, -PI + 0.1] {
+            app.insert_resource(Environment::default().with_time_of_day(time_of_day));
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Triggered>().0, ["rise", "noon", "set", "midnight"]);
+    }
+
+    #[test]
+    fn polar_events_over_a_year() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.init_resource::<Triggered>();
+        app.add_observer(|_: On<PolarDayStartEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("day start"));
+        app.add_observer(|_: On<PolarDayEndEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("day end"));
+        app.add_observer(|_: On<PolarNightStartEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("night start"));
+        app.add_observer(|_: On<PolarNightEndEvent>, mut triggered: ResMut<Triggered>| triggered.0.push("night end"));
+        let arctic = Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(85.0);
+        for date in [Environment::DATE_SPRING, 0.0, Environment::DATE_AUTUMN, Environment::DATE_WINTER, Environment::DATE_SPRING] {
+            app.insert_resource(arctic.with_date(date));
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Triggered>().0, ["day start", "day end", "night start", "night end"]);
+    }
+}
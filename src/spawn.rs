@@ -0,0 +1,125 @@
+//! Contains [`SunConfig`] and the [`SpawnSunExt`] extension trait for spawning a ready to use sun
+use bevy::light::light_consts::lux;
+use bevy::light::SunDisk;
+use bevy::prelude::*;
+use crate::Sun;
+
+
+/// Settings for the sun light spawned by [`SpawnSunExt::spawn_realistic_sun`]
+/// 
+/// The default is a shadow casting light as bright as direct sunlight, with Earth's
+/// [`SunDisk`] for Bevy's atmosphere.
+#[derive(Clone)]
+pub struct SunConfig
+{
+    /// Illuminance of the light in lux
+    pub illuminance: f32,
+
+    /// Whether the light casts shadows
+    pub shadows_enabled: bool,
+
+    /// Disk drawn for the sun by Bevy's atmosphere, or `None` to leave it out
+    pub sun_disk: Option<SunDisk>,
+}
+
+impl Default for SunConfig {
+    fn default() -> Self {
+        Self {
+            illuminance: lux::DIRECT_SUNLIGHT,
+            shadows_enabled: true,
+            sun_disk: Some(SunDisk::EARTH),
+        }
+    }
+}
+
+impl std::fmt::Debug for SunConfig {
+    /// Written by hand since [`SunDisk`] doesn't implement `Debug`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SunConfig")
+            .field("illuminance", &self.illuminance)
+            .field("shadows_enabled", &self.shadows_enabled)
+            .field("sun_disk", &self.sun_disk.as_ref().map(|disk| (disk.angular_size, disk.intensity)))
+            .finish()
+    }
+}
+
+impl SunConfig
+{
+    /// Sets the illuminance of the light in lux
+    pub const fn with_illuminance(mut self, illuminance: f32) -> Self {
+        self.illuminance = illuminance;
+        self
+    }
+
+    /// Sets whether the light casts shadows
+    pub const fn with_shadows(mut self, shadows_enabled: bool) -> Self {
+        self.shadows_enabled = shadows_enabled;
+        self
+    }
+
+    /// Sets the disk drawn for the sun by Bevy's atmosphere, or `None` to leave it out
+    pub fn with_sun_disk(mut self, sun_disk: Option<SunDisk>) -> Self {
+        self.sun_disk = sun_disk;
+        self
+    }
+}
+
+/// Adds [`spawn_realistic_sun`](SpawnSunExt::spawn_realistic_sun) to [`Commands`]
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{SpawnSunExt, SunConfig, SunAppearance};
+/// fn setup(mut commands: Commands) {
+///     // A directional light with shadows, a sun disk and the `Sun` marker
+///     commands.spawn_realistic_sun(SunConfig::default());
+///     // Returns the `EntityCommands`, so more components can be added
+///     commands.spawn_realistic_sun(SunConfig::default().with_shadows(false))
+///         .insert(SunAppearance::default());
+/// }
+/// ```
+pub trait SpawnSunExt
+{
+    /// Spawns a [`DirectionalLight`] with a [`Sun`] component, set up with `config`
+    fn spawn_realistic_sun(&mut self, config: SunConfig) -> EntityCommands<'_>;
+}
+
+impl SpawnSunExt for Commands<'_, '_> {
+    fn spawn_realistic_sun(&mut self, config: SunConfig) -> EntityCommands<'_> {
+        let mut sun = self.spawn((
+            DirectionalLight {
+                illuminance: config.illuminance,
+                shadows_enabled: config.shadows_enabled,
+                ..default()
+            },
+            Sun,
+        ));
+        if let Some(sun_disk) = config.sun_disk {
+            sun.insert(sun_disk);
+        }
+        sun
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::world::CommandQueue;
+
+    #[test]
+    fn spawns_configured_sun() {
+        let mut world = World::new();
+        let mut command_queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut command_queue, &world);
+        let sun = commands.spawn_realistic_sun(SunConfig::default()).id();
+        let plain = commands.spawn_realistic_sun(SunConfig::default().with_shadows(false).with_sun_disk(None)).id();
+        command_queue.apply(&mut world);
+        let light = world.get::<DirectionalLight>(sun).unwrap();
+        assert!(light.shadows_enabled);
+        assert_eq!(light.illuminance, lux::DIRECT_SUNLIGHT);
+        assert!(world.get::<Sun>(sun).is_some());
+        assert!(world.get::<SunDisk>(sun).is_some());
+        assert!(!world.get::<DirectionalLight>(plain).unwrap().shadows_enabled);
+        assert!(world.get::<SunDisk>(plain).is_none());
+    }
+}
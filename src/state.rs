@@ -0,0 +1,101 @@
+//! Contains the [`SunState`] resource, which publishes where the sun is without needing any lights
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, SunReferenceFrame};
+
+
+/// Where the sun is this frame, worked out from the [`Environment`]
+///
+/// Updated by the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin) every time
+/// the environment changes, so gameplay code can read the sun's position without an entity with a
+/// [`Sun`](crate::Sun) component. Includes any active [`CinematicOverrides`].
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunState;
+/// fn grow_crops(sun: Res<SunState>) {
+///     if sun.elevation > 0.0 {
+///         println!("The crops are growing, {:.0}% of the way through the day", sun.day_phase * 100.0);
+///     }
+/// }
+/// ```
+///
+/// Also kept up to date by a [`headless`](crate::RealisticSunDirectionPlugin::headless) plugin,
+/// which is the only way to get the sun position on a dedicated server
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SunState
+{
+    /// Unit vector pointing from the world *towards* the sun, see [`Environment::sun_direction`].
+    /// In world space, so it follows the [`SunReferenceFrame`]
+    pub direction: Vec3,
+
+    /// Angle of the sun above the horizon in radians, see [`Environment::solar_elevation`]
+    pub elevation: f32,
+
+    /// Compass direction of the sun in radians, see [`Environment::solar_azimuth`]
+    pub azimuth: f32,
+
+    /// How far through the solar day it is, from `0.0` at solar midnight through `0.5` at solar
+    /// noon to just under `1.0` at the next midnight
+    pub day_phase: f32,
+
+    /// Whether the sun is above the environment's horizon offset, see
+    /// [`Environment::is_daytime`]
+    pub is_daytime: bool,
+}
+
+impl SunState
+{
+    /// Works out the state of the sun in `environment`
+    pub fn from_environment(environment: &Environment) -> Self {
+        Self {
+            direction: environment.sun_direction(),
+            elevation: environment.solar_elevation(),
+            azimuth: environment.solar_azimuth(),
+            day_phase: (environment.solar_time_of_day() + PI).rem_euclid(TAU) / TAU,
+            is_daytime: environment.is_daytime(),
+        }
+    }
+}
+
+/// Keeps the [`SunState`] resource in line with the [`Environment`]
+pub(crate) fn update_sun_state(
+    mut state: ResMut<SunState>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+    reference_frame: Res<SunReferenceFrame>,
+){
+    if !(environment.is_changed() || cinematic_overrides.is_changed() || reference_frame.is_changed()) {
+        return;
+    }
+    let mut updated = SunState::from_environment(&cinematic_overrides.resolve(&environment));
+    updated.direction = reference_frame.to_world(updated.direction);
+    state.set_if_neq(updated);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn headless_publishes_state() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::headless()));
+        let environment = Environment::default()
+            .with_latitude_deg(45.0)
+            .with_hours_since_noon(-6.0);
+        app.insert_resource(environment);
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        let state = *app.world().resource::<SunState>();
+        assert!(state.direction.abs_diff_eq(environment.sun_direction(), 1e-6));
+        assert!((state.day_phase - 0.25).abs() < 1e-5, "{}", state.day_phase);
+        // lights are left alone
+        assert_eq!(app.world().get::<Transform>(sun).unwrap().rotation, Quat::IDENTITY);
+    }
+}
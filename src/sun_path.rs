@@ -0,0 +1,184 @@
+//! Contains [`SunPath`] for authoring custom sun paths, and [`StylizedSunPath`] for blending one
+//! with the realistic path
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::conversion::*;
+
+
+/// A single point on a [`SunPath`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Reflect)]
+pub struct SunPathKey
+{
+    /// Local solar time of day in radians, in the same format as
+    /// [`Environment::solar_time_of_day`](crate::Environment::solar_time_of_day)
+    pub time_of_day: f32,
+    /// World space direction *towards* the sun at this time of day
+    pub direction: Vec3,
+}
+
+/// A custom sun path authored as a looping spline of directions over the day
+/// 
+/// ```no_run
+/// # use kj_bevy_realistic_sun::SunPath;
+/// // A low, dramatic sun that rises in the north east and sets in the north west
+/// let path = SunPath::default()
+///     .with_key_deg(-6.0, 60.0, 0.0)
+///     .with_key_deg(0.0, 180.0, 25.0)
+///     .with_key_deg(6.0, 300.0, 0.0)
+///     .with_key_deg(12.0, 0.0, -20.0);
+/// ```
+/// 
+/// Keys are joined with a smooth curve that loops back around from the last key of the day to
+/// the first, so the sun doesn't snap at midnight.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Reflect)]
+#[reflect(Default)]
+pub struct SunPath
+{
+    keys: Vec<SunPathKey>,
+}
+
+impl SunPath
+{
+    /// Creates a new `SunPath` from a list of keys in any order
+    pub fn new(keys: impl IntoIterator<Item = SunPathKey>) -> Self {
+        let mut path = Self::default();
+        for key in keys {
+            path.insert(key);
+        }
+        path
+    }
+
+    /// Adds a key at `time_of_day` in radians with a `direction` towards the sun
+    pub fn with_key(mut self, time_of_day: f32, direction: Vec3) -> Self {
+        self.insert(SunPathKey { time_of_day, direction });
+        self
+    }
+
+    /// Adds a key at some hours since local solar noon, with the sun at a compass azimuth
+    /// (clockwise from north) and elevation above the horizon in degrees
+    pub fn with_key_deg(self, hours_since_noon: f32, azimuth: f32, elevation: f32) -> Self {
+        let direction = azimuth_elevation_to_direction(azimuth * DEG_TO_RAD, elevation * DEG_TO_RAD);
+        self.with_key(hours_since_noon * HOURS_TO_RAD, direction)
+    }
+
+    /// Adds a key, keeping the keys sorted by time of day
+    pub fn insert(&mut self, mut key: SunPathKey) {
+        key.time_of_day = wrap_angle(key.time_of_day);
+        key.direction = key.direction.normalize_or(Vec3::Y);
+        let index = self.keys.partition_point(|other| other.time_of_day <= key.time_of_day);
+        self.keys.insert(index, key);
+    }
+
+    /// Keys in the path, sorted by time of day
+    pub fn keys(&self) -> &[SunPathKey] {
+        &self.keys
+    }
+
+    /// Direction towards the sun at `time_of_day` in radians, or `None` if the path has no keys
+    pub fn sample(&self, time_of_day: f32) -> Option<Vec3> {
+        let count = self.keys.len();
+        match count {
+            0 => return None,
+            1 => return Some(self.keys[0].direction),
+            _ => {},
+        }
+        let time_of_day = wrap_angle(time_of_day);
+        // key at or before the time, looping back around to the last key before the first one
+        let next = self.keys.partition_point(|key| key.time_of_day <= time_of_day) % count;
+        let current = (next + count - 1) % count;
+        let start = self.keys[current].time_of_day;
+        let span = (self.keys[next].time_of_day - start).rem_euclid(TAU);
+        let t = if span > 0.0 { (time_of_day - start).rem_euclid(TAU) / span } else { 0.0 };
+        let p0 = self.keys[(current + count - 1) % count].direction;
+        let p1 = self.keys[current].direction;
+        let p2 = self.keys[next].direction;
+        let p3 = self.keys[(next + 1) % count].direction;
+        // uniform catmull-rom spline
+        let point = 0.5 * (
+            2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t
+        );
+        Some(point.try_normalize().unwrap_or(p1))
+    }
+}
+
+/// Blends the realistic sun path with an authored [`SunPath`]
+/// 
+/// While this resource exists, every [`Sun`](crate::Sun) is pointed somewhere between where the
+/// realistic sun is and where the authored path says it should be at the current time of day. A
+/// [`blend`](StylizedSunPath::blend) of `0.0` is fully realistic and `1.0` is fully authored.
+/// Everything else (the clock, events, and queries) keeps running off the realistic
+/// [`Environment`](crate::Environment) so stylized projects still get correct timing.
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::{StylizedSunPath, SunPath};
+/// # let mut app = App::new();
+/// let path = SunPath::default()
+///     .with_key_deg(-6.0, 90.0, 0.0)
+///     .with_key_deg(0.0, 180.0, 40.0)
+///     .with_key_deg(6.0, 270.0, 0.0);
+/// // Mostly the authored path, with a little realism mixed in
+/// app.insert_resource(StylizedSunPath::new(path).with_blend(0.8));
+/// ```
+/// 
+/// Swap or change this resource when a level loads to give each level its own amount of
+/// stylization.
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct StylizedSunPath
+{
+    /// The authored path
+    pub path: SunPath,
+    /// How far to blend from the realistic path (`0.0`) to the authored path (`1.0`)
+    pub blend: f32,
+}
+
+impl StylizedSunPath
+{
+    /// Creates a new `StylizedSunPath` fully blended to `path`
+    pub fn new(path: SunPath) -> Self {
+        Self { path, blend: 1.0 }
+    }
+
+    /// Sets the blend factor, clamped to `0.0..=1.0`
+    pub fn with_blend(mut self, blend: f32) -> Self {
+        self.blend = blend.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Blends a direction the realistic sun light travels in towards the authored path
+    pub(crate) fn blend_light_direction(&self, light_direction: Vec3, time_of_day: f32) -> Vec3 {
+        match self.path.sample(time_of_day) {
+            Some(to_sun) => light_direction.slerp(-to_sun, self.blend.clamp(0.0, 1.0)),
+            None => light_direction,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_through_keys() {
+        let path = SunPath::default()
+            .with_key_deg(6.0, 270.0, 0.0)
+            .with_key_deg(-6.0, 90.0, 0.0)
+            .with_key_deg(0.0, 180.0, 40.0);
+        for key in path.keys() {
+            let sampled = path.sample(key.time_of_day).unwrap();
+            assert!(sampled.abs_diff_eq(key.direction, 1e-5), "{:?} != {:?}", sampled, key.direction);
+        }
+        // in between keys is somewhere in between
+        let morning = path.sample(-3.0 * HOURS_TO_RAD).unwrap();
+        assert!(morning.x > 0.0 && morning.y > 0.0 && morning.z > 0.0, "{:?}", morning);
+        assert_eq!(SunPath::default().sample(0.0), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Contains the [`SunlightProbe`] component, which checks whether a point is in direct sunlight
+//! using a raycast provided by the [`SunlightOcclusion`] resource
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, SunReferenceFrame};
+
+
+/// Ray cast towards the sun from a [`SunlightProbe`], passed to the [`SunlightOcclusion`] system
+#[derive(Clone, Copy, Debug)]
+pub struct SunlightRay
+{
+    /// Probe entity the ray was cast for
+    pub entity: Entity,
+    /// World space point the ray starts at
+    pub origin: Vec3,
+    /// Direction *towards* the sun
+    pub to_sun: Dir3,
+}
+
+/// The system [`SunlightProbe`]s use to check if something blocks the sun
+/// 
+/// Holds a one-shot system that takes a [`SunlightRay`] and returns `true` if anything is in the
+/// way, usually by casting the ray with your physics engine's spatial query. Probes aren't updated
+/// while this resource doesn't exist.
+/// 
+/// ```no_run
+/// # use bevy::math::bounding::{Aabb3d, RayCast3d};
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::{SunlightOcclusion, SunlightRay};
+/// #[derive(Component)]
+/// struct Wall(Aabb3d);
+/// 
+/// fn is_occluded(In(ray): In<SunlightRay>, walls: Query<&Wall>) -> bool {
+///     let ray_cast = RayCast3d::new(ray.origin, ray.to_sun, 500.0);
+///     walls.iter().any(|wall| ray_cast.aabb_intersection_at(&wall.0).is_some())
+/// }
+/// 
+/// fn setup(mut commands: Commands) {
+///     let system = commands.register_system(is_occluded);
+///     commands.insert_resource(SunlightOcclusion(system));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource)]
+pub struct SunlightOcclusion(pub SystemId<In<SunlightRay>, bool>);
+
+/// Checks every frame whether its entity is in direct sunlight
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SunlightProbe;
+/// #[derive(Component)]
+/// struct Vampire;
+/// 
+/// fn burn_vampires(vampires: Query<(Entity, &SunlightProbe), With<Vampire>>) {
+///     for (vampire, probe) in &vampires {
+///         if probe.in_sunlight {
+///             println!("{} is burning", vampire);
+///         }
+///     }
+/// }
+/// ```
+/// 
+/// The ray is cast from the entity's [`GlobalTransform`] translation plus
+/// [`offset`](SunlightProbe::offset), using the [`SunlightOcclusion`] system. While the sun isn't
+/// up according to [`Environment::is_daytime`], the probe is out of the sun without any rays cast.
+/// The result is cached in [`in_sunlight`](SunlightProbe::in_sunlight) so any number of systems
+/// can read it for free.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct SunlightProbe
+{
+    /// Offset from the entity's position to cast the ray from, in world space, like the height of
+    /// a character's head
+    pub offset: Vec3,
+
+    /// Whether the point was in direct sunlight the last time probes were updated
+    pub in_sunlight: bool,
+}
+
+impl SunlightProbe
+{
+    /// Creates a new `SunlightProbe` casting its ray from `offset` away from the entity
+    pub const fn with_offset(offset: Vec3) -> Self {
+        Self { offset, in_sunlight: false }
+    }
+}
+
+/// Casts a ray towards the sun for every [`SunlightProbe`] using the [`SunlightOcclusion`] system
+pub(crate) fn update_sunlight_probes(world: &mut World) {
+    let Some(SunlightOcclusion(is_occluded)) = world.get_resource::<SunlightOcclusion>().copied() else {
+        return;
+    };
+    let environment = world.resource::<CinematicOverrides>().resolve(world.resource::<Environment>());
+    let to_sun = world.resource::<SunReferenceFrame>().to_world(environment.sun_direction());
+    let to_sun = Dir3::new(to_sun).ok().filter(|_| environment.is_daytime());
+    let mut probes = world.query::<(Entity, &SunlightProbe, &GlobalTransform)>();
+    let probes: Vec<_> = probes.iter(world)
+        .map(|(entity, probe, transform)| (entity, transform.translation() + probe.offset))
+        .collect();
+    for (entity, origin) in probes {
+        let in_sunlight = to_sun.is_some_and(|to_sun| {
+            let ray = SunlightRay { entity, origin, to_sun };
+            !world.run_system_with(is_occluded, ray).unwrap_or_default()
+        });
+        if let Some(mut probe) = world.get_mut::<SunlightProbe>(entity) && probe.in_sunlight != in_sunlight {
+            probe.in_sunlight = in_sunlight;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RealisticSunDirectionPlugin;
+
+    #[test]
+    fn probes_behind_walls_are_shaded() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        // everything west of the origin is behind a wall
+        let system = app.world_mut().register_system(|In(ray): In<SunlightRay>| ray.origin.x < 0.0);
+        app.insert_resource(SunlightOcclusion(system));
+        let shaded = app.world_mut().spawn((SunlightProbe::default(), GlobalTransform::from_xyz(-1.0, 0.0, 0.0))).id();
+        let sunny = app.world_mut().spawn((SunlightProbe::default(), GlobalTransform::from_xyz(1.0, 0.0, 0.0))).id();
+        app.update();
+        assert!(!app.world().get::<SunlightProbe>(shaded).unwrap().in_sunlight);
+        assert!(app.world().get::<SunlightProbe>(sunny).unwrap().in_sunlight);
+        app.insert_resource(Environment::default().with_hours_since_noon(12.0));
+        app.update();
+        assert!(!app.world().get::<SunlightProbe>(sunny).unwrap().in_sunlight);
+    }
+}
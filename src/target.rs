@@ -0,0 +1,172 @@
+//! Contains the [`SunTarget`] component for choosing what a [`Sun`](crate::Sun) writes its
+//! direction to
+use bevy::prelude::*;
+use crate::UpdatedSunFilter;
+
+
+/// Chooses what a [`Sun`](crate::Sun) writes its direction to
+/// 
+/// By default a sun's own [`Transform`] is rotated, which is relative to its parent. For suns
+/// deep inside a hierarchy that moves or turns, like a light attached to a rotating skybox or a
+/// vehicle, use [`World`](SunTarget::World) to cancel out the parent's rotation, or
+/// [`DirectionOnly`](SunTarget::DirectionOnly) to leave the transform alone entirely.
+/// 
+/// ```no_run
+/// # use bevy::ecs::prelude::Commands;
+/// # use bevy::ecs::world::CommandQueue;
+/// # use bevy::prelude::World;
+/// # use kj_bevy_realistic_sun::{Sun, SunTarget};
+/// # let mut command_queue = CommandQueue::default();
+/// # let world = World::default();
+/// # let mut commands = Commands::new(&mut command_queue, &world);
+/// // Only publishes the direction, for a sky drawn by your own shader
+/// commands.spawn((
+///     Sun,
+///     SunTarget::DirectionOnly,
+/// ));
+/// ```
+/// 
+/// Every target also keeps a [`SunDirection`] on the entity up to date, which is added along with
+/// this component. For a single sun driving a shader uniform, the [`SunState`](crate::SunState)
+/// resource has the same direction without needing an entity at all.
+/// 
+/// **Note:** features that read the sun's local [`Transform`], like
+/// [`SunAppearance`](crate::SunAppearance) and [`FacesSun`](crate::FacesSun), expect it to point at
+/// the sun, so they should only be used with a parent that isn't rotated when the target is
+/// [`World`](SunTarget::World)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(SunDirection, TargetParentRotation)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SunTarget
+{
+    /// Rotates the sun's own [`Transform`], so a rotated parent turns the light with it
+    #[default]
+    Local,
+
+    /// Rotates the sun's [`Transform`] so that its world space rotation points at the sun, no
+    /// matter how its parent is rotated
+    /// 
+    /// The parent's [`GlobalTransform`] is read when the sun is updated, and the rotation is
+    /// corrected again in [`PostUpdate`] just before transforms are propagated, so a parent that
+    /// turned this frame, like a moving ship or camera rig, doesn't leave the light or its children
+    /// a frame behind
+    World,
+
+    /// Leaves the [`Transform`] alone and only updates the [`SunDirection`]
+    DirectionOnly,
+}
+
+impl SunTarget
+{
+    /// Local rotation for an entity with `parent_rotation` in world space, so that its world
+    /// space rotation is `rotation`
+    pub(crate) fn local_rotation(&self, rotation: Quat, parent_rotation: Option<Quat>) -> Option<Quat> {
+        match (self, parent_rotation) {
+            (SunTarget::Local, _) | (SunTarget::World, None) => Some(rotation),
+            (SunTarget::World, Some(parent_rotation)) => Some(parent_rotation.inverse() * rotation),
+            (SunTarget::DirectionOnly, _) => None,
+        }
+    }
+}
+
+/// Unit vector pointing *towards* the sun that a [`Sun`](crate::Sun) is following, in world space
+/// 
+/// Added automatically with a [`SunTarget`], and updated whenever the sun's direction changes.
+/// Includes everything that moves the sun, like a [`SunEnvironment`](crate::SunEnvironment) or
+/// [`SunOffset`](crate::SunOffset), so it can be copied into a material or shader uniform.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct SunDirection(pub Vec3);
+
+
+/// World space rotation of the parent that a [`SunTarget::World`] sun's rotation was last worked
+/// out against
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Component)]
+pub(crate) struct TargetParentRotation(pub(crate) Quat);
+
+/// Corrects [`SunTarget::World`] suns whose parent turned after they were updated, before
+/// transforms are propagated so the sun's children pick up the corrected rotation this frame
+pub(crate) fn compensate_parent_rotation(
+    mut suns: Query<(&SunTarget, &mut TargetParentRotation, &ChildOf, &mut Transform), UpdatedSunFilter>,
+    ancestors: Query<(&Transform, Option<&ChildOf>), Without<SunTarget>>,
+){
+    for (target, mut used_rotation, parent, mut transform) in &mut suns {
+        if *target != SunTarget::World {
+            continue;
+        }
+        let Some(parent_rotation) = world_rotation(parent.parent(), &ancestors) else {
+            continue;
+        };
+        if used_rotation.0 == parent_rotation {
+            continue;
+        }
+        transform.rotation = parent_rotation.inverse() * used_rotation.0 * transform.rotation;
+        used_rotation.0 = parent_rotation;
+    }
+}
+
+/// World space rotation of `entity` from the local [`Transform`]s up its hierarchy, since its
+/// [`GlobalTransform`] is still last frame's before propagation
+fn world_rotation(entity: Entity, ancestors: &Query<(&Transform, Option<&ChildOf>), Without<SunTarget>>) -> Option<Quat> {
+    let (transform, mut parent) = ancestors.get(entity).ok()?;
+    let mut rotation = transform.rotation;
+    while let Some(child_of) = parent {
+        let Ok((transform, grandparent)) = ancestors.get(child_of.parent()) else {
+            break;
+        };
+        rotation = transform.rotation * rotation;
+        parent = grandparent;
+    }
+    Some(rotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::transform::TransformPlugin;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun};
+
+    #[test]
+    fn world_and_direction_only_targets() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_hours_since_noon(-3.0);
+        app.insert_resource(environment);
+        let parent_rotation = Quat::from_rotation_y(1.0) * Quat::from_rotation_x(0.3);
+        let parent_transform = Transform::from_rotation(parent_rotation);
+        let parent = app.world_mut().spawn((parent_transform, GlobalTransform::from(parent_transform))).id();
+        let world_sun = app.world_mut().spawn((Sun, SunTarget::World, ChildOf(parent))).id();
+        let direction_sun = app.world_mut().spawn((Sun, SunTarget::DirectionOnly, ChildOf(parent))).id();
+        app.update();
+        let local = app.world().get::<Transform>(world_sun).unwrap().rotation;
+        let forward = parent_rotation * local * Vec3::NEG_Z;
+        assert!(forward.abs_diff_eq(-environment.sun_direction(), 1e-5), "{forward}");
+        assert_eq!(app.world().get::<Transform>(direction_sun).unwrap().rotation, Quat::IDENTITY);
+        let direction = app.world().get::<SunDirection>(direction_sun).unwrap().0;
+        assert!(direction.abs_diff_eq(environment.sun_direction(), 1e-5), "{direction}");
+    }
+
+    #[test]
+    fn follows_turning_parent() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, RealisticSunDirectionPlugin::default()));
+        let environment = Environment::default().with_hours_since_noon(2.0);
+        app.insert_resource(environment);
+        let ship = app.world_mut().spawn(Transform::default()).id();
+        let sun = app.world_mut().spawn((Sun, SunTarget::World, ChildOf(ship))).id();
+        let flare = app.world_mut().spawn((Transform::default(), ChildOf(sun))).id();
+        for turn in 0..4 {
+            let rotation = Quat::from_rotation_y(turn as f32 * 0.7);
+            app.world_mut().get_mut::<Transform>(ship).unwrap().rotation = rotation;
+            app.update();
+            let forward = app.world().get::<GlobalTransform>(sun).unwrap().forward();
+            assert!(forward.abs_diff_eq(-environment.sun_direction(), 1e-5), "{turn}: {forward}");
+            let flare_forward = app.world().get::<GlobalTransform>(flare).unwrap().forward();
+            assert!(flare_forward.abs_diff_eq(*forward, 1e-5), "{turn}: {flare_forward}");
+        }
+    }
+}
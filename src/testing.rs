@@ -0,0 +1,110 @@
+//! Reference cases for where the sun should be, for regression testing code that extends or
+//! replaces the sun math
+//!
+//! ```no_run
+//! use kj_bevy_realistic_sun::testing::{self, assert_sun_close};
+//! # fn my_sun_position(latitude: f32, date: f32, hours_since_noon: f32) -> (f32, f32) { (0.0, 0.0) }
+//! for case in testing::REFERENCE_CASES {
+//!     let (elevation_deg, azimuth_deg) = my_sun_position(case.latitude_deg, case.date, case.hours_since_noon);
+//!     assert_sun_close(elevation_deg, azimuth_deg, case);
+//! }
+//! ```
+//!
+//! Every case is checked against [`Environment`] in this crate's own tests, so they are the
+//! crate's canonical answers rather than a precise ephemeris
+use crate::Environment;
+use crate::conversion::*;
+
+
+/// Where the sun should be for a latitude, date and time, with [`Environment::AXIAL_TILT_EARTH`]
+/// and every other [`Environment`] value left at its default
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SunReferenceCase
+{
+    /// Latitude in degrees, positive to the north
+    pub latitude_deg: f32,
+    /// [`time_of_year`](Environment::time_of_year) in radians, one of the `DATE_` constants
+    pub date: f32,
+    /// Hours since local solar noon, negative in the morning
+    pub hours_since_noon: f32,
+    /// Expected angle of the sun above the horizon in degrees
+    pub elevation_deg: f32,
+    /// Expected compass direction of the sun in degrees, clockwise from north
+    pub azimuth_deg: f32,
+    /// Largest difference in degrees from the expected elevation and azimuth that still passes
+    pub tolerance_deg: f32,
+}
+
+impl SunReferenceCase
+{
+    /// Creates a new `SunReferenceCase` with the default tolerance of `0.05` degrees
+    pub const fn new(latitude_deg: f32, date: f32, hours_since_noon: f32, elevation_deg: f32, azimuth_deg: f32) -> Self {
+        Self { latitude_deg, date, hours_since_noon, elevation_deg, azimuth_deg, tolerance_deg: 0.05 }
+    }
+
+    /// Environment the case describes
+    pub fn environment(&self) -> Environment {
+        Environment::default()
+            .with_axial_tilt(Environment::AXIAL_TILT_EARTH)
+            .with_latitude_deg(self.latitude_deg)
+            .with_date(self.date)
+            .with_hours_since_noon(self.hours_since_noon)
+    }
+}
+
+/// The crate's canonical sun positions, covering both hemispheres, the solstices and equinoxes,
+/// sunrise, night and the midnight sun
+pub const REFERENCE_CASES: &[SunReferenceCase] = &[
+    SunReferenceCase::new(0.0, Environment::DATE_SUMMER, 0.0, 78.28, 0.0),
+    SunReferenceCase::new(45.0, Environment::DATE_SUMMER, 0.0, 56.72, 180.0),
+    SunReferenceCase::new(45.0, Environment::DATE_WINTER, 0.0, 33.28, 180.0),
+    SunReferenceCase::new(45.0, Environment::DATE_SPRING, -6.0, 0.0, 90.0),
+    SunReferenceCase::new(45.0, Environment::DATE_AUTUMN, 3.0, 30.0, 234.736),
+    SunReferenceCase::new(51.5, Environment::DATE_SPRING, -3.0, 26.116, 128.047),
+    SunReferenceCase::new(23.4, Environment::DATE_AUTUMN, -1.5, 57.983, 133.795),
+    SunReferenceCase::new(-33.9, Environment::DATE_WINTER, 2.0, 54.797, 301.868),
+    SunReferenceCase::new(-33.9, Environment::DATE_SUMMER, -4.0, 17.041, 62.488),
+    SunReferenceCase::new(-70.0, Environment::DATE_WINTER, 0.0, 31.72, 0.0),
+    SunReferenceCase::new(60.0, Environment::DATE_SUMMER, 9.0, -9.804, 315.362),
+    SunReferenceCase::new(80.0, Environment::DATE_SUMMER, 12.0, 1.72, 0.0),
+];
+
+/// Panics if an elevation and azimuth in degrees are further from `expected` than its tolerance
+///
+/// Azimuths are compared the shortest way around, so `359.99` and `0.0` are close. The azimuth
+/// isn't checked when the sun is within the tolerance of straight up or down, where it has no
+/// meaning.
+pub fn assert_sun_close(elevation_deg: f32, azimuth_deg: f32, expected: &SunReferenceCase) {
+    let elevation_error = (elevation_deg - expected.elevation_deg).abs();
+    let azimuth_error = ((azimuth_deg - expected.azimuth_deg + 180.0).rem_euclid(360.0) - 180.0).abs();
+    let overhead = 90.0 - expected.elevation_deg.abs() <= expected.tolerance_deg;
+    assert!(
+        elevation_error <= expected.tolerance_deg && (overhead || azimuth_error <= expected.tolerance_deg),
+        "sun at elevation {elevation_deg:.3}° azimuth {azimuth_deg:.3}°, expected {expected:?}",
+    );
+}
+
+/// Checks the sun in `environment` against `expected` with [`assert_sun_close`]
+pub fn assert_environment_close(environment: &Environment, expected: &SunReferenceCase) {
+    let (elevation, azimuth) = environment.sun_horizontal();
+    assert_sun_close(elevation * RAD_TO_DEG, azimuth * RAD_TO_DEG, expected);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_matches_reference_cases() {
+        for case in REFERENCE_CASES {
+            assert_environment_close(&case.environment(), case);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn catches_wrong_azimuth() {
+        assert_sun_close(56.72, 0.0, &REFERENCE_CASES[1]);
+    }
+}
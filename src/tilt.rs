@@ -0,0 +1,188 @@
+//! Contains the [`TiltAnimation`] resource that changes the axial tilt over the years
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::Environment;
+use crate::conversion::*;
+
+
+/// Changes the tilt of the planet's axis as the years go by, for wobbling or fictional planets
+///
+/// While this resource exists, the [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin)
+/// counts the years passing in the [`Environment`]'s time of year and sets the tilt from the
+/// [`curve`](TiltAnimation::curve) with [`Environment::with_obliquity`], so tilts past `90`
+/// degrees turn the planet over instead of breaking the sun's path.
+///
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::TiltAnimation;
+/// # let mut app = App::new();
+/// // Wobbles between 10 and 50 degrees every 3 years
+/// app.insert_resource(TiltAnimation::oscillator_deg(30.0, 20.0, 3.0));
+/// // Rolls right over over the course of 10 years, then back again
+/// app.insert_resource(TiltAnimation::keyframes_deg([(0.0, 20.0), (5.0, 160.0), (10.0, 20.0)]));
+/// ```
+///
+/// **Note:** the animation overwrites the [`axial_tilt`](Environment::axial_tilt) and
+/// [`rotation_direction`](Environment::rotation_direction) every time it changes them, so set the
+/// tilt through the curve instead of the environment while it runs
+#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct TiltAnimation
+{
+    /// Tilt of the axis over time
+    pub curve: TiltCurve,
+
+    /// Years into the curve, moved along with the time of year
+    pub years: f32,
+}
+
+impl TiltAnimation
+{
+    /// Creates a new `TiltAnimation` that swings `amplitude` radians either side of `mean` every
+    /// `period` years
+    pub const fn oscillator(mean: f32, amplitude: f32, period: f32) -> Self {
+        Self { curve: TiltCurve::Oscillator { mean, amplitude, period }, years: 0.0 }
+    }
+
+    /// Creates a new `TiltAnimation` that swings `amplitude` degrees either side of `mean` every
+    /// `period` years
+    pub const fn oscillator_deg(mean: f32, amplitude: f32, period: f32) -> Self {
+        Self::oscillator(mean * DEG_TO_RAD, amplitude * DEG_TO_RAD, period)
+    }
+
+    /// Creates a new `TiltAnimation` that blends between tilts in radians at given years
+    pub fn keyframes(keyframes: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        Self { curve: TiltCurve::Keyframes(keyframes.into_iter().collect()), years: 0.0 }
+    }
+
+    /// Creates a new `TiltAnimation` that blends between tilts in degrees at given years
+    pub fn keyframes_deg(keyframes: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        Self::keyframes(keyframes.into_iter().map(|(year, tilt)| (year, tilt * DEG_TO_RAD)))
+    }
+
+    /// Sets how many years into the curve the animation starts
+    pub const fn with_years(mut self, years: f32) -> Self {
+        self.years = years;
+        self
+    }
+
+    /// Tilt of the axis in radians at the current point in the curve, which can be past `90`
+    /// degrees, see [`Environment::with_obliquity`]
+    pub fn obliquity(&self) -> f32 {
+        self.curve.sample(self.years)
+    }
+}
+
+/// Shape of a [`TiltAnimation`]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TiltCurve
+{
+    /// Swings smoothly back and forth
+    Oscillator {
+        /// Tilt in the middle of the swing, in radians
+        mean: f32,
+        /// Radians the tilt swings either side of the mean
+        amplitude: f32,
+        /// Years for one full swing, or `0.0` to hold at the mean
+        period: f32,
+    },
+
+    /// Blends in a straight line between `(years, tilt)` pairs in order, with the tilt in radians.
+    /// Loops back to the start after the last keyframe
+    Keyframes(Vec<(f32, f32)>),
+}
+
+impl Default for TiltCurve {
+    /// Earth's tilt, holding still
+    fn default() -> Self {
+        Self::Oscillator { mean: Environment::AXIAL_TILT_EARTH, amplitude: 0.0, period: 0.0 }
+    }
+}
+
+impl TiltCurve
+{
+    /// Tilt in radians `years` into the curve
+    pub fn sample(&self, years: f32) -> f32 {
+        match self {
+            Self::Oscillator { mean, amplitude, period } => {
+                if *period == 0.0 {
+                    return *mean;
+                }
+                mean + amplitude * (years / period * TAU).sin()
+            },
+            Self::Keyframes(keyframes) => {
+                let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+                    return 0.0;
+                };
+                let length = last.0 - first.0;
+                let years = if length > 0.0 { first.0 + (years - first.0).rem_euclid(length) } else { first.0 };
+                let next = keyframes.partition_point(|(year, _)| *year <= years).clamp(1, keyframes.len());
+                let (from, to) = (keyframes[next - 1], keyframes.get(next).copied().unwrap_or(keyframes[next - 1]));
+                let t = if to.0 > from.0 { (years - from.0) / (to.0 - from.0) } else { 0.0 };
+                from.1 + (to.1 - from.1) * t
+            },
+        }
+    }
+}
+
+/// Moves the [`TiltAnimation`] along with the time of year and writes its tilt into the
+/// [`Environment`]
+pub(crate) fn animate_axial_tilt(
+    mut animation: ResMut<TiltAnimation>,
+    mut environment: ResMut<Environment>,
+    mut last_time_of_year: Local<Option<f32>>,
+){
+    let time_of_year = environment.time_of_year;
+    if let Some(last) = last_time_of_year.replace(time_of_year) {
+        // shortest way around, so the year wrapping doesn't count as a whole year passing
+        let delta = wrap_angle(time_of_year - last);
+        if delta != 0.0 {
+            animation.years += delta / TAU;
+        }
+    }
+    if !(animation.is_changed() || environment.is_changed()) {
+        return;
+    }
+    let tilted = environment.with_obliquity(animation.obliquity());
+    if tilted.axial_tilt != environment.axial_tilt || tilted.rotation_direction != environment.rotation_direction {
+        *environment = tilted;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use crate::{RealisticSunDirectionPlugin, RotationDirection};
+
+    #[test]
+    fn keyframes_blend_and_loop() {
+        let curve = TiltCurve::Keyframes(vec![(0.0, 0.0), (2.0, 1.0), (4.0, 0.0)]);
+        assert_eq!(curve.sample(1.0), 0.5);
+        assert_eq!(curve.sample(3.0), 0.5);
+        assert_eq!(curve.sample(5.0), 0.5);
+        assert_eq!(curve.sample(-1.0), 0.5);
+    }
+
+    #[test]
+    fn tips_over_past_90_degrees() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(TiltAnimation::keyframes_deg([(0.0, 60.0), (1.0, 180.0)]));
+        app.update();
+        assert!((app.world().resource::<Environment>().axial_tilt.to_degrees() - 60.0).abs() < 1e-3);
+        // three eighths of a year later
+        app.world_mut().resource_mut::<Environment>().time_of_year += PI * 0.75;
+        app.update();
+        let environment = *app.world().resource::<Environment>();
+        assert!((environment.obliquity().to_degrees() - 105.0).abs() < 1e-3, "{}", environment.obliquity());
+        assert!((environment.axial_tilt.to_degrees() - 75.0).abs() < 1e-3);
+        assert_eq!(environment.rotation_direction, RotationDirection::Retrograde);
+        assert!(environment.sun_direction().is_finite());
+    }
+}
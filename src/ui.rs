@@ -0,0 +1,140 @@
+//! Helpers for showing the [`Environment`] to players, with formatting for clock times, seasons and
+//! compass directions and a ready made [`sun_info_overlay`] for debugging
+//! 
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use kj_bevy_realistic_sun::Environment;
+//! use kj_bevy_realistic_sun::ui;
+//! fn status_bar(environment: Res<Environment>) -> String {
+//!     format!(
+//!         "{} - {} - sun in the {}",
+//!         ui::clock(&environment),
+//!         ui::season_name(environment.season()),
+//!         ui::compass_point(environment.solar_azimuth()),
+//!     )
+//! }
+//! ```
+//! 
+//! Only available with the `ui` feature
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::{CinematicOverrides, Environment, Season};
+use crate::conversion::*;
+
+
+/// Names of the 16 compass points, clockwise from north
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+    "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+];
+
+/// The environment's [`time_of_day`](Environment::time_of_day) as a 24 hour clock time, like
+/// `"14:32"`
+/// 
+/// See [`format_time_of_day`] for how the time is rounded
+pub fn clock(environment: &Environment) -> String {
+    format_time_of_day(environment.time_of_day)
+}
+
+/// Name of a season, like `"Autumn"`
+pub const fn season_name(season: Season) -> &'static str {
+    match season {
+        Season::Spring => "Spring",
+        Season::Summer => "Summer",
+        Season::Autumn => "Autumn",
+        Season::Winter => "Winter",
+    }
+}
+
+/// Nearest of the 16 compass points to a compass `azimuth` in radians, like `"WSW"`
+/// 
+/// Azimuth is measured clockwise from north, the same as
+/// [`Environment::solar_azimuth`]
+pub fn compass_point(azimuth: f32) -> &'static str {
+    let index = (azimuth.rem_euclid(TAU) / TAU * 16.0).round() as usize % 16;
+    COMPASS_POINTS[index]
+}
+
+/// Marks a [`Text`] that shows the time, season and where the sun is, kept up to date by the
+/// [`RealisticSunDirectionPlugin`](crate::RealisticSunDirectionPlugin)
+/// 
+/// Spawned as part of a [`sun_info_overlay`], or add it to a text of your own
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(Text)]
+pub struct SunInfoText;
+
+/// A small panel in the top left corner of the screen showing the time, season and where the sun
+/// is, for debugging
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::ui;
+/// fn spawn_overlay(mut commands: Commands) {
+///     commands.spawn(ui::sun_info_overlay());
+/// }
+/// ```
+pub fn sun_info_overlay() -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.0),
+            top: Val::Px(0.0),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(5.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        children![(SunInfoText, TextFont { font_size: 13.0, ..default() })],
+    )
+}
+
+/// Text shown by a [`SunInfoText`] for `environment`
+fn sun_info(environment: &Environment) -> String {
+    format!(
+        "{} {}\nSun {:.0}° {}",
+        clock(environment),
+        season_name(environment.season()),
+        environment.solar_elevation_deg(),
+        compass_point(environment.solar_azimuth()),
+    )
+}
+
+/// Keeps every [`SunInfoText`] up to date with the [`Environment`]
+pub(crate) fn update_sun_info_text(
+    mut texts: Query<(&mut Text, Ref<SunInfoText>)>,
+    environment: Res<Environment>,
+    cinematic_overrides: Res<CinematicOverrides>,
+){
+    let globals_changed = environment.is_changed() || cinematic_overrides.is_changed();
+    let mut info = None;
+    for (mut text, marker) in &mut texts {
+        if !(globals_changed || marker.is_added()) {
+            continue;
+        }
+        let info = info.get_or_insert_with(|| sun_info(&cinematic_overrides.resolve(&environment)));
+        if text.0 != *info {
+            text.0.clone_from(info);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_values() {
+        assert_eq!(compass_point(0.0), "N");
+        assert_eq!(compass_point(TAU - 0.01), "N");
+        assert_eq!(compass_point(247.5 * DEG_TO_RAD), "WSW");
+        assert_eq!(compass_point(-90.0 * DEG_TO_RAD), "W");
+        let environment = Environment::default()
+            .with_latitude_deg(40.0)
+            .with_date(Environment::DATE_SUMMER + 0.1)
+            .with_hours_since_noon(2.5);
+        assert_eq!(clock(&environment), "14:30");
+        assert_eq!(season_name(environment.season()), "Summer");
+    }
+}
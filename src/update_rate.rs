@@ -0,0 +1,187 @@
+//! Contains the [`SunUpdateRate`] resource for updating suns less often than every frame
+use bevy::prelude::*;
+use crate::{UpdatedSunFilter, is_hidden};
+
+
+/// How often [`Sun`](crate::Sun) lights are pointed at the sun, for battery constrained builds like web and
+/// mobile where working out the sun every frame isn't worth it
+/// 
+/// ```no_run
+/// # use bevy::app::App;
+/// # use kj_bevy_realistic_sun::SunUpdateRate;
+/// # let mut app = App::new();
+/// // Works out the sun 4 times a second
+/// app.insert_resource(SunUpdateRate::EverySeconds(0.25));
+/// ```
+/// 
+/// Between updates each sun is turned smoothly from the last direction it was given to the newest
+/// one, so the light keeps moving every frame. This means suns trail the [`Environment`](crate::Environment)
+/// by up to one update, and a newly spawned sun waits for the next update before it points
+/// anywhere.
+/// 
+/// Without this resource suns are updated every frame, the same as
+/// [`EveryFrame`](SunUpdateRate::EveryFrame)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SunUpdateRate
+{
+    /// Update every frame, without any interpolation
+    #[default]
+    EveryFrame,
+    /// Update once every `n` frames
+    EveryNFrames(u32),
+    /// Update once every `s` seconds
+    EverySeconds(f32),
+}
+
+/// Whether the suns are due an update this frame, and how far through the current interval it is
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Resource)]
+pub(crate) struct SunUpdateTimer
+{
+    /// Frames or seconds since the last update, or `None` before the first one
+    since_update: Option<f32>,
+    due: bool,
+    progress: f32,
+    interpolating: bool,
+}
+
+/// Works out whether the suns are due an update this frame
+pub(crate) fn tick_sun_update_rate(
+    rate: Option<Res<SunUpdateRate>>,
+    mut timer: ResMut<SunUpdateTimer>,
+    time: Option<Res<Time>>,
+){
+    let (step, interval) = match rate.as_deref().copied().unwrap_or_default() {
+        SunUpdateRate::EveryFrame => {
+            *timer = SunUpdateTimer { since_update: None, due: true, progress: 1.0, interpolating: false };
+            return;
+        },
+        SunUpdateRate::EveryNFrames(frames) => (1.0, frames.max(1) as f32),
+        SunUpdateRate::EverySeconds(seconds) => {
+            let interval = seconds.max(f32::EPSILON);
+            // without a clock every frame counts as a whole interval
+            (time.map_or(interval, |time| time.delta_secs()), interval)
+        },
+    };
+    timer.interpolating = true;
+    let since_update = match timer.since_update {
+        Some(since_update) => since_update + step,
+        None => interval,
+    };
+    timer.due = since_update >= interval;
+    let since_update = if timer.due { (since_update - interval).min(interval) } else { since_update };
+    timer.since_update = Some(since_update);
+    // reaches 1.0 on the frame before the next update
+    timer.progress = match rate.as_deref() {
+        Some(SunUpdateRate::EveryNFrames(_)) => (since_update + 1.0) / interval,
+        _ => since_update / interval,
+    }.min(1.0);
+}
+
+/// Run condition for systems that only run when the suns are due an update
+pub(crate) fn sun_update_due(timer: Res<SunUpdateTimer>) -> bool {
+    timer.due
+}
+
+/// Run condition for [`interpolate_sun_updates`]
+pub(crate) fn sun_update_interpolating(timer: Res<SunUpdateTimer>) -> bool {
+    timer.interpolating
+}
+
+/// Rotation a [`Sun`](crate::Sun) was last shown at, and the two updates it's being turned between
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub(crate) struct SunUpdateInterpolation
+{
+    shown: Quat,
+    from: Quat,
+    to: Quat,
+}
+
+/// Turns suns from their previous update towards the newest one written by
+/// [`update_sun_lights`](crate::update_sun_lights), skipping the same hidden and disabled suns it
+/// does
+pub(crate) fn interpolate_sun_updates(
+    mut commands: Commands,
+    mut suns: Query<(Entity, &mut Transform, Option<&mut SunUpdateInterpolation>), UpdatedSunFilter>,
+    visibilities: Query<(Option<&Visibility>, Option<&ChildOf>)>,
+    timer: Res<SunUpdateTimer>,
+){
+    for (entity, mut transform, state) in &mut suns {
+        if is_hidden(entity, &visibilities) {
+            continue;
+        }
+        let Some(mut state) = state else {
+            let rotation = transform.rotation;
+            commands.entity(entity).insert(SunUpdateInterpolation { shown: rotation, from: rotation, to: rotation });
+            continue;
+        };
+        if timer.due {
+            state.from = state.shown;
+            state.to = transform.rotation;
+        }
+        let rotation = state.from.slerp(state.to, timer.progress);
+        state.shown = rotation;
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin, Sun, SunDisabled};
+
+    #[test]
+    fn interpolates_between_updates() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(SunUpdateRate::EveryNFrames(4));
+        app.insert_resource(Environment::default().with_hours_since_noon(-3.0));
+        let sun = app.world_mut().spawn(Sun).id();
+        app.update();
+        let morning = app.world().get::<Transform>(sun).unwrap().rotation;
+        app.insert_resource(Environment::default().with_hours_since_noon(3.0));
+        let afternoon = Transform::default()
+            .looking_to(-Environment::default().with_hours_since_noon(3.0).sun_direction(), Vec3::Y)
+            .rotation;
+        let mut angles = Vec::new();
+        for _ in 0..8 {
+            app.update();
+            angles.push(app.world().get::<Transform>(sun).unwrap().rotation.angle_between(afternoon));
+        }
+        let jump = morning.angle_between(afternoon);
+        // nothing moves until the next update, 4 frames after the first
+        assert!(angles[..3].iter().all(|angle| (angle - jump).abs() < 1e-4), "{:?}", angles);
+        // then the sun turns over the following 4 frames
+        assert!(angles[3] < jump && angles[4] < angles[3], "{:?}", angles);
+        assert!(angles[6] < 1e-4 && angles[7] < 1e-4, "{:?}", angles);
+    }
+
+    #[test]
+    fn leaves_disabled_suns_alone() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(SunUpdateRate::EveryNFrames(4));
+        let sun = app.world_mut().spawn((Sun, SunDisabled)).id();
+        for _ in 0..5 {
+            app.update();
+        }
+        // the game points disabled suns itself, between two updates
+        let rotation = Quat::from_rotation_x(-1.0);
+        app.world_mut().get_mut::<Transform>(sun).unwrap().rotation = rotation;
+        app.update();
+        let changed = app.world().entity(sun).get_change_ticks::<Transform>().unwrap().changed;
+        for _ in 0..4 {
+            app.insert_resource(Environment::default().with_hours_since_noon(3.0));
+            app.update();
+        }
+        assert_eq!(app.world().get::<Transform>(sun).unwrap().rotation, rotation);
+        assert_eq!(app.world().entity(sun).get_change_ticks::<Transform>().unwrap().changed, changed);
+    }
+}
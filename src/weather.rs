@@ -0,0 +1,168 @@
+//! Contains the [`SkyModifier`] resource that lets weather dim and soften the sun
+use bevy::color::Mix;
+use bevy::prelude::*;
+use crate::Sun;
+
+
+/// Dims and softens every [`Sun`] for cloudy or hazy weather
+/// 
+/// While this resource exists, the illuminance of every sun's
+/// [`DirectionalLight`](https://docs.rs/bevy/0.17.3/bevy/light/struct.DirectionalLight.html) is
+/// scaled down and its color is washed out towards white, on top of whatever
+/// [`SunAppearance`](crate::SunAppearance), [`IlluminanceCurve`](crate::IlluminanceCurve) or
+/// [`SolarEclipse`](crate::SolarEclipse) set it to. Removing the resource puts the lights back.
+/// 
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use kj_bevy_realistic_sun::SkyModifier;
+/// # #[derive(Resource)]
+/// # struct Weather { clouds: f32, fog: f32 }
+/// // A weather system only needs to fill these in, and the sun plugin does the rest
+/// fn apply_weather(weather: Res<Weather>, mut sky: ResMut<SkyModifier>) {
+///     sky.cloud_cover = weather.clouds;
+///     sky.haze = weather.fog;
+/// }
+/// ```
+/// 
+/// **Note:** only the lights are changed. Gameplay values like
+/// [`Environment::irradiance_on`](crate::Environment::irradiance_on) and the [`SunState`](crate::SunState)
+/// ignore the weather, use [`illuminance_scale`](SkyModifier::illuminance_scale) to account for it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(default))]
+pub struct SkyModifier
+{
+    /// How much of the sky is covered by cloud, from `0.0` for clear skies to `1.0` for fully
+    /// overcast
+    pub cloud_cover: f32,
+
+    /// How hazy or foggy the air is, from `0.0` for clear air to `1.0` for thick haze
+    pub haze: f32,
+}
+
+impl SkyModifier
+{
+    /// Fraction of the sunlight blocked by fully overcast skies
+    pub const OVERCAST_ATTENUATION: f32 = 0.9;
+
+    /// Fraction of the sunlight blocked by the thickest haze
+    pub const HAZE_ATTENUATION: f32 = 0.4;
+
+    /// Sets the cloud cover, from `0.0` to `1.0`
+    pub const fn with_cloud_cover(mut self, cloud_cover: f32) -> Self {
+        self.cloud_cover = cloud_cover;
+        self
+    }
+
+    /// Sets the haze, from `0.0` to `1.0`
+    pub const fn with_haze(mut self, haze: f32) -> Self {
+        self.haze = haze;
+        self
+    }
+
+    /// Multiplier for the sun's illuminance, `1.0` for clear skies
+    pub fn illuminance_scale(&self) -> f32 {
+        let clouds = self.cloud_cover.clamp(0.0, 1.0) * Self::OVERCAST_ATTENUATION;
+        let haze = self.haze.clamp(0.0, 1.0) * Self::HAZE_ATTENUATION;
+        (1.0 - clouds) * (1.0 - haze)
+    }
+
+    /// Washes `color` out towards white, more for thicker clouds and haze
+    pub fn soften_color(&self, color: Color) -> Color {
+        let softening = (self.cloud_cover.clamp(0.0, 1.0) + self.haze.clamp(0.0, 1.0) * 0.5).min(1.0);
+        LinearRgba::from(color).mix(&LinearRgba::WHITE, softening).into()
+    }
+}
+
+/// Illuminance and color a [`Sun`] had before the [`SkyModifier`] changed them, and the values
+/// written to it
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Component)]
+pub(crate) struct SkyModified
+{
+    base_illuminance: f32,
+    base_color: Color,
+    illuminance: f32,
+    color: Color,
+}
+
+/// Puts every modified [`Sun`] back how it was before the [`SkyModifier`] changed it, so the
+/// systems that set the light's illuminance and color don't see the weather as their starting
+/// point and the modifier isn't applied twice
+pub(crate) fn restore_sky_modified(mut suns: Query<(&mut DirectionalLight, &SkyModified)>) {
+    for (mut light, modified) in &mut suns {
+        let light = light.bypass_change_detection();
+        if light.illuminance == modified.illuminance {
+            light.illuminance = modified.base_illuminance;
+        }
+        if light.color == modified.color {
+            light.color = modified.base_color;
+        }
+    }
+}
+
+/// Applies the [`SkyModifier`] to every [`Sun`], after everything else has set their lights
+pub(crate) fn apply_sky_modifier(
+    mut commands: Commands,
+    mut suns: Query<(Entity, &mut DirectionalLight, Option<&mut SkyModified>), With<Sun>>,
+    modifier: Option<Res<SkyModifier>>,
+){
+    for (entity, mut light, previous) in &mut suns {
+        let Some(modifier) = &modifier else {
+            if previous.is_some() {
+                // already put back by `restore_sky_modified`, without change detection
+                light.set_changed();
+                commands.entity(entity).remove::<SkyModified>();
+            }
+            continue;
+        };
+        let modified = SkyModified {
+            base_illuminance: light.illuminance,
+            base_color: light.color,
+            illuminance: light.illuminance * modifier.illuminance_scale(),
+            color: modifier.soften_color(light.color),
+        };
+        // nothing changed since last frame, so quietly undo `restore_sky_modified`
+        let unchanged = previous.as_deref() == Some(&modified) && !light.is_changed();
+        match previous {
+            Some(mut previous) => *previous = modified,
+            None => {
+                commands.entity(entity).insert(modified);
+            },
+        }
+        let light = if unchanged { light.bypass_change_detection() } else { light.as_mut() };
+        light.illuminance = modified.illuminance;
+        light.color = modified.color;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Environment, RealisticSunDirectionPlugin};
+
+    #[test]
+    fn dims_and_restores() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RealisticSunDirectionPlugin::default()));
+        app.insert_resource(Environment::default());
+        let color = Color::srgb(1.0, 0.5, 0.2);
+        let sun = app.world_mut().spawn((DirectionalLight { illuminance: 100.0, color, ..default() }, Sun)).id();
+        app.update();
+        app.insert_resource(SkyModifier::default().with_cloud_cover(1.0));
+        for _ in 0..3 {
+            app.update();
+        }
+        let light = app.world().get::<DirectionalLight>(sun).unwrap();
+        assert!((light.illuminance - 10.0).abs() < 1e-3, "{}", light.illuminance);
+        let softened = light.color.to_linear();
+        assert!(softened.red.min(softened.green).min(softened.blue) > 0.9999, "{softened:?}");
+        app.world_mut().remove_resource::<SkyModifier>();
+        app.update();
+        let light = app.world().get::<DirectionalLight>(sun).unwrap();
+        assert_eq!(light.illuminance, 100.0);
+        assert_eq!(light.color, color);
+    }
+}